@@ -0,0 +1,127 @@
+//! 蓝牙子系统（对应 LicheeRV aic_bsp_driver.c aicbt_init / aicbt_patch_table_load）
+//!
+//! BT 固件（bt_adid、bt_patch、bt_ext_patch、bt_table）在 aicbsp_driver_fw_init 中与 WiFi 固件一起加载，
+//! AICBT_MODE_T 段的取值按 AicBtInfo 覆盖（btmode、btport、UART 参数等）。
+//! HCI 走板级 UART（AICBT_BTPORT_UART）：vendor SDIO 帧类型只到 0x13 PRINT，没有承载 HCI 的配置帧，
+//! 因此本模块只负责 BT 上电与固件加载，不提供 HCI 收发；HCI 传输由板级 UART 驱动与上层 BT 协议栈自行对接。
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use spin::Mutex;
+
+use crate::patch_table::{PatchOp, PatchSectionType, PatchTable};
+
+/// BT 工作模式（AICBT_BTMODE_*）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AicBtMode {
+    BtOnlySw = 0,
+    BtWifiCombo = 1,
+    BtOnly = 2,
+    BtOnlyTest = 3,
+    BtWifiComboTest = 4,
+}
+
+/// HCI 承载方式（AICBT_BTPORT_*）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AicBtPort {
+    Null = 0,
+    /// 固件侧 mailbox（AICBT_BTPORT_MB），本驱动不提供对应的 HCI 传输
+    Sdio = 1,
+    Uart = 2,
+}
+
+/// aicbt_info_t：写入 AICBT_MODE_T 段的 BT 配置
+#[derive(Debug, Clone, Copy)]
+pub struct AicBtInfo {
+    pub btmode: AicBtMode,
+    pub btport: AicBtPort,
+    pub uart_baud: u32,
+    pub uart_flowctrl: bool,
+    pub lpm_enable: bool,
+    /// None 时保留 patch 表中的芯片默认发射功率
+    pub txpwr_lvl: Option<u32>,
+}
+
+impl AicBtInfo {
+    /// LicheeRV 默认值：combo、UART 1.5M、流控开、LPM 关
+    pub const fn default_const() -> Self {
+        Self {
+            btmode: AicBtMode::BtWifiCombo,
+            btport: AicBtPort::Uart,
+            uart_baud: 1_500_000,
+            uart_flowctrl: true,
+            lpm_enable: false,
+            txpwr_lvl: None,
+        }
+    }
+}
+
+impl Default for AicBtInfo {
+    fn default() -> Self {
+        Self::default_const()
+    }
+}
+
+static BT_INFO: Mutex<AicBtInfo> = Mutex::new(AicBtInfo::default_const());
+/// 本次 driver_fw_init 是否已加载 BT 固件与 patch 表
+static BT_FW_READY: AtomicBool = AtomicBool::new(false);
+
+/// 设置 BT 配置，下次 driver_fw_init 生效（须在 aicbsp_set_subsys 上电前调用）
+pub fn aicbsp_set_bt_info(info: AicBtInfo) {
+    *BT_INFO.lock() = info;
+}
+
+pub fn aicbsp_get_bt_info() -> AicBtInfo {
+    *BT_INFO.lock()
+}
+
+/// BT 固件已加载（芯片未禁用 BT、固件齐全且非 RF 测试模式）
+pub fn aicbsp_bt_ready() -> bool {
+    BT_FW_READY.load(Ordering::Acquire)
+}
+
+pub(crate) fn set_bt_fw_ready(ready: bool) {
+    BT_FW_READY.store(ready, Ordering::Release);
+}
+
+/// AICBT_MODE_T 段取值（aicbt_patch_table_load 按对序号覆盖 value）
+pub(crate) struct BtModeCfg {
+    hwinfo: i32,
+    cpmode: u8,
+    bt: AicBtInfo,
+}
+
+impl BtModeCfg {
+    pub(crate) fn new(hwinfo: i32, cpmode: u8) -> Self {
+        Self {
+            hwinfo,
+            cpmode,
+            bt: aicbsp_get_bt_info(),
+        }
+    }
+
+    /// 依次为 hwinfo<0、hwinfo、cpmode、btmode、btport、uart_baud、uart_flowctrl、lpm_enable、txpwr_lvl
+    pub(crate) fn apply(&self, table: &mut PatchTable) {
+        let Some(sec) = table.sections.iter_mut().find(|s| s.ty == PatchSectionType::BtMode) else {
+            return;
+        };
+        let values = [
+            Some((self.hwinfo < 0) as u32),
+            Some(self.hwinfo as u32),
+            Some(self.cpmode as u32),
+            Some(self.bt.btmode as u32),
+            Some(self.bt.btport as u32),
+            Some(self.bt.uart_baud),
+            Some(self.bt.uart_flowctrl as u32),
+            Some(self.bt.lpm_enable as u32),
+            self.bt.txpwr_lvl,
+        ];
+        for (op, v) in sec.ops.iter_mut().zip(values) {
+            if let (PatchOp::Write { value, .. }, Some(v)) = (op, v) {
+                *value = v;
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! 芯片与固件版本信息（对应 LicheeRV aicbsp_info.chip_rev 与 rwnx_hw->version_cfm / rwnx_set_vers）
+//!
+//! driver_fw_init 读 chip_rev 后记录 ProductId、chip_rev、is_chip_id_h；FDRV 在固件启动后发 MM_VERSION_REQ，
+//! 解码结果经 aicbsp_set_fw_version 存入同一 ChipInfo。aicbsp_chip_info 的 Display 输出可直接贴进问题报告。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use spin::Mutex;
+
+use crate::sdio::ProductId;
+
+/// enum mm_features 各位（MM_FEAT_*_BIT）名称，下标即位号
+const MM_FEATURE_NAMES: [&str; 32] = [
+    "bcn", "autobcn", "hwscan", "cmon", "mrole", "radar", "ps", "uapsd", "dpsm", "ampdu", "amsdu", "chnl_ctxt",
+    "reord", "p2p", "p2p_go", "umac", "vht", "bfmee", "bfmer", "wapi", "mfp", "mu_mimo_rx", "mu_mimo_tx", "mesh",
+    "tdls", "ant_div", "uf", "amsdu_max_size0", "amsdu_max_size1", "mon_data", "he", "twt",
+];
+
+/// MM_VERSION_CFM 解码结果（struct mm_version_cfm）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FwVersion {
+    pub version_lmac: u32,
+    pub version_machw_1: u32,
+    pub version_machw_2: u32,
+    pub version_phy_1: u32,
+    pub version_phy_2: u32,
+    /// mm_features 位图
+    pub features: u32,
+    pub max_sta_nb: u16,
+    pub max_vif_nb: u8,
+}
+
+impl FwVersion {
+    /// 与 rwnx_set_vers 的 wiphy->fw_version 相同："a.b.c.d"（version_lmac 各字节，高位在前）
+    pub fn fw_version_string(&self) -> String {
+        let b = self.version_lmac.to_be_bytes();
+        alloc::format!("{}.{}.{}.{}", b[0], b[1], b[2], b[3])
+    }
+
+    /// 第 bit 位特性（MM_FEAT_*_BIT）是否支持
+    pub fn has_feature(&self, bit: u32) -> bool {
+        bit < 32 && self.features & (1 << bit) != 0
+    }
+
+    /// 已置位特性的名称
+    pub fn feature_names(&self) -> Vec<&'static str> {
+        (0..32).filter(|&bit| self.has_feature(bit)).map(|bit| MM_FEATURE_NAMES[bit as usize]).collect()
+    }
+}
+
+/// 当前芯片信息；fw_version 在 FDRV 查询 MM_VERSION 前为 None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChipInfo {
+    pub product_id: ProductId,
+    pub chip_rev: u8,
+    pub is_chip_id_h: bool,
+    pub fw_version: Option<FwVersion>,
+}
+
+impl fmt::Display for ChipInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} chip_rev=0x{:02x} chip_id_h={}", self.product_id, self.chip_rev, self.is_chip_id_h)?;
+        let Some(v) = &self.fw_version else {
+            return write!(f, " fw=unknown");
+        };
+        write!(
+            f,
+            " fw={} machw=0x{:08x}/0x{:08x} phy=0x{:08x}/0x{:08x} max_sta={} max_vif={} features=0x{:08x} [",
+            v.fw_version_string(),
+            v.version_machw_1,
+            v.version_machw_2,
+            v.version_phy_1,
+            v.version_phy_2,
+            v.max_sta_nb,
+            v.max_vif_nb,
+            v.features
+        )?;
+        for (i, name) in v.feature_names().iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", name)?;
+        }
+        write!(f, "]")
+    }
+}
+
+static CHIP_INFO: Mutex<Option<ChipInfo>> = Mutex::new(None);
+
+/// driver_fw_init 读出 chip_rev 后记录（清空旧的固件版本）
+pub(crate) fn set_chip_ident(product_id: ProductId, chip_rev: u8, is_chip_id_h: bool) {
+    *CHIP_INFO.lock() = Some(ChipInfo {
+        product_id,
+        chip_rev,
+        is_chip_id_h,
+        fw_version: None,
+    });
+}
+
+/// sdio_exit 时清空
+pub(crate) fn clear_chip_info() {
+    *CHIP_INFO.lock() = None;
+}
+
+/// FDRV 收到 MM_VERSION_CFM 后调用；尚未记录芯片信息时忽略
+pub fn aicbsp_set_fw_version(version: FwVersion) {
+    if let Some(info) = CHIP_INFO.lock().as_mut() {
+        info.fw_version = Some(version);
+    }
+}
+
+/// 当前芯片信息；未完成 driver_fw_init 时为 None
+pub fn aicbsp_chip_info() -> Option<ChipInfo> {
+    *CHIP_INFO.lock()
+}
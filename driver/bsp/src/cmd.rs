@@ -1,9 +1,19 @@
 //! 命令管理 — 原原本本照抄 LicheeRV aic_bsp_driver.c/h 中 rwnx_cmd_mgr、cmd_mgr_queue、cmd_mgr_msgind、cmd_complete
 
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicU32, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use spin::Mutex;
+
 /// IPC E2A 消息参数大小
 pub const IPC_E2A_MSG_PARAM_SIZE: usize = 256;
 
-/// E2A 确认消息最大长度（用于存储 cfm 的 param）
+/// E2A 确认消息常见最大长度（LicheeRV rwnx_cmd.e2a_msg 缓冲大小）。
+/// 本实现的 CFM 存于堆上、不再按此截断，此值仅作调用方栈上缓冲的默认大小。
 pub const RWNX_CMD_E2AMSG_LEN_MAX: usize = 256;
 
 /// App 到 Emb 的消息
@@ -41,8 +51,9 @@ pub struct LmacMsgHeader {
     pub param_len: u16,
 }
 
-/// LMAC 消息最大长度（含 param）。与 LicheeRV 一致：8801 BSP rwnx_plat_bin_fw_upload_android 每块 1024 字节，param = 8+1024 = 1032
-pub const LMAC_MSG_MAX_LEN: usize = 1032;
+/// LMAC 消息最大长度（含 param）。8801 与 LicheeRV 一致每块 1024 字节（param = 8+1024）；
+/// 8800DC/DW/D80 固件上传用 FW_UPLOAD_BLOCK_SIZE_LARGE(1280) 字节块，param = 8+1280 = 1288
+pub const LMAC_MSG_MAX_LEN: usize = 1288;
 
 /// A2E 消息：头 + 可变长 param，序列化后经 SDIO 发送
 #[derive(Debug, Clone)]
@@ -141,10 +152,12 @@ pub const SM_DISCONNECT_REQ: u16 = SM_CONNECT_REQ + 3;
 pub const SM_DISCONNECT_CFM: u16 = SM_CONNECT_REQ + 4;
 pub const SM_DISCONNECT_IND: u16 = SM_CONNECT_REQ + 5;
 
-/// MM 任务消息 ID（TASK_MM = 0），与 lmac_msg.h enum mm_msg_tag 对齐
-pub const MM_ADD_IF_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 4;
+/// MM 任务消息 ID（TASK_MM = 0），与 lmac_msg.h enum mm_msg_tag 对齐（RESET=0、START=2、VERSION=4、ADD_IF=6、REMOVE_IF=8）
+pub const MM_VERSION_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 4;
+pub const MM_VERSION_CFM: u16 = MM_VERSION_REQ + 1;
+pub const MM_ADD_IF_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 6;
 pub const MM_ADD_IF_CFM: u16 = MM_ADD_IF_REQ + 1;
-pub const MM_REMOVE_IF_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 6;
+pub const MM_REMOVE_IF_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 8;
 pub const MM_REMOVE_IF_CFM: u16 = MM_REMOVE_IF_REQ + 1;
 pub const MM_STA_ADD_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 10;
 pub const MM_STA_ADD_CFM: u16 = MM_STA_ADD_REQ + 1;
@@ -162,6 +175,9 @@ pub const MM_PS_CHANGE_IND: u16 = lmac_first_msg(TaskId::Mm as u8) + 75;
 pub const MM_RSSI_STATUS_IND: u16 = lmac_first_msg(TaskId::Mm as u8) + 89;
 pub const MM_GET_STA_INFO_REQ: u16 = lmac_first_msg(TaskId::Mm as u8) + 368;
 pub const MM_GET_STA_INFO_CFM: u16 = MM_GET_STA_INFO_REQ + 1;
+/// lmac_msg.h 中 MM_SET_RF_CONFIG、MM_SET_RF_CALIB、MM_GET_MAC_ADDR 依次紧挨在 MM_GET_STA_INFO 之前
+pub const MM_SET_RF_CONFIG_REQ: u16 = MM_GET_STA_INFO_REQ - 6;
+pub const MM_SET_RF_CONFIG_CFM: u16 = MM_SET_RF_CONFIG_REQ + 1;
 
 /// APM 任务消息 ID（TASK_APM = 7）
 pub const APM_START_REQ: u16 = lmac_first_msg(TaskId::Apm as u8);
@@ -191,8 +207,8 @@ pub const fn rwnx_cmd_wait_complete(flags: u16) -> bool {
 /// 802.11 命令超时 (ms) — aic_bsp_driver.h RWNX_80211_CMD_TIMEOUT_MS 6000
 pub const RWNX_80211_CMD_TIMEOUT_MS: u32 = 6000;
 
-/// 命令管理器最大挂起数 — RWNX_CMD_MAX_QUEUED 8
-const CMD_MGR_MAX_PENDING: usize = 8;
+/// 命令管理器默认最大挂起数 — RWNX_CMD_MAX_QUEUED 8；可由 RwnxCmdMgr::with_max_queued 覆盖
+pub const RWNX_CMD_MAX_QUEUED: usize = 8;
 
 /// 命令管理器状态 — aic_bsp_driver.h enum rwnx_cmd_mgr_state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -202,53 +218,267 @@ pub enum RwnxCmdMgrState {
     Crashed = 2,
 }
 
-/// 单条挂起命令 — 对应 struct rwnx_cmd（id/reqid/a2e_msg 由调用方在 push 时绑定，此处只存 reqid 与 cfm 结果）
+/// 命令超时现场：complete_timeout 置 Crashed 时记录，供崩溃恢复打印与上报（对应 LicheeRV cmd_mgr 超时时的 cmd dump）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CmdCrashInfo {
+    /// 超时命令等待的 CFM id
+    pub timeout_reqid: u16,
+    /// 超时命令的 tkn
+    pub timeout_tkn: u32,
+    /// 超时时仍在队列中的其它命令的 CFM id，按入队顺序；队列深度大于 RWNX_CMD_MAX_QUEUED 时只记最早的若干条
+    pub pending_reqids: [Option<u16>; RWNX_CMD_MAX_QUEUED],
+    pub queue_sz: u32,
+}
+
+/// 命令完成回调：对应 rwnx_cmd 的 msg_cb，参数为 (result, cfm param)。
+/// 与 LicheeRV cmd_mgr_msgind 不同，不在 cmd_mgr 锁内调用：完成时先取出放入 CmdCompletions，
+/// 由 with_cmd_mgr 释放锁后再调用，因此回调内可以 with_cmd_mgr 发下一条命令、drop CmdHandle。
+pub type CmdCompleteCb = Box<dyn FnOnce(i32, &[u8]) + Send>;
+
+/// 已完成、尚未调用的回调及其 (result, cfm param)，由 RwnxCmdMgr::take_completions 取出后在锁外 run
+#[must_use]
+pub struct CmdCompletions(Vec<(CmdCompleteCb, i32, Vec<u8>)>);
+
+impl CmdCompletions {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 按完成顺序调用回调；调用方不得持有 CMD_MGR 锁
+    pub fn run(self) {
+        for (cb, result, param) in self.0 {
+            cb(result, &param);
+        }
+    }
+}
+
+/// 单条挂起命令 — 对应 struct rwnx_cmd（id/reqid/a2e_msg 由调用方在 push 时绑定，此处只存 reqid 与 cfm 结果）。
+/// 不缓存 a2e_msg，因此 DBG_MEM_BLOCK_WRITE_REQ 等 1KB 以上的命令同样可入队，发送始终由调用方完成。
 struct PendingCmd {
     reqid: u16,
+    /// 入队时的 next_tkn，对应 rwnx_cmd->tkn；用于区分复用同一 slot 的前后两条命令
+    tkn: u32,
     flags: u16,
     result: i32,
     done: bool,
-    cfm_len: usize,
-    cfm_data: [u8; RWNX_CMD_E2AMSG_LEN_MAX],
+    /// CFM param，按实际长度存放（可超过 RWNX_CMD_E2AMSG_LEN_MAX）
+    cfm_data: Vec<u8>,
+    /// CmdHandle 作为 Future 被 poll 时登记的 waker，cmd_complete 时唤醒
+    waker: Option<Waker>,
+    /// push_with_cb 登记的完成回调；存在时 cmd_complete 直接回调并释放 slot
+    cb: Option<CmdCompleteCb>,
+    /// set_timeout 设置的剩余等待时间（ms），由 tick_timeouts 递减；None 为不限时
+    timeout_left_ms: Option<u32>,
 }
 
-impl Default for PendingCmd {
-    fn default() -> Self {
+impl PendingCmd {
+    fn new(reqid: u16, tkn: u32, flags: u16) -> Self {
         Self {
-            reqid: 0,
-            flags: 0,
+            reqid,
+            tkn,
+            flags,
             result: -4, // -EINTR
             done: false,
-            cfm_len: 0,
-            cfm_data: [0; RWNX_CMD_E2AMSG_LEN_MAX],
+            cfm_data: Vec::new(),
+            waker: None,
+            cb: None,
+            timeout_left_ms: None,
+        }
+    }
+}
+
+/// RwnxCmdMgr 实例编号，CmdHandle 据此只作用于创建它的 cmd_mgr（sdio_exit 后重建的 cmd_mgr 不受旧句柄影响）
+static NEXT_CMD_MGR_ID: AtomicU32 = AtomicU32::new(1);
+
+/// CMD_MGR 锁被占用时（with_cmd_mgr 闭包内或其它线程正持锁）drop 的句柄 (mgr, token, tkn)，
+/// 下次取得锁时由 cancel_dropped_handles 统一 cancel。锁顺序 CMD_MGR → DROPPED_HANDLES
+static DROPPED_HANDLES: Mutex<Vec<(u32, usize, u32)>> = Mutex::new(Vec::new());
+
+/// 已入队命令的句柄：cmd_mgr 编号 + slot 下标 + 入队 tkn。
+///
+/// 可 `.await`（Output 为 CFM param），由 busrx 线程 on_cfm → cmd_complete 唤醒，
+/// 因此同一任务可同时挂起多条控制命令（scan + get_station + set_power），无需每条一个线程阻塞等待。
+/// 超时用 `RwnxCmdMgr::set_timeout` 设置：到期后由 RX 轮询（tick_timeouts）走 complete_timeout 并唤醒，
+/// Future 返回 -ETIMEDOUT；未设置时不限时，由调用方自行 complete_timeout。
+///
+/// 句柄独占其 slot：drop 时对全局 cmd_mgr 调 cancel（已完成取走或已超时释放的 slot 不受影响），
+/// 因此被丢弃的 Future 不会泄漏 slot。CMD_MGR 锁被占用时（如在 with_cmd_mgr 闭包内 drop）不等待，
+/// 记入 DROPPED_HANDLES，下次取得锁时再 cancel。
+#[derive(Debug, PartialEq, Eq)]
+pub struct CmdHandle {
+    mgr: u32,
+    token: usize,
+    tkn: u32,
+}
+
+impl CmdHandle {
+    /// slot 下标，与 push 返回的 token 相同，可用于 is_done/take_cfm/complete_timeout
+    pub fn token(&self) -> usize {
+        self.token
+    }
+
+    /// 放弃句柄所有权、不取消命令，返回 slot 下标；之后由调用方按 token 负责 take_cfm/complete_timeout。
+    /// 用于 push_with_cb 后只等回调、不再需要句柄的场景。
+    pub fn detach(self) -> usize {
+        let token = self.token;
+        core::mem::forget(self);
+        token
+    }
+}
+
+impl Drop for CmdHandle {
+    fn drop(&mut self) {
+        if crate::sdio::try_with_cmd_mgr(|m| m.cancel(self)).is_none() {
+            DROPPED_HANDLES.lock().push((self.mgr, self.token, self.tkn));
         }
     }
 }
 
+/// CmdHandle 完成后的 CFM param（完整长度）
+#[derive(Clone)]
+pub struct CmdCfm {
+    pub data: Vec<u8>,
+}
+
+impl CmdCfm {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Future for CmdHandle {
+    type Output = Result<CmdCfm, i32>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let handle = &*self;
+        crate::sdio::with_cmd_mgr(|m| m.poll_handle(handle, cx.waker()))
+            .unwrap_or(Poll::Ready(Err(-19))) // -ENODEV：CMD_MGR 尚未创建或已在 sdio_exit 中清除
+    }
+}
+
 /// 命令管理器 — 照抄 aic_bsp_driver.h struct rwnx_cmd_mgr（state, lock, next_tkn, queue_sz, max_queue_sz, cmds）
 pub struct RwnxCmdMgr {
     pub state: RwnxCmdMgrState,
+    /// 实例编号，写入本实例发出的 CmdHandle
+    id: u32,
     next_tkn: u32,
     /// 当前队列中的命令数，与 LicheeRV cmd_mgr->queue_sz 一致；超时用 RWNX_80211_CMD_TIMEOUT_MS * queue_sz
     pub queue_sz: u32,
     max_queue_sz: u32,
-    slots: [Option<PendingCmd>; CMD_MGR_MAX_PENDING],
+    /// 长度 = max_queue_sz，构造时分配；slot 下标即 token
+    slots: Vec<Option<PendingCmd>>,
+    /// 最近一次 complete_timeout 的现场，Crashed 后保留直到 cmd_mgr 重建
+    last_crash: Option<CmdCrashInfo>,
+    /// 已完成待调用的回调，见 take_completions
+    completions: Vec<(CmdCompleteCb, i32, Vec<u8>)>,
+}
+
+impl Default for RwnxCmdMgr {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RwnxCmdMgr {
-    pub const fn new() -> Self {
+    /// 队列深度为 RWNX_CMD_MAX_QUEUED（与 rwnx_cmd_mgr_init 一致）
+    pub fn new() -> Self {
+        Self::with_max_queued(RWNX_CMD_MAX_QUEUED)
+    }
+
+    /// 指定队列深度（至少为 1），例如 AP 模式逐 STA 轮询 MM_GET_STA_INFO 时加深队列
+    pub fn with_max_queued(max_queued: usize) -> Self {
+        let max_queued = max_queued.max(1);
+        let mut slots = Vec::with_capacity(max_queued);
+        slots.resize_with(max_queued, || None);
         Self {
             state: RwnxCmdMgrState::Inited,
+            id: NEXT_CMD_MGR_ID.fetch_add(1, Ordering::Relaxed),
             next_tkn: 0,
             queue_sz: 0,
-            max_queue_sz: CMD_MGR_MAX_PENDING as u32,
-            slots: [None, None, None, None, None, None, None, None],
+            max_queue_sz: max_queued as u32,
+            slots,
+            last_crash: None,
+            completions: Vec::new(),
+        }
+    }
+
+    /// 构造时确定的队列深度
+    pub fn max_queued(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_crashed(&self) -> bool {
+        self.state == RwnxCmdMgrState::Crashed
+    }
+
+    /// 最近一次命令超时的现场（未发生过超时为 None）
+    pub fn crash_info(&self) -> Option<CmdCrashInfo> {
+        self.last_crash
+    }
+
+    /// 取出已完成（CFM/ACK、cancel、超时、flush）的回调，须在释放持有本实例的锁后 run。
+    /// 全局 cmd_mgr 由 with_cmd_mgr 负责；调用方自建的实例在调用 on_cfm 等之后自行取出
+    pub fn take_completions(&mut self) -> CmdCompletions {
+        CmdCompletions(core::mem::take(&mut self.completions))
+    }
+
+    /// cancel 在 CMD_MGR 锁被占用时 drop 的句柄（with_cmd_mgr 取得锁后调用）
+    pub(crate) fn cancel_dropped_handles(&mut self) {
+        let dropped = core::mem::take(&mut *DROPPED_HANDLES.lock());
+        for (mgr, token, tkn) in dropped {
+            if mgr == self.id {
+                self.cancel_slot(token, tkn);
+            }
         }
     }
 
     /// cmd_mgr_queue 中“入队”部分：list_add_tail, queue_sz++；REQ_CFM 时 flags|=WAIT_CFM；result=-EINTR；返回 token。
     /// 照抄 aic_bsp_driver.c cmd_mgr_queue 63-105 行。
-    pub fn push(&mut self, reqid: u16) -> Option<usize> {
+    ///
+    /// flags 取 cmd_flags 的 NONBLOCK / REQ_CFM / WAIT_ACK（与 rwnx_send_msg 组装 cmd->flags 一致）：
+    /// - REQ_CFM：等待 reqid 对应的 CFM；
+    /// - WAIT_ACK：等待调用方在总线写完成后调用 on_ack（对应 cmd_mgr_llind）；
+    /// - NONBLOCK：调用方不等待，完成时（无 waker/cb）立即释放 slot，token 不可再用于 take_cfm；
+    /// - 既无 REQ_CFM 也无 WAIT_ACK 时入队即完成（LicheeRV NONBLOCK 分支 cmd->result = 0）。
+    ///
+    /// WAIT_PUSH 不适用：本实现不缓存 a2e_msg、不延迟发送，入队时清除。
+    pub fn push(&mut self, reqid: u16, flags: u16) -> Option<usize> {
+        self.queue(reqid, flags, None)
+    }
+
+    /// 与 push 相同，但返回 CmdHandle，可直接 `.await` 或交给 is_done/take_cfm。
+    /// 句柄需要 slot 保留到被取走，因此忽略 NONBLOCK。
+    pub fn push_handle(&mut self, reqid: u16, flags: u16) -> Option<CmdHandle> {
+        let token = self.queue(reqid, flags & !cmd_flags::NONBLOCK, None)?;
+        let tkn = self.slots[token].as_ref()?.tkn;
+        Some(CmdHandle { mgr: self.id, token, tkn })
+    }
+
+    /// 与 push 相同，并登记完成回调（对应 cmd_mgr_queue 的 cb 参数）。
+    /// 完成（CFM/ACK 到达，或入队即完成）时释放 slot 并排入 cb(0, param)，调用方无需 take_cfm；
+    /// 超时（complete_timeout）或 flush/cancel 时排入 cb(-ETIMEDOUT/-EINTR, &[])。回调在 take_completions().run() 时执行。
+    /// 返回的句柄仅用于 cancel/complete_timeout；回调前 drop 句柄即取消命令，只等回调时用 `detach`。
+    pub fn push_with_cb(&mut self, reqid: u16, flags: u16, cb: CmdCompleteCb) -> Option<CmdHandle> {
+        let tkn = self.next_tkn;
+        let token = self.queue(reqid, flags, Some(cb))?;
+        Some(CmdHandle { mgr: self.id, token, tkn })
+    }
+
+    /// 句柄是否仍指向本实例中同一条命令（slot 未被释放或复用）
+    fn owns(&self, handle: &CmdHandle) -> bool {
+        handle.mgr == self.id && self.slot_holds(handle.token, handle.tkn)
+    }
+
+    /// slot 中仍是入队 tkn 的那条命令
+    fn slot_holds(&self, token: usize, tkn: u32) -> bool {
+        self.slots
+            .get(token)
+            .and_then(|s| s.as_ref())
+            .map(|s| s.tkn == tkn)
+            .unwrap_or(false)
+    }
+
+    fn queue(&mut self, reqid: u16, flags: u16, cb: Option<CmdCompleteCb>) -> Option<usize> {
         if self.state == RwnxCmdMgrState::Crashed {
             log::error!(target: "wireless::bsp", "cmd queue crashed");
             return None;
@@ -257,80 +487,245 @@ impl RwnxCmdMgr {
             log::error!(target: "wireless::bsp", "Too many cmds ({}) already queued", self.max_queue_sz);
             return None;
         }
-        for (i, slot) in self.slots.iter_mut().enumerate() {
-            if slot.is_none() {
-                let tkn = self.next_tkn;
-                self.next_tkn = self.next_tkn.wrapping_add(1);
-                let flags = cmd_flags::REQ_CFM | cmd_flags::WAIT_CFM; // reqcfm 时 flags |= REQ_CFM；queue 内 if REQ_CFM -> WAIT_CFM
-                *slot = Some(PendingCmd {
-                    reqid,
-                    flags,
-                    result: -4, // -EINTR
-                    done: false,
-                    cfm_len: 0,
-                    cfm_data: [0; RWNX_CMD_E2AMSG_LEN_MAX],
-                });
-                self.queue_sz += 1;
-                log::debug!(target: "wireless::bsp", "cmd_mgr push reqid=0x{:04x} token={} tkn={} queue_sz={}", reqid, i, tkn, self.queue_sz);
-                return Some(i);
+        let token = self.slots.iter().position(|s| s.is_none())?;
+        let tkn = self.next_tkn;
+        self.next_tkn = self.next_tkn.wrapping_add(1);
+        let mut flags = flags & !(cmd_flags::WAIT_PUSH | cmd_flags::WAIT_CFM | cmd_flags::DONE);
+        if flags & cmd_flags::REQ_CFM != 0 {
+            flags |= cmd_flags::WAIT_CFM;
+        }
+        let mut cmd = PendingCmd::new(reqid, tkn, flags);
+        cmd.cb = cb;
+        self.slots[token] = Some(cmd);
+        self.queue_sz += 1;
+        log::debug!(target: "wireless::bsp", "cmd_mgr push reqid=0x{:04x} flags=0x{:02x} token={} tkn={} queue_sz={}", reqid, flags, token, tkn, self.queue_sz);
+        if rwnx_cmd_wait_complete(flags) {
+            if let Some(ref mut s) = self.slots[token] {
+                s.result = 0;
+            }
+            self.cmd_complete(token);
+        }
+        Some(token)
+    }
+
+    /// CmdHandle::poll 的实现：已完成则取走 cfm 并释放 slot；未完成则登记 waker。
+    /// slot 已被 complete_timeout 释放或被后续命令复用（tkn 不符）时返回 -ETIMEDOUT。
+    pub fn poll_handle(&mut self, handle: &CmdHandle, waker: &Waker) -> Poll<Result<CmdCfm, i32>> {
+        if handle.mgr != self.id || handle.token >= self.slots.len() {
+            return Poll::Ready(Err(-22));
+        }
+        if !self.owns(handle) {
+            return Poll::Ready(Err(-62));
+        }
+        if let Some(ref mut s) = self.slots[handle.token] {
+            if !s.done {
+                s.waker = Some(waker.clone());
+                return Poll::Pending;
+            }
+        }
+        match self.take_cfm_vec(handle.token) {
+            Some(data) => Poll::Ready(Ok(CmdCfm { data })),
+            None => Poll::Ready(Err(-5)),
+        }
+    }
+
+    /// 为句柄对应的命令设置超时：之后 tick_timeouts 累计满 timeout_ms 仍未完成即 complete_timeout（state=CRASHED），
+    /// 与 wait_done_until 超时后调用方 complete_timeout 的效果相同。句柄已失效时为空操作。
+    pub fn set_timeout(&mut self, handle: &CmdHandle, timeout_ms: u32) {
+        if !self.owns(handle) {
+            return;
+        }
+        if let Some(ref mut s) = self.slots[handle.token] {
+            s.timeout_left_ms = Some(timeout_ms);
+        }
+    }
+
+    /// 推进 set_timeout 设置的超时：未完成命令的剩余时间减 elapsed_ms，到期者 complete_timeout。
+    /// 由 RX 轮询调用（busrx 每轮、或无 busrx 时 sdio_poll_rx_once 每次），与 wait_done_until 一样按每轮约 1ms 计时。
+    pub fn tick_timeouts(&mut self, elapsed_ms: u32) {
+        let mut expired: Vec<usize> = Vec::new();
+        for (token, slot) in self.slots.iter_mut().enumerate() {
+            let Some(s) = slot.as_mut().filter(|s| !s.done) else {
+                continue;
+            };
+            if let Some(left) = s.timeout_left_ms {
+                let left = left.saturating_sub(elapsed_ms);
+                s.timeout_left_ms = Some(left);
+                if left == 0 {
+                    expired.push(token);
+                }
             }
         }
-        None
+        for token in expired {
+            log::warn!(target: "wireless::bsp", "cmd_mgr tick_timeouts: token={} timed out waiting for CFM", token);
+            self.complete_timeout(token);
+        }
+    }
+
+    /// 放弃一条命令（CmdHandle drop 时自动调用，或上层自行超时）：释放 slot，不标记 Crashed。
+    /// 句柄已失效（slot 已取走/超时释放/被复用，或属于其它 cmd_mgr 实例）时为空操作。
+    pub fn cancel(&mut self, handle: &CmdHandle) {
+        if handle.mgr == self.id {
+            self.cancel_slot(handle.token, handle.tkn);
+        }
+    }
+
+    fn cancel_slot(&mut self, token: usize, tkn: u32) {
+        if !self.slot_holds(token, tkn) {
+            return;
+        }
+        if let Some(s) = self.slots[token].take() {
+            if s.flags & cmd_flags::DONE == 0 {
+                self.queue_sz = self.queue_sz.saturating_sub(1);
+            }
+            if let Some(cb) = s.cb {
+                self.completions.push((cb, -4, Vec::new())); // -EINTR
+            }
+        }
+    }
+
+    /// cmd_mgr_flush：丢弃所有挂起命令，唤醒等待者并排入 -EINTR 回调（rwnx_cmd_mgr_deinit 时调用）。
+    pub fn flush(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if let Some(s) = slot.take() {
+                if let Some(w) = s.waker {
+                    w.wake();
+                }
+                if let Some(cb) = s.cb {
+                    self.completions.push((cb, -4, Vec::new())); // -EINTR
+                }
+            }
+        }
+        self.queue_sz = 0;
     }
 
     /// cmd_mgr_msgind：匹配 reqid==msg->id 且 (flags & WAIT_CFM)；clear WAIT_CFM；cap param_len；memcpy(e2a_msg, param)；RWNX_CMD_WAIT_COMPLETE 则 cmd_complete。
     /// 照抄 aic_bsp_driver.c cmd_mgr_msgind 156-194 行。
+    /// LicheeRV 按 cmds 链表（入队顺序）查找第一条匹配；slot 复用后下标不再反映入队顺序，
+    /// 因此按 tkn 取最早入队的一条，保证同一 CFM id 的多条命令（如多个 STA 的 MM_GET_STA_INFO_REQ）按提交顺序完成。
     pub fn on_cfm(&mut self, msg_id: u16, param: &[u8]) {
-        for (token, slot) in self.slots.iter_mut().enumerate() {
-            if let Some(ref mut s) = *slot {
-                if s.reqid == msg_id && (s.flags & cmd_flags::WAIT_CFM) != 0 {
-                    s.flags &= !cmd_flags::WAIT_CFM;
-                    let mut param_len = param.len();
-                    if param_len > RWNX_CMD_E2AMSG_LEN_MAX {
-                        param_len = RWNX_CMD_E2AMSG_LEN_MAX;
-                    }
-                    s.cfm_data[..param_len].copy_from_slice(&param[..param_len]);
-                    s.cfm_len = param_len;
-                    s.done = true;
+        let Some(token) = self.oldest_waiting_cfm(msg_id) else {
+            return;
+        };
+        if let Some(ref mut s) = self.slots[token] {
+            s.flags &= !cmd_flags::WAIT_CFM;
+            // LicheeRV 在此按 RWNX_CMD_E2AMSG_LEN_MAX 截断；此处整段保存，交给调用方按需取用
+            s.cfm_data.clear();
+            s.cfm_data.extend_from_slice(param);
+            s.result = 0;
+            log::debug!(target: "wireless::bsp", "cmd_mgr on_cfm msg_id=0x{:04x} len={} token={} tkn={}", msg_id, param.len(), token, s.tkn);
+            if rwnx_cmd_wait_complete(s.flags) {
+                self.cmd_complete(token);
+            }
+        }
+    }
+
+    /// 等待 msg_id 的命令中最早入队者（next_tkn - tkn 最大，tkn 回绕亦成立）
+    fn oldest_waiting_cfm(&self, msg_id: u16) -> Option<usize> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.as_ref().map(|s| (i, s)))
+            .filter(|(_, s)| s.reqid == msg_id && (s.flags & cmd_flags::WAIT_CFM) != 0)
+            .max_by_key(|(_, s)| self.next_tkn.wrapping_sub(s.tkn))
+            .map(|(i, _)| i)
+    }
+
+    /// cmd_mgr_llind：命令已写入总线（bustx 的 CMD53 完成）；clear WAIT_ACK，RWNX_CMD_WAIT_COMPLETE 则 cmd_complete。
+    /// 仅对 push 时带 WAIT_ACK 的命令生效；未带 REQ_CFM 的命令以 ACK 为完成点（result=0）。
+    pub fn on_ack(&mut self, token: usize) {
+        if token >= self.slots.len() {
+            return;
+        }
+        match self.slots[token] {
+            Some(ref mut s) if s.flags & cmd_flags::WAIT_ACK != 0 => {
+                s.flags &= !cmd_flags::WAIT_ACK;
+                if s.flags & cmd_flags::REQ_CFM == 0 {
                     s.result = 0;
-                    log::debug!(target: "wireless::bsp", "cmd_mgr on_cfm msg_id=0x{:04x} len={} token={}", msg_id, param_len, token);
-                    if rwnx_cmd_wait_complete(s.flags) {
-                        self.cmd_complete(token);
-                    }
-                    return;
+                }
+                log::trace!(target: "wireless::bsp", "cmd_mgr on_ack reqid=0x{:04x} token={}", s.reqid, token);
+                if rwnx_cmd_wait_complete(s.flags) {
+                    self.cmd_complete(token);
                 }
             }
+            Some(_) => {}
+            None => log::error!(target: "wireless::bsp", "cmd_mgr on_ack: acked cmd not found (token={})", token),
         }
     }
 
     /// cmd_complete：list_del, queue_sz--, flags|=DONE；若 RWNX_CMD_WAIT_COMPLETE 则 complete(&cmd->complete)。
     /// 照抄 aic_bsp_driver.c cmd_complete 44-61 行。
+    /// 有 cb 时释放 slot 并排入 completions；NONBLOCK 且无人等待（无 waker）时直接释放 slot（对应 LicheeRV NONBLOCK 命令 kfree(cmd)）。
     fn cmd_complete(&mut self, token: usize) {
-        if token >= CMD_MGR_MAX_PENDING {
+        if token >= self.slots.len() {
             return;
         }
-        if let Some(ref mut s) = self.slots[token] {
-            s.flags |= cmd_flags::DONE;
-            self.queue_sz = self.queue_sz.saturating_sub(1);
-            crate::sdio_irq::notify_wait_done();
-            crate::sdio_irq::notify_cmd_done();
+        let release = match self.slots[token] {
+            Some(ref mut s) => {
+                s.flags |= cmd_flags::DONE;
+                s.done = true;
+                self.queue_sz = self.queue_sz.saturating_sub(1);
+                let waker = s.waker.take();
+                let release = s.cb.is_some() || (s.flags & cmd_flags::NONBLOCK != 0 && waker.is_none());
+                if let Some(w) = waker {
+                    w.wake();
+                }
+                release
+            }
+            None => return,
+        };
+        if release {
+            // 对应 LicheeRV cmd_mgr_msgind 内 cb(rwnx_hw, cmd, msg)：回调后无人 take_cfm，直接释放 slot；回调留到锁外执行
+            if let Some(s) = self.slots[token].take() {
+                if let Some(cb) = s.cb {
+                    self.completions.push((cb, s.result, s.cfm_data));
+                }
+            }
         }
+        crate::sdio_irq::notify_wait_done();
+        crate::sdio_irq::notify_cmd_done();
     }
 
     /// 超时路径：与 LicheeRV queue() 内 wait_for_completion_killable_timeout 超时后 cmd_complete(cmd); state=CRASHED 一致。
     pub fn complete_timeout(&mut self, token: usize) {
-        if token >= CMD_MGR_MAX_PENDING {
+        if token >= self.slots.len() {
             return;
         }
-        if self.slots[token].take().is_some() {
+        if let Some(ref s) = self.slots[token] {
+            let mut others: Vec<&PendingCmd> = self
+                .slots
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != token)
+                .filter_map(|(_, slot)| slot.as_ref())
+                .collect();
+            others.sort_by_key(|p| core::cmp::Reverse(self.next_tkn.wrapping_sub(p.tkn)));
+            let mut pending_reqids = [None; RWNX_CMD_MAX_QUEUED];
+            for (dst, p) in pending_reqids.iter_mut().zip(others) {
+                *dst = Some(p.reqid);
+            }
+            self.last_crash = Some(CmdCrashInfo {
+                timeout_reqid: s.reqid,
+                timeout_tkn: s.tkn,
+                pending_reqids,
+                queue_sz: self.queue_sz,
+            });
+        }
+        if let Some(s) = self.slots[token].take() {
             self.queue_sz = self.queue_sz.saturating_sub(1);
+            if let Some(w) = s.waker {
+                w.wake();
+            }
+            if let Some(cb) = s.cb {
+                self.completions.push((cb, -62, Vec::new())); // -ETIMEDOUT
+            }
         }
         self.state = RwnxCmdMgrState::Crashed;
         log::warn!(target: "wireless::bsp", "cmd_mgr complete_timeout token={} queue_sz={}", token, self.queue_sz);
     }
 
     pub fn is_done(&self, token: usize) -> bool {
-        if token >= CMD_MGR_MAX_PENDING {
+        if token >= self.slots.len() {
             return false;
         }
         self.slots[token]
@@ -339,18 +734,27 @@ impl RwnxCmdMgr {
             .unwrap_or(false)
     }
 
-    /// 取走 cfm 数据并释放 slot；queue_sz 已在 on_cfm -> cmd_complete 时减过，此处不再减
+    /// 取走 cfm 数据并释放 slot；queue_sz 已在 on_cfm -> cmd_complete 时减过，此处不再减。
+    /// CFM 长于 out 时截断，需要完整内容时用 take_cfm_vec。
     pub fn take_cfm(&mut self, token: usize, out: &mut [u8]) -> Option<usize> {
-        if token >= CMD_MGR_MAX_PENDING {
+        let data = self.take_cfm_vec(token)?;
+        let len = data.len().min(out.len());
+        if len < data.len() {
+            log::debug!(target: "wireless::bsp", "cmd_mgr take_cfm token={} truncated {} -> {}", token, data.len(), len);
+        }
+        out[..len].copy_from_slice(&data[..len]);
+        Some(len)
+    }
+
+    /// 取走完整 cfm 数据（堆上，长度不受 RWNX_CMD_E2AMSG_LEN_MAX 限制）并释放 slot
+    pub fn take_cfm_vec(&mut self, token: usize) -> Option<Vec<u8>> {
+        if token >= self.slots.len() {
             return None;
         }
-        let slot = self.slots[token].take()?;
-        if !slot.done {
+        if !self.slots[token].as_ref()?.done {
             return None;
         }
-        let len = slot.cfm_len.min(out.len());
-        out[..len].copy_from_slice(&slot.cfm_data[..len]);
-        Some(len)
+        self.slots[token].take().map(|s| s.cfm_data)
     }
 
     /// 等待该 token 完成；poll_fn 由调用方提供（例如执行一次 bus_poll_rx 并 on_cfm）
@@ -440,3 +844,220 @@ impl RwnxCmdMgr {
         Err(-62) // -ETIMEDOUT
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use alloc::task::Wake;
+    use core::sync::atomic::AtomicUsize;
+
+    struct CountWaker(AtomicUsize);
+
+    impl Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn count_waker() -> (Arc<CountWaker>, Waker) {
+        let w = Arc::new(CountWaker(AtomicUsize::new(0)));
+        (w.clone(), Waker::from(w))
+    }
+
+    type CbLog = Arc<spin::Mutex<Vec<(i32, Vec<u8>)>>>;
+
+    fn logging_cb(log: &CbLog) -> CmdCompleteCb {
+        let log = log.clone();
+        Box::new(move |result, param| log.lock().push((result, param.to_vec())))
+    }
+
+    #[test]
+    fn push_handle_and_poll_handle() {
+        let mut m = RwnxCmdMgr::new();
+        let h = m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM | cmd_flags::NONBLOCK).unwrap();
+        let (count, waker) = count_waker();
+        assert!(m.poll_handle(&h, &waker).is_pending());
+        assert_eq!(m.queue_sz, 1);
+
+        // 其它 CFM 不影响
+        m.on_cfm(MM_ADD_IF_CFM, &[9]);
+        assert_eq!(count.0.load(Ordering::SeqCst), 0);
+
+        m.on_cfm(MM_VERSION_CFM, &[1, 2, 3]);
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+        assert_eq!(m.queue_sz, 0);
+        match m.poll_handle(&h, &waker) {
+            Poll::Ready(Ok(cfm)) => assert_eq!(cfm.as_bytes(), &[1, 2, 3]),
+            _ => panic!("CmdHandle not ready after CFM"),
+        }
+        // slot 已随 CFM 取走释放，再 poll 即 -ETIMEDOUT
+        assert!(matches!(m.poll_handle(&h, &waker), Poll::Ready(Err(-62))));
+        assert_eq!(m.max_queued(), (0..RWNX_CMD_MAX_QUEUED).filter_map(|_| m.push(MM_VERSION_CFM, cmd_flags::REQ_CFM)).count());
+    }
+
+    #[test]
+    fn poll_handle_after_timeout_and_from_other_mgr() {
+        let mut m = RwnxCmdMgr::new();
+        let mut other = RwnxCmdMgr::new();
+        let h = m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM).unwrap();
+        let (_, waker) = count_waker();
+        assert!(matches!(other.poll_handle(&h, &waker), Poll::Ready(Err(-22))));
+        other.cancel(&h);
+        assert_eq!(m.queue_sz, 1);
+
+        m.complete_timeout(h.token());
+        assert!(m.is_crashed());
+        assert_eq!(m.crash_info().map(|c| c.timeout_reqid), Some(MM_VERSION_CFM));
+        assert!(matches!(m.poll_handle(&h, &waker), Poll::Ready(Err(-62))));
+        assert!(m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM).is_none());
+    }
+
+    #[test]
+    fn set_timeout_expires_on_tick_and_wakes_handle() {
+        let mut m = RwnxCmdMgr::new();
+        let slow = m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM).unwrap();
+        let fast = m.push_handle(MM_ADD_IF_CFM, cmd_flags::REQ_CFM).unwrap();
+        m.set_timeout(&slow, 3);
+        m.set_timeout(&fast, 3);
+        let (count, waker) = count_waker();
+        assert!(m.poll_handle(&slow, &waker).is_pending());
+
+        m.tick_timeouts(2);
+        m.on_cfm(MM_ADD_IF_CFM, &[0, 1]);
+        assert!(!m.is_crashed());
+        assert_eq!(count.0.load(Ordering::SeqCst), 0);
+
+        // 已完成的 fast 不再计时；slow 到期后被 complete_timeout 并唤醒
+        m.tick_timeouts(1);
+        assert!(m.is_crashed());
+        assert_eq!(m.crash_info().map(|c| c.timeout_reqid), Some(MM_VERSION_CFM));
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+        assert!(matches!(m.poll_handle(&slow, &waker), Poll::Ready(Err(-62))));
+        assert!(matches!(m.poll_handle(&fast, &waker), Poll::Ready(Ok(ref c)) if c.as_bytes() == [0, 1]));
+    }
+
+    #[test]
+    fn cancel_releases_slot_once() {
+        let mut m = RwnxCmdMgr::with_max_queued(2);
+        let a = m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM).unwrap();
+        let _b = m.push_handle(MM_ADD_IF_CFM, cmd_flags::REQ_CFM).unwrap();
+        assert!(m.push_handle(MM_STA_ADD_CFM, cmd_flags::REQ_CFM).is_none());
+
+        m.cancel(&a);
+        assert_eq!(m.queue_sz, 1);
+        let c = m.push_handle(MM_STA_ADD_CFM, cmd_flags::REQ_CFM).unwrap();
+        assert_eq!(c.token(), a.token());
+
+        // a 的 slot 已被 c 复用：再次 cancel(a) 不影响 c
+        m.cancel(&a);
+        assert_eq!(m.queue_sz, 2);
+        m.on_cfm(MM_STA_ADD_CFM, &[4]);
+        assert!(m.is_done(c.token()));
+
+        // 已完成未取走的命令被 cancel：释放 slot，queue_sz 不重复减
+        m.cancel(&c);
+        assert_eq!(m.queue_sz, 1);
+        assert!(!m.is_done(c.token()));
+    }
+
+    #[test]
+    fn callbacks_on_cfm_cancel_timeout_and_flush() {
+        let mut m = RwnxCmdMgr::new();
+        let log: CbLog = Arc::default();
+
+        m.push_with_cb(MM_VERSION_CFM, cmd_flags::REQ_CFM, logging_cb(&log)).unwrap().detach();
+        m.on_cfm(MM_VERSION_CFM, &[7, 8]);
+        // NONBLOCK 且不等 ACK/CFM：入队即回调
+        m.push_with_cb(MM_SET_POWER_REQ, cmd_flags::NONBLOCK, logging_cb(&log)).unwrap().detach();
+        let h = m.push_with_cb(MM_ADD_IF_CFM, cmd_flags::REQ_CFM, logging_cb(&log)).unwrap();
+        m.cancel(&h);
+        let t = m.push_with_cb(MM_STA_ADD_CFM, cmd_flags::REQ_CFM, logging_cb(&log)).unwrap().detach();
+        m.complete_timeout(t);
+        assert_eq!(m.queue_sz, 0);
+        // 回调只排队，不在 cmd_mgr 方法内执行
+        assert!(log.lock().is_empty());
+        m.take_completions().run();
+
+        let mut m = RwnxCmdMgr::new();
+        m.push_with_cb(MM_STA_DEL_CFM, cmd_flags::REQ_CFM, logging_cb(&log)).unwrap().detach();
+        m.flush();
+        m.take_completions().run();
+        assert!(m.take_completions().is_empty());
+
+        assert_eq!(
+            *log.lock(),
+            [(0, alloc::vec![7, 8]), (0, Vec::new()), (-4, Vec::new()), (-62, Vec::new()), (-4, Vec::new())]
+        );
+    }
+
+    #[test]
+    fn dropped_handle_cancels_on_global_cmd_mgr() {
+        use crate::sdio::with_cmd_mgr;
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let saved = crate::sdio::replace_cmd_mgr_for_test(Some(RwnxCmdMgr::with_max_queued(1)));
+
+        // 未完成的 Future 被丢弃：slot 归还
+        let mut h = with_cmd_mgr(|m| m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM)).flatten().unwrap();
+        let (count, waker) = count_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Pin::new(&mut h).poll(&mut cx).is_pending());
+        drop(h);
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(0));
+
+        // 经 Future 完成后 drop：不影响复用该 slot 的后续命令
+        let mut h = with_cmd_mgr(|m| m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM)).flatten().unwrap();
+        assert!(Pin::new(&mut h).poll(&mut cx).is_pending());
+        with_cmd_mgr(|m| m.on_cfm(MM_VERSION_CFM, &[5]));
+        assert_eq!(count.0.load(Ordering::SeqCst), 1);
+        match Pin::new(&mut h).poll(&mut cx) {
+            Poll::Ready(Ok(cfm)) => assert_eq!(cfm.as_bytes(), &[5]),
+            _ => panic!("CmdHandle not ready after CFM"),
+        }
+        let next = with_cmd_mgr(|m| m.push_handle(MM_ADD_IF_CFM, cmd_flags::REQ_CFM)).flatten().unwrap();
+        drop(h);
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(1));
+
+        // 其它 cmd_mgr 实例发出的句柄 drop 时不触碰全局 cmd_mgr
+        let mut local = RwnxCmdMgr::with_max_queued(1);
+        drop(local.push_handle(MM_ADD_IF_CFM, cmd_flags::REQ_CFM).unwrap());
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(1));
+        assert_eq!(local.queue_sz, 1);
+
+        drop(next);
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(0));
+        crate::sdio::replace_cmd_mgr_for_test(saved);
+    }
+
+    #[test]
+    fn callback_chains_command_and_drops_handle_outside_lock() {
+        use crate::sdio::with_cmd_mgr;
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let saved = crate::sdio::replace_cmd_mgr_for_test(Some(RwnxCmdMgr::with_max_queued(2)));
+        let log: CbLog = Arc::default();
+
+        // 回调内 drop 另一条命令的句柄并 with_cmd_mgr 发下一条命令
+        let add_if = with_cmd_mgr(|m| m.push_handle(MM_ADD_IF_CFM, cmd_flags::REQ_CFM)).flatten().unwrap();
+        let chained = log.clone();
+        let cb: CmdCompleteCb = Box::new(move |result, _| {
+            assert_eq!(result, 0);
+            drop(add_if);
+            with_cmd_mgr(|m| m.push_with_cb(MM_STA_ADD_CFM, cmd_flags::REQ_CFM, logging_cb(&chained)).map(CmdHandle::detach));
+        });
+        with_cmd_mgr(|m| m.push_with_cb(MM_VERSION_CFM, cmd_flags::REQ_CFM, cb).map(CmdHandle::detach));
+        with_cmd_mgr(|m| m.on_cfm(MM_VERSION_CFM, &[1]));
+        // ADD_IF 已取消，只剩回调发出的 STA_ADD
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(1));
+        with_cmd_mgr(|m| m.on_cfm(MM_STA_ADD_CFM, &[2]));
+        assert_eq!(*log.lock(), [(0, alloc::vec![2])]);
+
+        // with_cmd_mgr 闭包内 drop 句柄：不死锁，下次取得锁时取消
+        let inside = with_cmd_mgr(|m| {
+            drop(m.push_handle(MM_VERSION_CFM, cmd_flags::REQ_CFM));
+            m.queue_sz
+        });
+        assert_eq!(inside, Some(1));
+        assert_eq!(with_cmd_mgr(|m| m.queue_sz), Some(0));
+        crate::sdio::replace_cmd_mgr_for_test(saved);
+    }
+}
@@ -24,6 +24,32 @@ pub enum SkBuffId {
     TxData = 0,
 }
 
+/// 固件上传后的 RAM 回读校验方式（LicheeRV 无此步骤）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FwVerifyMode {
+    /// 不校验（量产默认）
+    #[default]
+    Off,
+    /// 每 1KB 块回读首/中/尾 3 个字
+    Sampled,
+    /// 逐字回读整个区域（每字一次 DBG_MEM_READ，较慢，仅用于调试）
+    Full,
+}
+
+/// 上传前按固件清单（FIRMWARE_MANIFEST）校验长度与 SHA-256 的范围（LicheeRV 无此步骤）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FwIntegrityPolicy {
+    /// 只校验编译期嵌入的固件，不符即中止；文件系统/注册表提供的同名固件视为有意替换，不按清单校验（默认）
+    #[default]
+    EmbeddedOnly,
+    /// 所有来源都按清单校验，不符即中止（固件随系统镜像发布、不允许替换时使用）
+    Enforce,
+    /// 所有来源都按清单校验，不符只打日志并继续上传
+    Warn,
+    /// 不校验
+    Off,
+}
+
 /// BSP 特性配置
 #[derive(Debug, Clone, Default)]
 pub struct AicBspFeature {
@@ -32,6 +58,25 @@ pub struct AicBspFeature {
     pub sdio_phase: u8,
     pub fwlog_en: bool,
     pub irqf: u8,
+    /// 固件上传后回读校验，不符的块重传
+    pub fw_verify: FwVerifyMode,
+    /// 上传前固件清单校验范围
+    pub fw_integrity: FwIntegrityPolicy,
+}
+
+impl AicBspFeature {
+    /// 用于静态初始化（const 上下文），与 Default::default() 同值
+    pub const fn default_const() -> Self {
+        Self {
+            hwinfo: 0,
+            sdio_clock: 0,
+            sdio_phase: 0,
+            fwlog_en: false,
+            irqf: 0,
+            fw_verify: FwVerifyMode::Off,
+            fw_integrity: FwIntegrityPolicy::EmbeddedOnly,
+        }
+    }
 }
 
 /// BSP 全局信息（对应 aic_bsp_main.c 中 aicbsp_info）
@@ -1,6 +1,8 @@
 //! 固件配置
 //! 对应 aic_bsp_main.c 中的固件定义
 
+use crate::export::FwIntegrityPolicy;
+use crate::fw_source::FirmwareBlob;
 use crate::ProductId;
 
 /// 芯片版本（与 LicheeRV aic_bsp_driver.h enum chip_rev 数值完全一致）
@@ -228,6 +230,130 @@ pub const FW_8800D80X2: [AicBspFirmware; 2] = [
     },
 ];
 
+/// 固件清单条目：文件名、字节数与 SHA-256
+#[derive(Debug, Clone, Copy)]
+pub struct FirmwareManifestEntry {
+    pub name: &'static str,
+    pub size: usize,
+    pub sha256: [u8; 32],
+}
+
+impl FirmwareManifestEntry {
+    /// sha256_hex 为 64 位小写十六进制，编译期解析
+    const fn new(name: &'static str, size: usize, sha256_hex: &str) -> Self {
+        let hex = sha256_hex.as_bytes();
+        assert!(hex.len() == 64);
+        let mut sha256 = [0u8; 32];
+        let mut i = 0;
+        while i < 32 {
+            sha256[i] = (hex_nibble(hex[2 * i]) << 4) | hex_nibble(hex[2 * i + 1]);
+            i += 1;
+        }
+        Self { name, size, sha256 }
+    }
+}
+
+const fn hex_nibble(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => panic!("invalid hex digit in firmware manifest"),
+    }
+}
+
+/// 已知固件清单（与 wireless/firmware/ 下的 .bin 一致；更新固件时须同步更新此表）
+pub const FIRMWARE_MANIFEST: &[FirmwareManifestEntry] = &[
+    FirmwareManifestEntry::new("fmacfw.bin", 321760, "38415b246b2dca580bb0d8fdcab59b392386875c1efb7b8b7ed39b4fa188632e"),
+    FirmwareManifestEntry::new("fmacfw_8800d80_u02.bin", 337184, "ffb49ede6004e58453f01489edf28b888b509529c3173554c98aa94fbb33507d"),
+    FirmwareManifestEntry::new("fmacfw_patch.bin", 76, "6c8126ad655e9971f05ca03dc60fa82cb6d48c3b02cf3ba960137566ce2e28d5"),
+    FirmwareManifestEntry::new("fmacfw_patch_8800dc_u02.bin", 31624, "69d3ac2038da3b8e652ed1ec5079598ceb6df51db7b87b1d33f6d3c820c86a6f"),
+    FirmwareManifestEntry::new("fw_adid.bin", 1204, "1a54cd5f56552b1df20ae20377194ea1e0b1c6e65df8873c3d6a5ab0f4760828"),
+    FirmwareManifestEntry::new("fw_adid_8800d80_u02.bin", 1708, "a526cbd02fcdc495f049f3ad6b5933cb08cd984b16790c716a060d582fee1a56"),
+    FirmwareManifestEntry::new("fw_adid_8800dc_u02.bin", 1468, "a06cc1ad0dfc7ad03e6b0c8dd3b6bf29b51dcd0958cd99babcd27049fc7a7566"),
+    FirmwareManifestEntry::new("fw_adid_u03.bin", 1208, "6c7cc9d899d2a4e5b91b0f009aa6679498131adc27d220b96ec162536370a190"),
+    FirmwareManifestEntry::new("fw_patch.bin", 57848, "d9e164c90fd76f767bb7f5d5041a1e0f7b69096d7a408ff605920f909207ee14"),
+    FirmwareManifestEntry::new("fw_patch_8800d80_u02.bin", 32700, "f0e2f5bbc17bc327ca7f1574ff55370dfd863d931514347bb4abc18a74f6218f"),
+    FirmwareManifestEntry::new("fw_patch_8800dc_u02.bin", 17396, "c4087b95e788785df0fc55aa92152d214323ee028c70ba0ebb23944d4070340b"),
+    FirmwareManifestEntry::new("fw_patch_table.bin", 1248, "38d572126d767d2fe34b011076675195ddcb108c67dbaca0c14dbc9dc9617964"),
+    FirmwareManifestEntry::new("fw_patch_table_8800d80_u02.bin", 1384, "9decb77435b7e9713e33e32da483d683b7329ed93b672b2d1b134031d7da5f67"),
+    FirmwareManifestEntry::new("fw_patch_table_8800dc_u02.bin", 960, "e7eea12cc85fca5d8667182b4520b6a0929044c70c6d9e9a3d7ece8b16169688"),
+    FirmwareManifestEntry::new("fw_patch_table_u03.bin", 1392, "0b1f6c1f1c845e3b0665a1bd1a5cd433d55bd24b79a86bbb6b06c8446453e1a1"),
+    FirmwareManifestEntry::new("fw_patch_u03.bin", 65136, "5f024e0b80802a30797d5afb3d06e3a8d02efe74d7ed0f7d4ee807093a579067"),
+];
+
+/// 固件完整性校验失败
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareIntegrityError {
+    /// 长度与清单不符（多为截断或拿错文件）
+    SizeMismatch {
+        name: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// 长度相同但 SHA-256 不符
+    DigestMismatch { name: &'static str },
+}
+
+impl core::fmt::Display for FirmwareIntegrityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FirmwareIntegrityError::SizeMismatch { name, expected, actual } => write!(
+                f,
+                "firmware {}: size mismatch (expected {} bytes, got {})",
+                name, expected, actual
+            ),
+            FirmwareIntegrityError::DigestMismatch { name } => {
+                write!(f, "firmware {}: SHA-256 mismatch", name)
+            }
+        }
+    }
+}
+
+/// 按文件名查清单
+pub fn firmware_manifest_entry(name: &str) -> Option<&'static FirmwareManifestEntry> {
+    FIRMWARE_MANIFEST.iter().find(|e| e.name == name)
+}
+
+/// 上传前按清单校验固件；不在清单中的文件（如自备的 fmacfw_rf.bin）无法校验，返回 Ok 并打日志
+pub fn validate_firmware_image(name: &str, data: &[u8]) -> Result<(), FirmwareIntegrityError> {
+    let entry = match firmware_manifest_entry(name) {
+        Some(e) => e,
+        None => {
+            log::warn!(target: "wireless::bsp", "firmware {} not in manifest, skip integrity check", name);
+            return Ok(());
+        }
+    };
+    if data.len() != entry.size {
+        return Err(FirmwareIntegrityError::SizeMismatch {
+            name: entry.name,
+            expected: entry.size,
+            actual: data.len(),
+        });
+    }
+    if crate::sha256::sha256(data) != entry.sha256 {
+        return Err(FirmwareIntegrityError::DigestMismatch { name: entry.name });
+    }
+    Ok(())
+}
+
+/// 按 policy 对来源链取到的固件做清单校验：EmbeddedOnly 只查嵌入固件，Warn 不符只打日志，Off 不查
+pub fn check_firmware_image(name: &str, blob: &FirmwareBlob, policy: FwIntegrityPolicy) -> Result<(), FirmwareIntegrityError> {
+    match policy {
+        FwIntegrityPolicy::Off => Ok(()),
+        FwIntegrityPolicy::EmbeddedOnly if !blob.is_embedded() => {
+            log::debug!(target: "wireless::bsp", "firmware {} not embedded, manifest not applied", name);
+            Ok(())
+        }
+        FwIntegrityPolicy::EmbeddedOnly | FwIntegrityPolicy::Enforce => validate_firmware_image(name, blob),
+        FwIntegrityPolicy::Warn => {
+            if let Err(e) = validate_firmware_image(name, blob) {
+                log::warn!(target: "wireless::bsp", "{} (policy Warn, uploading anyway)", e);
+            }
+            Ok(())
+        }
+    }
+}
+
 /// 根据 product_id 与 chip_rev 选择固件表（对应 LicheeRV aicbsp_driver_fw_init 内 aicbsp_firmware_list 赋值）
 pub fn get_firmware_list(
     product_id: ProductId,
@@ -279,3 +405,38 @@ pub fn get_firmware_list(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn manifest_policy_scopes_the_check() {
+        let bad = vec![0u8; 76];
+        let embedded = FirmwareBlob::Embedded(&[0u8; 76]);
+        let replaced = FirmwareBlob::Owned(bad.clone());
+        let digest_err = Err(FirmwareIntegrityError::DigestMismatch { name: "fmacfw_patch.bin" });
+
+        assert_eq!(check_firmware_image("fmacfw_patch.bin", &embedded, FwIntegrityPolicy::EmbeddedOnly), digest_err);
+        assert_eq!(check_firmware_image("fmacfw_patch.bin", &replaced, FwIntegrityPolicy::EmbeddedOnly), Ok(()));
+        assert_eq!(check_firmware_image("fmacfw_patch.bin", &replaced, FwIntegrityPolicy::Enforce), digest_err);
+        assert_eq!(check_firmware_image("fmacfw_patch.bin", &replaced, FwIntegrityPolicy::Warn), Ok(()));
+        assert_eq!(check_firmware_image("fmacfw_patch.bin", &embedded, FwIntegrityPolicy::Off), Ok(()));
+        // 不在清单中的文件无从校验
+        assert_eq!(check_firmware_image("fmacfw_rf.bin", &replaced, FwIntegrityPolicy::Enforce), Ok(()));
+        assert_eq!(
+            check_firmware_image("fmacfw_patch.bin", &FirmwareBlob::Owned(bad[..75].to_vec()), FwIntegrityPolicy::Enforce),
+            Err(FirmwareIntegrityError::SizeMismatch { name: "fmacfw_patch.bin", expected: 76, actual: 75 })
+        );
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn embedded_8801_images_match_manifest() {
+        for name in ["fmacfw.bin", "fmacfw_patch.bin"] {
+            let blob = crate::fw_source::FirmwareSource::load(&crate::fw_source::EmbeddedFirmwareSource, name).unwrap();
+            assert_eq!(check_firmware_image(name, &blob, FwIntegrityPolicy::Enforce), Ok(()), "{}", name);
+        }
+    }
+}
@@ -3,7 +3,7 @@
 //! 固件按名称在 `StarryOS/wireless/firmware` 查找：
 //! - 默认 feature `embed_firmware_8801`：仅嵌入 fmacfw.bin、fmacfw_patch.bin（8801 必需），需复制到 wireless/firmware/
 //! - feature `embed_firmware`：嵌入全部 .bin（依赖 embed_firmware_8801）
-//! - 未启用时返回 None，由上层通过 `set_wifi_firmware` 注册后由 `get_firmware_by_name` 的注册表提供
+//! - 未启用时返回 None，由上层通过 `set_wifi_firmware` 注册或配置 fw_source 来源链（如 PathFirmwareSource）提供
 
 #[cfg(feature = "embed_firmware")]
 pub fn get_firmware_by_name(name: &str) -> Option<&'static [u8]> {
@@ -2,7 +2,18 @@
 //! 对应 aic_bsp_driver.c 中 rwnx_plat_bin_fw_upload_android、rwnx_send_dbg_start_app_req 等
 //! 通过 CmdMgr + 平台提供的 tx_fn 发送 DBG_* 消息
 
-use crate::cmd::{LmacMsg, RwnxCmdMgr};
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use spin::Mutex;
+
+use crate::cmd::{cmd_flags, LmacMsg, RwnxCmdMgr};
+use crate::export::FwVerifyMode;
+use crate::fw_source::FirmwareBlob;
+use crate::sdio::ProductId;
 use crate::LMAC_MSG_MAX_LEN;
 
 /// DBG 任务 ID (TASK_DBG)
@@ -15,17 +26,34 @@ pub const DBG_MEM_READ_REQ: u16 = 1024;
 pub const DBG_MEM_READ_CFM: u16 = 1025;
 pub const DBG_MEM_WRITE_REQ: u16 = 1026;
 pub const DBG_MEM_WRITE_CFM: u16 = 1027;
+/// 固件日志过滤（rwnx_send_dbg_set_mod_filter_req / rwnx_send_dbg_set_sev_filter_req）
+pub const DBG_SET_MOD_FILTER_REQ: u16 = 1028;
+pub const DBG_SET_MOD_FILTER_CFM: u16 = 1029;
+pub const DBG_SET_SEV_FILTER_REQ: u16 = 1030;
+pub const DBG_SET_SEV_FILTER_CFM: u16 = 1031;
 pub const DBG_MEM_BLOCK_WRITE_REQ: u16 = 1034; // 1024+10
 pub const DBG_MEM_BLOCK_WRITE_CFM: u16 = 1035;
 pub const DBG_START_APP_REQ: u16 = 1036;
 pub const DBG_START_APP_CFM: u16 = 1037;
 pub const DBG_MEM_MASK_WRITE_REQ: u16 = 1038;
 pub const DBG_MEM_MASK_WRITE_CFM: u16 = 1039;
+/// RF 测试命令（rwnx_send_rftest_req），仅 RF 测试固件处理
+pub const DBG_RFTEST_CMD_REQ: u16 = 1040;
+pub const DBG_RFTEST_CMD_CFM: u16 = 1041;
 
 /// RAM FMAC 固件基址 (与 LicheeRV aic_bsp_driver.h RAM_FMAC_FW_ADDR 0x00120000 一致)
 pub const RAM_FMAC_FW_ADDR: u32 = 0x0012_0000;
 /// RAM FMAC 补丁固件地址 (LicheeRV RAM_FMAC_FW_PATCH_ADDR)
 pub const RAM_FMAC_FW_PATCH_ADDR: u32 = 0x0019_0000;
+/// 8800DC/D80 RF 测试固件（lmacfw_rf_*.bin）上传与启动地址（LicheeRV RAM_LMAC_FW_ADDR）
+pub const RAM_LMAC_FW_ADDR: u32 = 0x0015_0000;
+/// 8801 BT adid / patch 上传地址（LicheeRV FW_RAM_ADID_BASE_ADDR / FW_RAM_PATCH_BASE_ADDR，8801 的 fw_patch_table 无 PINF 段）
+pub const FW_RAM_ADID_BASE_ADDR: u32 = 0x0016_1928;
+pub const FW_RAM_PATCH_BASE_ADDR: u32 = 0x0010_0000;
+/// 8800D80 BT adid 默认地址（LicheeRV FW_RAM_ADID_BASE_ADDR_8800D80，fw_patch_table 无 AICBT_PINF_T 段时使用）
+pub const FW_RAM_ADID_BASE_ADDR_8800D80: u32 = 0x0020_1940;
+/// 8800D80 BT patch 默认地址（LicheeRV FW_RAM_PATCH_BASE_ADDR_8800D80）
+pub const FW_RAM_PATCH_BASE_ADDR_8800D80: u32 = 0x001e_0000;
 /// 启动类型：自动
 pub const HOST_START_APP_AUTO: u32 = 1;
 /// 启动类型：Dummy（供上层选用）
@@ -35,31 +63,85 @@ pub const HOST_START_APP_DUMMY: u32 = 0;
 /// 芯片版本寄存器地址（读得 chip_rev，LicheeRV aic_bsp_driver.c）
 pub const CHIP_REV_MEM_ADDR: u32 = 0x4050_0000;
 
-/// 固件存储：按名称在本地（embed）或注册表中查找，对应 LicheeRV 从路径/数组按名加载
-use spin::Mutex;
-const MAX_FIRMWARE_SLOTS: usize = 12;
-static WIFI_FIRMWARE_STORE: Mutex<[Option<(&'static str, &'static [u8])>; MAX_FIRMWARE_SLOTS]> =
-    Mutex::new([None; MAX_FIRMWARE_SLOTS]);
+/// 运行时固件注册表：按文件名保存固件，供 RegisteredFirmwareSource 查找（对应 LicheeRV 按名加载的内存来源）。
+/// 以 BTreeMap 保存，数量不设上限，registered_wifi_firmware 按名称顺序列出。
+static WIFI_FIRMWARE_STORE: Mutex<BTreeMap<String, FirmwareBlob>> = Mutex::new(BTreeMap::new());
 
-/// 注册 WiFi 固件数据（按文件名，如 "fmacfw.bin"、"fmacfw_patch.bin"），可多次调用注册多份
-pub fn set_wifi_firmware(name: &'static str, data: &'static [u8]) {
+/// 注册表操作失败
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FirmwareRegistryError {
+    /// 同名固件已注册（替换请用 replace_wifi_firmware）
+    AlreadyRegistered { name: String },
+    /// 未注册该名称
+    NotRegistered { name: String },
+}
+
+impl core::fmt::Display for FirmwareRegistryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FirmwareRegistryError::AlreadyRegistered { name } => write!(f, "firmware {}: already registered", name),
+            FirmwareRegistryError::NotRegistered { name } => write!(f, "firmware {}: not registered", name),
+        }
+    }
+}
+
+fn registry_insert(name: &str, blob: FirmwareBlob) -> Result<(), FirmwareRegistryError> {
     let mut guard = WIFI_FIRMWARE_STORE.lock();
-    if let Some(slot) = guard.iter_mut().find(|s| s.is_none()) {
-        *slot = Some((name, data));
+    if guard.contains_key(name) {
+        return Err(FirmwareRegistryError::AlreadyRegistered { name: String::from(name) });
     }
+    log::debug!(target: "wireless::bsp", "firmware {} registered ({} bytes)", name, blob.len());
+    guard.insert(String::from(name), blob);
+    Ok(())
+}
+
+/// 注册 'static 固件数据（按文件名，如 "fmacfw.bin"、"fmacfw_patch.bin"），同名已存在时返回 AlreadyRegistered
+pub fn set_wifi_firmware(name: &str, data: &'static [u8]) -> Result<(), FirmwareRegistryError> {
+    registry_insert(name, FirmwareBlob::Static(data))
+}
+
+/// 注册运行时获得的固件（如网络下载），注册表持有数据；同名已存在时返回 AlreadyRegistered
+pub fn register_wifi_firmware(name: &str, data: impl Into<Arc<[u8]>>) -> Result<(), FirmwareRegistryError> {
+    registry_insert(name, FirmwareBlob::Shared(data.into()))
 }
 
-/// 按文件名取已注册的 WiFi 固件
-pub fn get_wifi_firmware(name: &str) -> Option<&'static [u8]> {
-    let guard = WIFI_FIRMWARE_STORE.lock();
-    guard
+/// 注册或替换同名固件，返回被替换的旧数据；已交给上传流程的旧数据不受影响
+pub fn replace_wifi_firmware(name: &str, data: impl Into<Arc<[u8]>>) -> Option<FirmwareBlob> {
+    let blob = FirmwareBlob::Shared(data.into());
+    log::debug!(target: "wireless::bsp", "firmware {} replaced ({} bytes)", name, blob.len());
+    WIFI_FIRMWARE_STORE.lock().insert(String::from(name), blob)
+}
+
+/// 注销固件，返回其数据
+pub fn unregister_wifi_firmware(name: &str) -> Result<FirmwareBlob, FirmwareRegistryError> {
+    WIFI_FIRMWARE_STORE
+        .lock()
+        .remove(name)
+        .ok_or_else(|| FirmwareRegistryError::NotRegistered { name: String::from(name) })
+}
+
+/// 清空注册表
+pub fn clear_wifi_firmware() {
+    WIFI_FIRMWARE_STORE.lock().clear();
+}
+
+/// 已注册固件的 (文件名, 字节数)，按文件名排序
+pub fn registered_wifi_firmware() -> Vec<(String, usize)> {
+    WIFI_FIRMWARE_STORE
+        .lock()
         .iter()
-        .find_map(|s| s.as_ref().filter(|(n, _)| *n == name).map(|(_, d)| *d))
+        .map(|(name, blob)| (name.clone(), blob.len()))
+        .collect()
+}
+
+/// 按文件名取已注册的 WiFi 固件（Static/Shared 均只复制引用）
+pub fn get_wifi_firmware(name: &str) -> Option<FirmwareBlob> {
+    WIFI_FIRMWARE_STORE.lock().get(name).cloned()
 }
 
-/// 按名称取固件：先查本地（firmware_data，如 embed 或平台预置），再查注册表。对应 LicheeRV 按名从路径读
-pub fn get_firmware_by_name(name: &str) -> Option<&'static [u8]> {
-    crate::firmware_data::get_firmware_by_name(name).or_else(|| get_wifi_firmware(name))
+/// 按名称取固件：依次查询固件来源链（默认为嵌入 → 注册表，见 fw_source）。对应 LicheeRV 按名从路径读
+pub fn get_firmware_by_name(name: &str) -> Option<FirmwareBlob> {
+    crate::fw_source::load_from_sources(name)
 }
 
 /// 构建 DBG_MEM_READ_REQ 消息（param: memaddr 4 字节）
@@ -69,24 +151,13 @@ pub fn build_dbg_mem_read_req(mem_addr: u32) -> LmacMsg {
     msg
 }
 
-/// 从 DBG_MEM_READ_CFM 的 param 解析 memdata（与 LicheeRV 布局一致时：param 为 [memaddr][memdata]）
-///
-/// **布局**：LicheeRV `struct dbg_mem_read_cfm { u32 memaddr; u32 memdata; }`，E2A 消息 param 为
-/// `[memaddr LE 4B][memdata LE 4B]`。部分 8801 bootrom 返回顺序为 **[memdata][memaddr]**，需用
-/// `parse_dbg_mem_read_cfm_with_addr(param, requested_addr)` 根据请求地址自动识别。
-pub fn parse_dbg_mem_read_cfm(param: &[u8]) -> Option<u32> {
-    if param.len() < 8 {
-        return None;
-    }
-    let mut buf = [0u8; 4];
-    buf.copy_from_slice(&param[4..8]);
-    Some(u32::from_le_bytes(buf))
-}
-
 /// 解析 DBG_MEM_READ_CFM 的 memdata，根据请求地址自动识别 param 顺序（与 LicheeRV 差异修复）
 ///
 /// 若 param 为 [memaddr][memdata]（LicheeRV 顺序），则第二 4 字节为 memdata；
 /// 若 8801 bootrom 返回 [memdata][memaddr]，则第二 4 字节等于 requested_addr，此时取第一 4 字节为 memdata。
+///
+/// **布局**：LicheeRV `struct dbg_mem_read_cfm { u32 memaddr; u32 memdata; }`，E2A 消息 param 为
+/// `[memaddr LE 4B][memdata LE 4B]`。
 pub fn parse_dbg_mem_read_cfm_with_addr(param: &[u8], requested_addr: u32) -> Option<u32> {
     let (first, second) = parse_dbg_mem_read_cfm_full(param)?;
     let memdata = if second == requested_addr {
@@ -141,7 +212,7 @@ where
     F: FnMut(&LmacMsg) -> Result<(), E>,
 {
     let msg = build_dbg_mem_read_req(mem_addr);
-    let token = cmd_mgr.push(DBG_MEM_READ_CFM).ok_or(-12)?;
+    let token = cmd_mgr.push(DBG_MEM_READ_CFM, cmd_flags::REQ_CFM).ok_or(-12)?;
     tx_fn(&msg).map_err(|_| -5)?;
     log::info!(target: "wireless::bsp", "dbg_mem_read: request sent, waiting CFM (timeout {}ms)", timeout_ms);
     // 给卡足够时间处理请求并把 CFM 放入 rd_fifo（首包/ROM 可能较慢，100ms 后再轮询）
@@ -176,9 +247,12 @@ where
         msg.header.id, msg.header.dest_id, msg.header.src_id, msg.header.param_len,
         mem_addr, mem_data
     );
-    let token = cmd_mgr.push(DBG_MEM_WRITE_CFM).ok_or(-12)?;
+    let token = cmd_mgr.push(DBG_MEM_WRITE_CFM, cmd_flags::REQ_CFM).ok_or(-12)?;
     tx_fn(&msg).map_err(|_| -5)?;
-    cmd_mgr.wait_done(token, timeout_ms, poll_fn, tick)
+    cmd_mgr.wait_done(token, timeout_ms, poll_fn, tick)?;
+    // 与 LicheeRV rwnx_send_msg 返回前 kfree(cmd) 一致：释放 slot，否则连续写会占满 cmd_mgr
+    let _ = cmd_mgr.take_cfm(token, &mut [0u8; 16]);
+    Ok(())
 }
 
 /// 发送 DBG_MEM_MASK_WRITE_REQ
@@ -195,9 +269,11 @@ where
     F: FnMut(&LmacMsg) -> Result<(), E>,
 {
     let msg = build_dbg_mem_mask_write_req(mem_addr, mem_mask, mem_data);
-    let token = cmd_mgr.push(DBG_MEM_MASK_WRITE_CFM).ok_or(-12)?;
+    let token = cmd_mgr.push(DBG_MEM_MASK_WRITE_CFM, cmd_flags::REQ_CFM).ok_or(-12)?;
     tx_fn(&msg).map_err(|_| -5)?;
-    cmd_mgr.wait_done(token, timeout_ms, poll_fn, None)
+    cmd_mgr.wait_done(token, timeout_ms, poll_fn, None)?;
+    let _ = cmd_mgr.take_cfm(token, &mut [0u8; 16]);
+    Ok(())
 }
 
 /// 构建 DBG_MEM_BLOCK_WRITE_REQ 消息
@@ -227,50 +303,309 @@ pub fn build_dbg_start_app_req(boot_addr: u32, boot_type: u32) -> LmacMsg {
     msg
 }
 
+/// 构建 DBG_SET_MOD_FILTER_REQ 消息
+/// param: mod_filter(4)，按模块位图打开固件日志
+pub fn build_dbg_set_mod_filter_req(mod_filter: u32) -> LmacMsg {
+    let mut msg = LmacMsg::new(DBG_SET_MOD_FILTER_REQ, TASK_DBG, DRV_TASK_ID, 4);
+    msg.param[0..4].copy_from_slice(&mod_filter.to_le_bytes());
+    msg
+}
+
+/// 构建 DBG_SET_SEV_FILTER_REQ 消息
+/// param: sev_filter(4)，固件只输出不高于该级别的日志
+pub fn build_dbg_set_sev_filter_req(sev_filter: u32) -> LmacMsg {
+    let mut msg = LmacMsg::new(DBG_SET_SEV_FILTER_REQ, TASK_DBG, DRV_TASK_ID, 4);
+    msg.param[0..4].copy_from_slice(&sev_filter.to_le_bytes());
+    msg
+}
+
 /// 固件块上传：与 LicheeRV aic_bsp_driver.c rwnx_plat_bin_fw_upload_android 完全一致，每块 1024 字节，每块等待 DBG_MEM_BLOCK_WRITE_CFM。
 /// 每块：push_fn → tx_fn → wait_fn(token)，无“只发不等”。
 pub fn fw_upload_blocks<F, E, P, W>(
+    tx_fn: F,
+    mem_addr: u32,
+    data: &[u8],
+    push_fn: P,
+    wait_fn: W,
+) -> Result<(), i32>
+where
+    F: FnMut(&LmacMsg) -> Result<(), E>,
+    P: FnMut() -> Option<usize>,
+    W: FnMut(usize) -> Result<(), i32>,
+{
+    fw_upload_blocks_windowed(tx_fn, mem_addr, data, FW_UPLOAD_BLOCK_SIZE, 1, push_fn, wait_fn).map(|_| ())
+}
+
+/// 窗口化固件上传：最多 window 个 DBG_MEM_BLOCK_WRITE_REQ 同时在途（各占一个 cmd_mgr 槽位），
+/// 窗口满时按提交顺序 wait_fn 最早的块（同 id 的 CFM 由 cmd_mgr 按提交顺序完成）。window = 1 即 fw_upload_blocks。
+/// 出错时仍等待已发出的块以释放槽位，再返回首个错误。
+pub fn fw_upload_blocks_windowed<F, E, P, W>(
     mut tx_fn: F,
     mem_addr: u32,
     data: &[u8],
+    block_size: usize,
+    window: usize,
     mut push_fn: P,
     mut wait_fn: W,
-) -> Result<(), i32>
+) -> Result<FwUploadStats, i32>
 where
     F: FnMut(&LmacMsg) -> Result<(), E>,
     P: FnMut() -> Option<usize>,
     W: FnMut(usize) -> Result<(), i32>,
 {
-    /// 与 LicheeRV 8801 BSP 一致：if (size > 1024) for (i = 0; i < (size - 1024); i += 1024) 每块 1KB
-    const BLOCK: usize = 1024;
-    let total_blocks = (data.len() + BLOCK - 1) / BLOCK;
+    let block_size = block_size.clamp(4, LMAC_MSG_MAX_LEN - 8);
+    let window = window.max(1);
+    let total_blocks = data.len().div_ceil(block_size);
     log::info!(
         target: "wireless::bsp",
-        "fw_upload_blocks: start addr=0x{:08x} len={} ({} blocks)",
+        "fw_upload_blocks: start addr=0x{:08x} len={} ({} blocks of {}, window {})",
         mem_addr,
         data.len(),
-        total_blocks
+        total_blocks,
+        block_size,
+        window
     );
+    let start_ns = axhal::time::monotonic_time_nanos();
+    let mut in_flight: VecDeque<usize> = VecDeque::with_capacity(window);
     let mut addr = mem_addr;
     let mut off = 0;
     let mut block_index: usize = 0;
+    let mut max_in_flight: usize = 0;
+    let mut result: Result<(), i32> = Ok(());
     while off < data.len() {
-        let len = (data.len() - off).min(BLOCK);
+        if in_flight.len() >= window {
+            let token = in_flight.pop_front().unwrap();
+            if let Err(e) = wait_fn(token) {
+                result = Err(e);
+                break;
+            }
+        }
+        let len = (data.len() - off).min(block_size);
         let block = &data[off..off + len];
-        let msg = build_dbg_mem_block_write_req(addr, len as u32, block).ok_or(-12)?; // -ENOMEM
-        let token = push_fn().ok_or(-12)?;
-        tx_fn(&msg).map_err(|_| -5)?; // -EIO
-        wait_fn(token)?;
+        let msg = match build_dbg_mem_block_write_req(addr, len as u32, block) {
+            Some(m) => m,
+            None => {
+                result = Err(-12); // -ENOMEM
+                break;
+            }
+        };
+        let token = match push_fn() {
+            Some(t) => t,
+            None => {
+                result = Err(-12);
+                break;
+            }
+        };
+        if tx_fn(&msg).is_err() {
+            result = Err(-5); // -EIO，未发出的块不会有 CFM，不入在途队列
+            break;
+        }
+        in_flight.push_back(token);
+        max_in_flight = max_in_flight.max(in_flight.len());
         addr += len as u32;
         off += len;
         block_index += 1;
         let pct = (off * 100) / data.len();
-        if block_index % 32 == 0 || pct == 25 || pct == 50 || pct == 75 || off == data.len() {
-            log::info!(target: "wireless::bsp", "fw_upload_blocks: progress block {}/{} ({}% done)", block_index, total_blocks, pct);
+        if block_index.is_multiple_of(32) || pct == 25 || pct == 50 || pct == 75 || off == data.len() {
+            log::info!(target: "wireless::bsp", "fw_upload_blocks: progress block {}/{} ({}% sent)", block_index, total_blocks, pct);
         }
     }
-    log::info!(target: "wireless::bsp", "fw_upload_blocks: done, {} blocks written", block_index);
-    Ok(())
+    while let Some(token) = in_flight.pop_front() {
+        let r = wait_fn(token);
+        if result.is_ok() {
+            result = r;
+        }
+    }
+    result?;
+    let stats = FwUploadStats {
+        mem_addr,
+        bytes: data.len(),
+        blocks: block_index,
+        block_size,
+        window,
+        max_in_flight,
+        elapsed_ns: axhal::time::monotonic_time_nanos().wrapping_sub(start_ns),
+    };
+    log::info!(
+        target: "wireless::bsp",
+        "fw_upload_blocks: done, {} blocks written in {} us ({} KiB/s)",
+        block_index,
+        stats.elapsed_ns / 1000,
+        stats.kib_per_sec()
+    );
+    Ok(stats)
+}
+
+/// fw_upload_blocks 的块大小（8801 与 LicheeRV 一致），回读校验按同样的块划分，便于只重传不符的块
+pub const FW_UPLOAD_BLOCK_SIZE: usize = 1024;
+/// 8800DC/DW/D80 可接受的大块：16B 头 + 8B memaddr/memsize + 1280 + SDIO 头尾对齐仍在 CMD_BUF_MAX(1536) 内
+pub const FW_UPLOAD_BLOCK_SIZE_LARGE: usize = 1280;
+/// 8800DC/DW/D80 默认同时在途的块数（各占一个 cmd_mgr 槽位，按提交顺序回 CFM）
+pub const FW_UPLOAD_WINDOW_DEFAULT: usize = 4;
+/// 8801 默认窗口：与 LicheeRV rwnx_plat_bin_fw_upload_android 一致逐块等 CFM，可经 aicbsp_set_fw_upload_window 开启流水线
+pub const FW_UPLOAD_WINDOW_8801: usize = 1;
+
+/// 一次固件上传的计时统计（启动耗时基准）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FwUploadStats {
+    pub mem_addr: u32,
+    pub bytes: usize,
+    pub blocks: usize,
+    pub block_size: usize,
+    pub window: usize,
+    /// 实际同时在途的最大块数（window 为 1 时恒为 1）
+    pub max_in_flight: usize,
+    /// axhal::time::monotonic_time_nanos() 计得的总耗时
+    pub elapsed_ns: u64,
+}
+
+impl FwUploadStats {
+    /// 吞吐率（KiB/s），耗时为 0 时返回 0
+    pub fn kib_per_sec(&self) -> u64 {
+        if self.elapsed_ns == 0 {
+            return 0;
+        }
+        (self.bytes as u64 * 1_000_000_000 / 1024) / self.elapsed_ns
+    }
+}
+
+/// 最近一次 aicbsp_driver_fw_init 中各段上传的统计（wl_fw、patch），每次固件加载前清空
+static FW_UPLOAD_STATS: Mutex<Vec<FwUploadStats>> = Mutex::new(Vec::new());
+/// 按 ProductId 下标的上传窗口：8801 为 FW_UPLOAD_WINDOW_8801，其余为 FW_UPLOAD_WINDOW_DEFAULT
+static FW_UPLOAD_WINDOW: [AtomicUsize; 5] = [
+    AtomicUsize::new(FW_UPLOAD_WINDOW_8801),
+    AtomicUsize::new(FW_UPLOAD_WINDOW_DEFAULT),
+    AtomicUsize::new(FW_UPLOAD_WINDOW_DEFAULT),
+    AtomicUsize::new(FW_UPLOAD_WINDOW_DEFAULT),
+    AtomicUsize::new(FW_UPLOAD_WINDOW_DEFAULT),
+];
+
+/// 设置某一芯片的固件上传窗口（同时在途的 DBG_MEM_BLOCK_WRITE 数），1 即逐块等 CFM 的 LicheeRV 行为；
+/// 只影响该 product_id 的后续 aicbsp_driver_fw_init，实际窗口不超过 cmd_mgr 队列深度
+pub fn aicbsp_set_fw_upload_window(product_id: ProductId, window: usize) {
+    FW_UPLOAD_WINDOW[product_id as usize].store(window.max(1), Ordering::Relaxed);
+}
+
+pub(crate) fn fw_upload_window(product_id: ProductId) -> usize {
+    FW_UPLOAD_WINDOW[product_id as usize].load(Ordering::Relaxed)
+}
+
+/// 固件上传块大小：8801 与 LicheeRV 一致 1KB，8800DC/DW/D80 用大块
+pub(crate) fn fw_upload_block_size(product_id: ProductId) -> usize {
+    if product_id == ProductId::Aic8801 {
+        FW_UPLOAD_BLOCK_SIZE
+    } else {
+        FW_UPLOAD_BLOCK_SIZE_LARGE
+    }
+}
+
+/// 取最近一次固件加载的上传统计
+pub fn aicbsp_fw_upload_stats() -> Vec<FwUploadStats> {
+    FW_UPLOAD_STATS.lock().clone()
+}
+
+pub(crate) fn fw_upload_stats_reset() {
+    FW_UPLOAD_STATS.lock().clear();
+}
+
+pub(crate) fn fw_upload_stats_record(stats: FwUploadStats) {
+    FW_UPLOAD_STATS.lock().push(stats);
+}
+
+/// 回读校验发现的不符块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FwVerifyMismatch {
+    /// 块起始地址（设备侧）
+    pub addr: u32,
+    /// 块在 data 中的偏移
+    pub offset: usize,
+    pub len: usize,
+    /// 块内第一个不符的字：设备侧地址、期望值、回读值（尾部不足 4 字节的字只含有效字节）
+    pub word_addr: u32,
+    pub expected: u32,
+    pub actual: u32,
+    /// 块内参与比较且不符的字数
+    pub bad_words: usize,
+}
+
+/// 按 FwVerifyMode 回读已上传区域，返回不符的块（空表示一致）。
+///
+/// 引导 ROM 无校验和请求，只能经 read_fn（DBG_MEM_READ_REQ）逐字回读；Sampled 每块比较首/中/尾 3 个字，
+/// Full 比较每个字。每个字单独与期望值比较（不用累加和，避免两处错误相互抵消）；
+/// 尾部不足 4 字节的字只比较有效字节。read_fn 出错时直接返回该错误。
+pub fn fw_verify_blocks<R>(
+    mem_addr: u32,
+    data: &[u8],
+    mode: FwVerifyMode,
+    mut read_fn: R,
+) -> Result<Vec<FwVerifyMismatch>, i32>
+where
+    R: FnMut(u32) -> Result<u32, i32>,
+{
+    let mut mismatches = Vec::new();
+    if mode == FwVerifyMode::Off {
+        return Ok(mismatches);
+    }
+    let mut words_checked = 0usize;
+    for (index, block) in data.chunks(FW_UPLOAD_BLOCK_SIZE).enumerate() {
+        let offset = index * FW_UPLOAD_BLOCK_SIZE;
+        let addr = mem_addr + offset as u32;
+        let words = block.len().div_ceil(4);
+        let mut bad: Option<FwVerifyMismatch> = None;
+        let mut check_word = |w: usize| -> Result<(), i32> {
+            let bytes = &block[w * 4..(w * 4 + 4).min(block.len())];
+            let mut raw = [0u8; 4];
+            raw[..bytes.len()].copy_from_slice(bytes);
+            let mask = if bytes.len() == 4 { u32::MAX } else { (1u32 << (bytes.len() * 8)) - 1 };
+            let expected = u32::from_le_bytes(raw);
+            let word_addr = addr + (w * 4) as u32;
+            let actual = read_fn(word_addr)? & mask;
+            words_checked += 1;
+            if actual != expected {
+                let m = bad.get_or_insert(FwVerifyMismatch {
+                    addr,
+                    offset,
+                    len: block.len(),
+                    word_addr,
+                    expected,
+                    actual,
+                    bad_words: 0,
+                });
+                m.bad_words += 1;
+            }
+            Ok(())
+        };
+        match mode {
+            FwVerifyMode::Full => {
+                for w in 0..words {
+                    check_word(w)?;
+                }
+            }
+            _ => {
+                let samples = [0, words / 2, words - 1];
+                for (i, &w) in samples.iter().enumerate() {
+                    if samples[..i].contains(&w) {
+                        continue;
+                    }
+                    check_word(w)?;
+                }
+            }
+        }
+        if let Some(m) = bad {
+            log::warn!(
+                target: "wireless::bsp",
+                "fw_verify: block mismatch addr=0x{:08x} len={} {} bad words, first at 0x{:08x} expected=0x{:08x} actual=0x{:08x}",
+                addr, block.len(), m.bad_words, m.word_addr, m.expected, m.actual
+            );
+            mismatches.push(m);
+        }
+    }
+    log::info!(
+        target: "wireless::bsp",
+        "fw_verify: addr=0x{:08x} len={} mode={:?} {} words checked, {} bad blocks",
+        mem_addr, data.len(), mode, words_checked, mismatches.len()
+    );
+    Ok(mismatches)
 }
 
 /// 启动固件：发送 DBG_START_APP_REQ，等待 DBG_START_APP_CFM
@@ -287,7 +622,63 @@ where
 {
     log::info!(target: "wireless::bsp", "fw_start_app addr=0x{:08x} type={}", boot_addr, boot_type);
     let msg = build_dbg_start_app_req(boot_addr, boot_type);
-    let token = cmd_mgr.push(DBG_START_APP_CFM).ok_or(-12)?;
+    let token = cmd_mgr.push(DBG_START_APP_CFM, cmd_flags::REQ_CFM).ok_or(-12)?;
     tx_fn(&msg).map_err(|_| -5)?;
-    cmd_mgr.wait_done(token, timeout_ms, poll_fn, None)
+    cmd_mgr.wait_done(token, timeout_ms, poll_fn, None)?;
+    let _ = cmd_mgr.take_cfm(token, &mut [0u8; 16]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    fn read_from(mem_addr: u32, mem: &[u8]) -> impl FnMut(u32) -> Result<u32, i32> + '_ {
+        move |addr| {
+            let off = (addr - mem_addr) as usize;
+            let mut raw = [0u8; 4];
+            let end = (off + 4).min(mem.len());
+            raw[..end - off].copy_from_slice(&mem[off..end]);
+            Ok(u32::from_le_bytes(raw))
+        }
+    }
+
+    #[test]
+    fn verify_full_catches_compensating_word_errors() {
+        let base = 0x0010_0000;
+        let data = image(FW_UPLOAD_BLOCK_SIZE * 2 + 6);
+        assert!(fw_verify_blocks(base, &data, FwVerifyMode::Full, read_from(base, &data)).unwrap().is_empty());
+
+        // 第二块内 +1 / -1 两处错误：累加和不变，逐字比较必须发现
+        let mut mem = data.clone();
+        let w0 = FW_UPLOAD_BLOCK_SIZE + 8;
+        let w1 = FW_UPLOAD_BLOCK_SIZE + 16;
+        mem[w0] = mem[w0].wrapping_add(1);
+        mem[w1] = mem[w1].wrapping_sub(1);
+        let bad = fw_verify_blocks(base, &data, FwVerifyMode::Full, read_from(base, &mem)).unwrap();
+        assert_eq!(bad.len(), 1);
+        let m = bad[0];
+        assert_eq!(m.offset, FW_UPLOAD_BLOCK_SIZE);
+        assert_eq!(m.word_addr, base + w0 as u32);
+        assert_eq!(m.expected, u32::from_le_bytes(data[w0..w0 + 4].try_into().unwrap()));
+        assert_eq!(m.actual, u32::from_le_bytes(mem[w0..w0 + 4].try_into().unwrap()));
+        assert_eq!(m.bad_words, 2);
+    }
+
+    #[test]
+    fn verify_tail_word_ignores_bytes_past_image() {
+        let base = 0x0020_0000;
+        let data = image(10);
+        let mut mem = data.clone();
+        mem.extend_from_slice(&[0xaa, 0xbb]);
+        assert!(fw_verify_blocks(base, &data, FwVerifyMode::Sampled, read_from(base, &mem)).unwrap().is_empty());
+        mem[9] ^= 0xff;
+        let bad = fw_verify_blocks(base, &data, FwVerifyMode::Sampled, read_from(base, &mem)).unwrap();
+        assert_eq!(bad.len(), 1);
+        assert_eq!(bad[0].word_addr, base + 8);
+    }
 }
@@ -0,0 +1,222 @@
+//! 固件来源链（对应 LicheeRV rwnx_load_firmware：aic_fw_path + "/" + name 经 request_firmware/filp_open 读入）
+//!
+//! get_firmware_by_name 依次询问链上每个 FirmwareSource，返回第一个命中的固件。
+//! 默认链为 [EmbeddedFirmwareSource, RegisteredFirmwareSource]，与引入来源链之前的查找顺序一致；
+//! 挂载根文件系统后可追加 PathFirmwareSource，从 DEFAULT_FW_PATH 读取，无需重编内核镜像即可替换固件。
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use spin::Mutex;
+
+use crate::{DEFAULT_FW_PATH, FW_PATH_MAX};
+
+/// 一份固件数据：编译期嵌入的为 Embedded，运行时以 'static 切片注册的为 Static，从文件系统读入的为堆上缓冲，
+/// 运行时注册的为共享缓冲（查找时不拷贝）
+#[derive(Debug, Clone)]
+pub enum FirmwareBlob {
+    Embedded(&'static [u8]),
+    Static(&'static [u8]),
+    Owned(Vec<u8>),
+    Shared(Arc<[u8]>),
+}
+
+impl Deref for FirmwareBlob {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FirmwareBlob::Embedded(d) | FirmwareBlob::Static(d) => d,
+            FirmwareBlob::Owned(v) => v,
+            FirmwareBlob::Shared(a) => a,
+        }
+    }
+}
+
+impl FirmwareBlob {
+    /// 是否来自 EmbeddedFirmwareSource（与 FIRMWARE_MANIFEST 同源，可按清单校验）
+    pub fn is_embedded(&self) -> bool {
+        matches!(self, FirmwareBlob::Embedded(_))
+    }
+}
+
+impl AsRef<[u8]> for FirmwareBlob {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// 固件来源：按文件名（如 "fmacfw.bin"）返回固件，未找到返回 None
+pub trait FirmwareSource: Send + Sync {
+    /// 来源名称，仅用于日志
+    fn name(&self) -> &str;
+    fn load(&self, fw_name: &str) -> Option<FirmwareBlob>;
+}
+
+/// 编译期嵌入的固件（feature embed_firmware_8801 / embed_firmware）
+pub struct EmbeddedFirmwareSource;
+
+impl FirmwareSource for EmbeddedFirmwareSource {
+    fn name(&self) -> &str {
+        "embedded"
+    }
+
+    fn load(&self, fw_name: &str) -> Option<FirmwareBlob> {
+        crate::firmware_data::get_firmware_by_name(fw_name).map(FirmwareBlob::Embedded)
+    }
+}
+
+/// 运行时注册表（set_wifi_firmware / register_wifi_firmware 等）
+pub struct RegisteredFirmwareSource;
+
+impl FirmwareSource for RegisteredFirmwareSource {
+    fn name(&self) -> &str {
+        "registered"
+    }
+
+    fn load(&self, fw_name: &str) -> Option<FirmwareBlob> {
+        crate::fw_load::get_wifi_firmware(fw_name)
+    }
+}
+
+/// 读整个文件的平台回调（如基于 axfs::api::read），路径不存在或读失败返回 None
+pub type FirmwareFileReader = fn(path: &str) -> Option<Vec<u8>>;
+
+/// 按 `<dir>/<fw_name>` 从文件系统读取，对应 LicheeRV aic_fw_path 模块参数
+pub struct PathFirmwareSource {
+    dir: String,
+    reader: FirmwareFileReader,
+}
+
+impl PathFirmwareSource {
+    /// dir 为空时使用 DEFAULT_FW_PATH
+    pub fn new(dir: &str, reader: FirmwareFileReader) -> Self {
+        let dir = if dir.is_empty() { DEFAULT_FW_PATH } else { dir };
+        Self {
+            dir: String::from(dir.trim_end_matches('/')),
+            reader,
+        }
+    }
+
+    /// 使用 DEFAULT_FW_PATH
+    pub fn with_default_path(reader: FirmwareFileReader) -> Self {
+        Self::new(DEFAULT_FW_PATH, reader)
+    }
+
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+}
+
+impl FirmwareSource for PathFirmwareSource {
+    fn name(&self) -> &str {
+        &self.dir
+    }
+
+    fn load(&self, fw_name: &str) -> Option<FirmwareBlob> {
+        // 与 LicheeRV 一致：路径超过 FW_PATH_MAX 视为无效
+        if self.dir.len() + 1 + fw_name.len() >= FW_PATH_MAX {
+            log::warn!(target: "wireless::bsp", "fw path too long: {}/{}", self.dir, fw_name);
+            return None;
+        }
+        let mut path = String::with_capacity(FW_PATH_MAX);
+        path.push_str(&self.dir);
+        path.push('/');
+        path.push_str(fw_name);
+        (self.reader)(&path).map(FirmwareBlob::Owned)
+    }
+}
+
+/// 来源链快照：查找时只在锁内克隆 Arc，读取（可能是慢速文件系统 I/O）在锁外进行
+type SourceChain = Arc<Vec<Arc<dyn FirmwareSource>>>;
+
+/// 当前来源链；None 表示尚未配置，使用 default_firmware_sources()
+static FIRMWARE_SOURCES: Mutex<Option<SourceChain>> = Mutex::new(None);
+
+/// 默认来源链：嵌入 → 注册表
+pub fn default_firmware_sources() -> Vec<Box<dyn FirmwareSource>> {
+    alloc::vec![Box::new(EmbeddedFirmwareSource), Box::new(RegisteredFirmwareSource)]
+}
+
+fn to_chain(chain: Vec<Box<dyn FirmwareSource>>) -> Vec<Arc<dyn FirmwareSource>> {
+    chain.into_iter().map(Arc::from).collect()
+}
+
+/// 按 f 修改当前链的副本后整体替换；正在进行的查找继续使用旧快照
+fn update_sources(f: impl FnOnce(&mut Vec<Arc<dyn FirmwareSource>>)) {
+    let mut guard = FIRMWARE_SOURCES.lock();
+    let mut chain = match guard.as_ref() {
+        Some(cur) => cur.iter().cloned().collect(),
+        None => to_chain(default_firmware_sources()),
+    };
+    f(&mut chain);
+    *guard = Some(Arc::new(chain));
+}
+
+/// 替换整个来源链（按顺序查找，先命中者优先）
+pub fn set_firmware_sources(chain: Vec<Box<dyn FirmwareSource>>) {
+    *FIRMWARE_SOURCES.lock() = Some(Arc::new(to_chain(chain)));
+}
+
+/// 在链首插入来源（优先于嵌入固件，如根文件系统上的新固件）
+pub fn prepend_firmware_source(src: Box<dyn FirmwareSource>) {
+    update_sources(|chain| chain.insert(0, Arc::from(src)));
+}
+
+/// 在链尾追加来源（仅作兜底）
+pub fn append_firmware_source(src: Box<dyn FirmwareSource>) {
+    update_sources(|chain| chain.push(Arc::from(src)));
+}
+
+/// 恢复默认来源链
+pub fn reset_firmware_sources() {
+    *FIRMWARE_SOURCES.lock() = None;
+}
+
+/// 依次查询来源链：持锁只取链快照，各来源的 load 在锁外执行，
+/// 因此来源内部可以阻塞读文件，也可以再调用 get_firmware_by_name / set_firmware_sources
+pub(crate) fn load_from_sources(fw_name: &str) -> Option<FirmwareBlob> {
+    let chain = FIRMWARE_SOURCES
+        .lock()
+        .get_or_insert_with(|| Arc::new(to_chain(default_firmware_sources())))
+        .clone();
+    chain.iter().find_map(|src| {
+        let blob = src.load(fw_name)?;
+        log::debug!(target: "wireless::bsp", "firmware {} from source {} ({} bytes)", fw_name, src.name(), blob.len());
+        Some(blob)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 把 "alias.bin" 解析为链上另一个名字：load 内再次查询来源链
+    struct AliasSource;
+
+    impl FirmwareSource for AliasSource {
+        fn name(&self) -> &str {
+            "alias"
+        }
+
+        fn load(&self, fw_name: &str) -> Option<FirmwareBlob> {
+            match fw_name {
+                "alias.bin" => load_from_sources("fw_source_test_target.bin"),
+                "fw_source_test_target.bin" => Some(FirmwareBlob::Static(b"target")),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn source_load_runs_without_holding_the_chain_lock() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        prepend_firmware_source(Box::new(AliasSource));
+        let blob = load_from_sources("alias.bin");
+        reset_firmware_sources();
+        assert_eq!(blob.as_deref(), Some(&b"target"[..]));
+    }
+}
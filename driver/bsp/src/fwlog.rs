@@ -0,0 +1,165 @@
+//! 固件日志（对应 LicheeRV rwnx_rx_handle_print 与 debugfs fw_log 环形缓冲）
+//!
+//! poll_rx_one 收到 SDIO_TYPE_CFG_PRINT(0x13) 帧后把正文交给 fwlog_handle_print，按当前 FwLogSink 输出：
+//! log crate（target "wireless::fw"，按行输出）、环形缓冲（aicbsp_fwlog_read 取出）或用户回调。
+//! 固件默认不打印；fwlog_en 置位时 aicbsp_driver_fw_init 在 START_APP 后调用 aicbsp_fwlog_enable 打开过滤器。
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use crate::cmd::LmacMsg;
+use crate::fw_load::{
+    build_dbg_set_mod_filter_req, build_dbg_set_sev_filter_req, DBG_SET_MOD_FILTER_CFM, DBG_SET_SEV_FILTER_CFM,
+};
+
+/// 环形缓冲容量（LicheeRV FW_LOG_SIZE）
+pub const FW_LOG_SIZE: usize = 10240;
+/// 打开全部固件模块（DBG_MOD_ALL）
+pub const FW_LOG_MOD_ALL: u32 = 0xffff_ffff;
+/// log 输出时单行最大长度，超过即截断输出
+const FW_LOG_LINE_MAX: usize = 256;
+/// 等待过滤器 CFM 的超时
+const FW_LOG_CMD_TIMEOUT_MS: u32 = 2000;
+
+/// 固件日志级别（DBG_SEV_IDX_*），固件只输出不高于该级别的日志
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u32)]
+pub enum FwLogLevel {
+    None = 0,
+    Critical = 1,
+    Error = 2,
+    Warning = 3,
+    #[default]
+    Info = 4,
+    Verbose = 5,
+}
+
+/// 用户回调：每个 PRINT 帧的正文（已去掉 4 字节 SDIO 头与对齐填充）
+pub type FwLogCb = fn(text: &[u8]);
+
+/// PRINT 帧的去向
+#[derive(Debug, Clone, Copy, Default)]
+pub enum FwLogSink {
+    /// 丢弃
+    Off,
+    /// log::info!(target: "wireless::fw")，按换行切分
+    #[default]
+    Log,
+    /// 写入 FW_LOG_SIZE 字节环形缓冲，满时覆盖最旧数据
+    Ring,
+    Callback(FwLogCb),
+}
+
+struct FwLogState {
+    sink: FwLogSink,
+    /// Log 模式下未遇到换行的半行
+    line: Vec<u8>,
+    ring: VecDeque<u8>,
+    /// Ring 模式下因覆盖而丢失的字节数
+    dropped: usize,
+}
+
+static FW_LOG: Mutex<FwLogState> = Mutex::new(FwLogState {
+    sink: FwLogSink::Log,
+    line: Vec::new(),
+    ring: VecDeque::new(),
+    dropped: 0,
+});
+
+/// 设置 PRINT 帧去向；切换时丢弃 Log 模式的半行
+pub fn aicbsp_set_fwlog_sink(sink: FwLogSink) {
+    let mut st = FW_LOG.lock();
+    st.sink = sink;
+    st.line.clear();
+}
+
+pub fn aicbsp_fwlog_sink() -> FwLogSink {
+    FW_LOG.lock().sink
+}
+
+/// 从环形缓冲取出最多 buf.len() 字节（取出即删除），返回实际长度
+pub fn aicbsp_fwlog_read(buf: &mut [u8]) -> usize {
+    let mut st = FW_LOG.lock();
+    let n = buf.len().min(st.ring.len());
+    for (dst, src) in buf.iter_mut().zip(st.ring.drain(..n)) {
+        *dst = src;
+    }
+    n
+}
+
+/// 清空环形缓冲，返回清空前累计被覆盖的字节数
+pub fn aicbsp_fwlog_clear() -> usize {
+    let mut st = FW_LOG.lock();
+    st.ring.clear();
+    core::mem::take(&mut st.dropped)
+}
+
+fn emit_line(line: &[u8]) {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    if !line.is_empty() {
+        log::info!(target: "wireless::fw", "{}", String::from_utf8_lossy(line));
+    }
+}
+
+/// rwnx_rx_handle_print：处理一个 PRINT 帧正文（固件 printf 输出，可能不以换行结尾、带 0 填充）
+pub(crate) fn fwlog_handle_print(text: &[u8]) {
+    let mut st = FW_LOG.lock();
+    match st.sink {
+        FwLogSink::Off => {}
+        FwLogSink::Callback(cb) => {
+            drop(st);
+            cb(text);
+        }
+        FwLogSink::Ring => {
+            for &b in text.iter().filter(|&&b| b != 0) {
+                if st.ring.len() == FW_LOG_SIZE {
+                    st.ring.pop_front();
+                    st.dropped += 1;
+                }
+                st.ring.push_back(b);
+            }
+        }
+        FwLogSink::Log => {
+            // 先把完整行拷出，释放 FW_LOG 后再打印：本函数在 CMD_MGR 锁内被调用，log 后端可能较慢
+            let mut lines: Vec<Vec<u8>> = Vec::new();
+            for &b in text.iter().filter(|&&b| b != 0) {
+                if b == b'\n' {
+                    lines.push(core::mem::take(&mut st.line));
+                    continue;
+                }
+                st.line.push(b);
+                if st.line.len() >= FW_LOG_LINE_MAX {
+                    lines.push(core::mem::take(&mut st.line));
+                }
+            }
+            drop(st);
+            for line in &lines {
+                emit_line(line);
+            }
+        }
+    }
+}
+
+/// 打开固件日志：DBG_SET_MOD_FILTER_REQ(全部模块) + DBG_SET_SEV_FILTER_REQ(level)；须在 START_APP 之后调用
+pub fn aicbsp_fwlog_enable(level: FwLogLevel) -> Result<(), i32> {
+    fwlog_enable_with(&mut |msg, cfm_id| crate::sdio::send_dbg_req(msg, cfm_id, FW_LOG_CMD_TIMEOUT_MS), level)
+}
+
+/// 同 aicbsp_fwlog_enable，请求经 send 发出并等 CFM（固件初始化时走初始化所用的 SdioOps）
+pub(crate) fn fwlog_enable_with(
+    send: &mut dyn FnMut(&LmacMsg, u16) -> Result<(), i32>,
+    level: FwLogLevel,
+) -> Result<(), i32> {
+    send(&build_dbg_set_mod_filter_req(FW_LOG_MOD_ALL), DBG_SET_MOD_FILTER_CFM)?;
+    send(&build_dbg_set_sev_filter_req(level as u32), DBG_SET_SEV_FILTER_CFM)?;
+    log::info!(target: "wireless::bsp", "fwlog enabled (sev={:?})", level);
+    Ok(())
+}
+
+/// 关闭固件日志（模块位图清零）
+pub fn aicbsp_fwlog_disable() -> Result<(), i32> {
+    crate::sdio::send_dbg_req(&build_dbg_set_mod_filter_req(0), DBG_SET_MOD_FILTER_CFM, FW_LOG_CMD_TIMEOUT_MS)
+}
@@ -0,0 +1,229 @@
+//! IPC 追踪：最近若干条 A2E/E2A 消息的环形记录（LicheeRV 无对应实现，仅靠 printk 打 msg_id）
+//!
+//! 常开、定长：submit_cmd_tx_and_wait_tx_done / driver_fw_init 的 tx_fn 记录 A2E，parse_one_cfm_at 记录 E2A。
+//! 命令超时后可用 ipc_trace_snapshot 取出，再导出为 JSON-lines 或 pcapng（LINKTYPE_USER0，包体为 lmac 头 + param 前缀）。
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use spin::Mutex;
+
+/// 环形缓冲条目数
+pub const IPC_TRACE_DEPTH: usize = 128;
+/// 每条保存的 param 前缀字节数
+pub const IPC_TRACE_PAYLOAD_PREFIX: usize = 32;
+
+/// 消息方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcTraceDir {
+    /// 主机 → 固件（lmac_msg）
+    A2E,
+    /// 固件 → 主机（ipc_e2a_msg）
+    E2A,
+}
+
+impl IpcTraceDir {
+    fn as_str(self) -> &'static str {
+        match self {
+            IpcTraceDir::A2E => "a2e",
+            IpcTraceDir::E2A => "e2a",
+        }
+    }
+}
+
+/// 一条追踪记录
+#[derive(Debug, Clone, Copy)]
+pub struct IpcTraceEntry {
+    /// 全局序号，快照中序号不连续说明中间条目已被覆盖
+    pub seq: u32,
+    /// axhal::time::monotonic_time_nanos()
+    pub timestamp_ns: u64,
+    pub dir: IpcTraceDir,
+    pub msg_id: u16,
+    pub dest_id: u16,
+    pub src_id: u16,
+    /// 头中的 param_len（完整长度，不受前缀截断影响）
+    pub param_len: u16,
+    /// payload 中有效字节数（<= IPC_TRACE_PAYLOAD_PREFIX）
+    pub payload_len: u8,
+    pub payload: [u8; IPC_TRACE_PAYLOAD_PREFIX],
+}
+
+impl IpcTraceEntry {
+    const EMPTY: Self = Self {
+        seq: 0,
+        timestamp_ns: 0,
+        dir: IpcTraceDir::A2E,
+        msg_id: 0,
+        dest_id: 0,
+        src_id: 0,
+        param_len: 0,
+        payload_len: 0,
+        payload: [0; IPC_TRACE_PAYLOAD_PREFIX],
+    };
+
+    /// 已保存的 param 前缀
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.payload_len as usize]
+    }
+}
+
+struct IpcTraceRing {
+    entries: [IpcTraceEntry; IPC_TRACE_DEPTH],
+    /// 下一条写入位置
+    head: usize,
+    count: usize,
+}
+
+static IPC_TRACE: Mutex<IpcTraceRing> = Mutex::new(IpcTraceRing {
+    entries: [IpcTraceEntry::EMPTY; IPC_TRACE_DEPTH],
+    head: 0,
+    count: 0,
+});
+static IPC_TRACE_SEQ: AtomicU32 = AtomicU32::new(0);
+
+/// 记录一条消息；param 可为完整 param 或其前缀，仅保存前 IPC_TRACE_PAYLOAD_PREFIX 字节
+pub(crate) fn ipc_trace_record(dir: IpcTraceDir, msg_id: u16, dest_id: u16, src_id: u16, param_len: u16, param: &[u8]) {
+    let n = param.len().min(IPC_TRACE_PAYLOAD_PREFIX);
+    let mut entry = IpcTraceEntry {
+        seq: IPC_TRACE_SEQ.fetch_add(1, Ordering::Relaxed),
+        timestamp_ns: axhal::time::monotonic_time_nanos(),
+        dir,
+        msg_id,
+        dest_id,
+        src_id,
+        param_len,
+        payload_len: n as u8,
+        payload: [0; IPC_TRACE_PAYLOAD_PREFIX],
+    };
+    entry.payload[..n].copy_from_slice(&param[..n]);
+    let mut ring = IPC_TRACE.lock();
+    let head = ring.head;
+    ring.entries[head] = entry;
+    ring.head = (head + 1) % IPC_TRACE_DEPTH;
+    ring.count = (ring.count + 1).min(IPC_TRACE_DEPTH);
+}
+
+/// 从已序列化的 A2E 缓冲记录：hdr_off 为 lmac_msg 头偏移（8801 为 8，其余为 0）
+pub(crate) fn ipc_trace_record_a2e_buf(buf: &[u8], hdr_off: usize) {
+    if buf.len() < hdr_off + 8 {
+        return;
+    }
+    let h = &buf[hdr_off..];
+    let param_len = u16::from_le_bytes([h[6], h[7]]);
+    let end = (8 + param_len as usize).min(h.len());
+    ipc_trace_record(
+        IpcTraceDir::A2E,
+        u16::from_le_bytes([h[0], h[1]]),
+        u16::from_le_bytes([h[2], h[3]]),
+        u16::from_le_bytes([h[4], h[5]]),
+        param_len,
+        &h[8..end],
+    );
+}
+
+/// 按时间顺序（最旧在前）复制当前缓冲内容
+pub fn ipc_trace_snapshot() -> Vec<IpcTraceEntry> {
+    let ring = IPC_TRACE.lock();
+    let start = (ring.head + IPC_TRACE_DEPTH - ring.count) % IPC_TRACE_DEPTH;
+    (0..ring.count)
+        .map(|i| ring.entries[(start + i) % IPC_TRACE_DEPTH])
+        .collect()
+}
+
+/// 清空缓冲（序号不复位）
+pub fn ipc_trace_clear() {
+    let mut ring = IPC_TRACE.lock();
+    ring.head = 0;
+    ring.count = 0;
+}
+
+/// 导出为 JSON-lines，每条一行：
+/// `{"seq":..,"ts_ns":..,"dir":"a2e","id":"0x0400","dest":..,"src":..,"param_len":..,"data":"hex"}`
+pub fn ipc_trace_export_jsonl(entries: &[IpcTraceEntry], out: &mut dyn fmt::Write) -> fmt::Result {
+    for e in entries {
+        write!(
+            out,
+            "{{\"seq\":{},\"ts_ns\":{},\"dir\":\"{}\",\"id\":\"0x{:04x}\",\"dest\":{},\"src\":{},\"param_len\":{},\"data\":\"",
+            e.seq,
+            e.timestamp_ns,
+            e.dir.as_str(),
+            e.msg_id,
+            e.dest_id,
+            e.src_id,
+            e.param_len
+        )?;
+        for b in e.payload() {
+            write!(out, "{:02x}", b)?;
+        }
+        out.write_str("\"}\n")?;
+    }
+    Ok(())
+}
+
+/// pcapng 自定义链路类型（LINKTYPE_USER0）
+const PCAPNG_LINKTYPE_USER0: u16 = 147;
+const PCAPNG_BT_SHB: u32 = 0x0A0D_0D0A;
+const PCAPNG_BT_IDB: u32 = 0x0000_0001;
+const PCAPNG_BT_EPB: u32 = 0x0000_0006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+/// if_tsresol = 9（纳秒）
+const PCAPNG_OPT_IF_TSRESOL: u16 = 9;
+/// epb_flags：bit0-1 方向，1 = inbound，2 = outbound
+const PCAPNG_OPT_EPB_FLAGS: u16 = 2;
+
+fn pcapng_block(out: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let total = (12 + body.len()) as u32;
+    out.extend_from_slice(&block_type.to_le_bytes());
+    out.extend_from_slice(&total.to_le_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(&total.to_le_bytes());
+}
+
+/// 导出为小端 pcapng：SHB + 单个 IDB（纳秒时间戳）+ 每条一个 EPB。
+/// 包体为 8 字节 lmac 头（id/dest/src/param_len，LE）+ param 前缀，原始长度为 8 + param_len；方向写入 epb_flags。
+pub fn ipc_trace_export_pcapng(entries: &[IpcTraceEntry], out: &mut Vec<u8>) {
+    let mut body = Vec::with_capacity(64);
+    body.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&u64::MAX.to_le_bytes()); // section length 未知
+    pcapng_block(out, PCAPNG_BT_SHB, &body);
+
+    body.clear();
+    body.extend_from_slice(&PCAPNG_LINKTYPE_USER0.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes()); // snaplen 不限
+    body.extend_from_slice(&PCAPNG_OPT_IF_TSRESOL.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes());
+    body.extend_from_slice(&[9, 0, 0, 0]);
+    body.extend_from_slice(&[0u8; 4]); // opt_endofopt
+    pcapng_block(out, PCAPNG_BT_IDB, &body);
+
+    for e in entries {
+        body.clear();
+        let cap_len = 8 + e.payload_len as usize;
+        let orig_len = 8 + e.param_len as u32;
+        body.extend_from_slice(&0u32.to_le_bytes()); // interface id
+        body.extend_from_slice(&((e.timestamp_ns >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(e.timestamp_ns as u32).to_le_bytes());
+        body.extend_from_slice(&(cap_len as u32).to_le_bytes());
+        body.extend_from_slice(&orig_len.to_le_bytes());
+        body.extend_from_slice(&e.msg_id.to_le_bytes());
+        body.extend_from_slice(&e.dest_id.to_le_bytes());
+        body.extend_from_slice(&e.src_id.to_le_bytes());
+        body.extend_from_slice(&e.param_len.to_le_bytes());
+        body.extend_from_slice(e.payload());
+        body.resize((body.len() + 3) & !3, 0);
+        let flags: u32 = match e.dir {
+            IpcTraceDir::E2A => 1,
+            IpcTraceDir::A2E => 2,
+        };
+        body.extend_from_slice(&PCAPNG_OPT_EPB_FLAGS.to_le_bytes());
+        body.extend_from_slice(&4u16.to_le_bytes());
+        body.extend_from_slice(&flags.to_le_bytes());
+        body.extend_from_slice(&[0u8; 4]); // opt_endofopt
+        pcapng_block(out, PCAPNG_BT_EPB, &body);
+    }
+}
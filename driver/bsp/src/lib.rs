@@ -4,61 +4,126 @@
 //!
 //! 功能包括:
 //! - 固件管理 (fmacfw, fw_adid, fw_patch, fw_patch_table)
+//! - 蓝牙子系统（BT 上电与固件加载；HCI 走板级 UART，不在本 crate）
+//! - patch 表解析与下发（fw_patch_table*.bin → DBG_MEM_WRITE / DBG_MEM_MASK_WRITE）
 //! - SDIO 接口抽象
 //! - 命令管理 (rwnx_cmd_mgr)
+//! - 芯片信息（ProductId、chip_rev 与 MM_VERSION 固件版本缓存）
+//! - IPC 追踪（最近 A2E/E2A 消息环形记录）
+//! - 固件日志（SDIO PRINT 帧 → log / 环形缓冲 / 回调）
 //! - 预留内存管理
 //!
 //! 不抽象平台，按 Linux 流程实现；上电、SDIO 注册/探测、固件加载由上层在 Linux 上直接调用对应接口。
 
 #![no_std]
 
-use axerrno::AxResult;
+extern crate alloc;
 
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use axerrno::{AxError, AxResult};
+
+mod bt;
+mod chip_info;
 mod cmd;
 mod export;
 mod firmware;
 mod firmware_data;
 mod fw_load;
+mod fw_source;
+mod fwlog;
 mod gpio;
+mod ipc_trace;
+mod patch_table;
+mod recovery;
 mod sdio;
 mod sdio_irq;
+mod sha256;
 mod sync;
 
+pub use bt::{
+    aicbsp_bt_ready, aicbsp_get_bt_info, aicbsp_set_bt_info, AicBtInfo, AicBtMode, AicBtPort,
+};
 pub use sdio_irq::{sdio_tick, set_use_soft_irq_wake, SDIO_TIMER_POLL_INTERVAL_MS};
 
+pub use chip_info::{aicbsp_chip_info, aicbsp_set_fw_version, ChipInfo, FwVersion};
 pub use cmd::{
-    cmd_flags, IpcE2AMsg, LmacMsg, LmacMsgHeader, RwnxCmdMgr, TaskId, IPC_E2A_MSG_PARAM_SIZE,
-    LMAC_MSG_MAX_LEN, RWNX_80211_CMD_TIMEOUT_MS, RWNX_CMD_E2AMSG_LEN_MAX,
+    cmd_flags, CmdCfm, CmdCompleteCb, CmdCompletions, CmdCrashInfo, CmdHandle, IpcE2AMsg, LmacMsg, LmacMsgHeader, RwnxCmdMgr,
+    RwnxCmdMgrState, TaskId, IPC_E2A_MSG_PARAM_SIZE,
+    LMAC_MSG_MAX_LEN, RWNX_80211_CMD_TIMEOUT_MS, RWNX_CMD_E2AMSG_LEN_MAX, RWNX_CMD_MAX_QUEUED,
     SCANU_START_REQ, SCANU_START_CFM, SCANU_RESULT_IND,
     SM_CONNECT_REQ, SM_CONNECT_CFM, SM_CONNECT_IND,
     SM_DISCONNECT_REQ, SM_DISCONNECT_CFM, SM_DISCONNECT_IND,
     MM_ADD_IF_REQ, MM_ADD_IF_CFM, MM_REMOVE_IF_REQ, MM_REMOVE_IF_CFM,
     MM_STA_ADD_REQ, MM_STA_ADD_CFM, MM_STA_DEL_REQ, MM_STA_DEL_CFM,
     MM_KEY_ADD_REQ, MM_KEY_ADD_CFM, MM_KEY_DEL_REQ, MM_KEY_DEL_CFM,
-    MM_SET_POWER_REQ, MM_SET_POWER_CFM,
+    MM_SET_POWER_REQ, MM_SET_POWER_CFM, MM_VERSION_REQ, MM_VERSION_CFM,
     MM_PS_CHANGE_IND, MM_RSSI_STATUS_IND,
-    MM_GET_STA_INFO_REQ, MM_GET_STA_INFO_CFM,
+    MM_GET_STA_INFO_REQ, MM_GET_STA_INFO_CFM, MM_SET_RF_CONFIG_REQ, MM_SET_RF_CONFIG_CFM,
     APM_START_REQ, APM_START_CFM, APM_STOP_REQ, APM_STOP_CFM,
     DRV_TASK_ID,
 };
-pub use export::{AicBspFeature, AicBspInfo, AicBspPwrState, AicBspSubsys, SkBuffId};
+pub use export::{AicBspFeature, AicBspInfo, AicBspPwrState, AicBspSubsys, FwIntegrityPolicy, FwVerifyMode, SkBuffId};
 pub use firmware::{
     AicBspCpMode, AicBspFirmware, ChipRev, FW_8800DC_U02, FW_8800D80_U02, FW_U02,
-    get_firmware_list,
+    check_firmware_image, get_firmware_list, firmware_manifest_entry, validate_firmware_image, FirmwareIntegrityError,
+    FirmwareManifestEntry, FIRMWARE_MANIFEST,
 };
 pub use fw_load::{
+    aicbsp_fw_upload_stats, aicbsp_set_fw_upload_window,
     build_dbg_mem_block_write_req, build_dbg_start_app_req, fw_start_app, fw_upload_blocks,
-    get_firmware_by_name, get_wifi_firmware, send_dbg_mem_read, set_wifi_firmware,
-    CHIP_REV_MEM_ADDR, DBG_MEM_BLOCK_WRITE_CFM, DBG_START_APP_CFM, HOST_START_APP_AUTO,
-    RAM_FMAC_FW_ADDR, RAM_FMAC_FW_PATCH_ADDR,
+    fw_upload_blocks_windowed, fw_verify_blocks,
+    get_firmware_by_name, get_wifi_firmware, send_dbg_mem_mask_write, send_dbg_mem_read, send_dbg_mem_write,
+    set_wifi_firmware,
+    clear_wifi_firmware, register_wifi_firmware, registered_wifi_firmware, replace_wifi_firmware,
+    unregister_wifi_firmware, FirmwareRegistryError,
+    build_dbg_set_mod_filter_req, build_dbg_set_sev_filter_req,
+    CHIP_REV_MEM_ADDR, DBG_MEM_BLOCK_WRITE_CFM, DBG_RFTEST_CMD_CFM, DBG_RFTEST_CMD_REQ, DBG_START_APP_CFM,
+    DBG_SET_MOD_FILTER_CFM, DBG_SET_MOD_FILTER_REQ, DBG_SET_SEV_FILTER_CFM, DBG_SET_SEV_FILTER_REQ,
+    FW_RAM_ADID_BASE_ADDR, FW_RAM_PATCH_BASE_ADDR, HOST_START_APP_AUTO, RAM_FMAC_FW_ADDR, RAM_FMAC_FW_PATCH_ADDR, RAM_LMAC_FW_ADDR, FwUploadStats, FwVerifyMismatch, FW_UPLOAD_BLOCK_SIZE,
+    FW_UPLOAD_BLOCK_SIZE_LARGE, FW_UPLOAD_WINDOW_8801, FW_UPLOAD_WINDOW_DEFAULT,
+};
+pub use fwlog::{
+    aicbsp_fwlog_clear, aicbsp_fwlog_disable, aicbsp_fwlog_enable, aicbsp_fwlog_read, aicbsp_fwlog_sink,
+    aicbsp_set_fwlog_sink, FwLogCb, FwLogLevel, FwLogSink, FW_LOG_MOD_ALL, FW_LOG_SIZE,
+};
+pub use ipc_trace::{
+    ipc_trace_clear, ipc_trace_export_jsonl, ipc_trace_export_pcapng, ipc_trace_snapshot, IpcTraceDir,
+    IpcTraceEntry, IPC_TRACE_DEPTH, IPC_TRACE_PAYLOAD_PREFIX,
 };
+pub use patch_table::{
+    apply_patch_table, PatchOp, PatchSection, PatchSectionType, PatchTable, PatchTableError, PatchTableWriter,
+    AICBT_PT_TAG,
+};
+pub use fw_source::{
+    append_firmware_source, default_firmware_sources, prepend_firmware_source, reset_firmware_sources,
+    set_firmware_sources, EmbeddedFirmwareSource, FirmwareBlob, FirmwareFileReader, FirmwareSource,
+    PathFirmwareSource, RegisteredFirmwareSource,
+};
+pub use recovery::{
+    aicbsp_crash_dump, aicbsp_recovery_count, aicbsp_wifi_crashed, aicbsp_wifi_recover, AicBspCrashInfo,
+};
+// 主机侧模拟（EmuBootrom8801 与软件设备挂载），生产构建不包含
+#[cfg(any(test, feature = "sim"))]
 pub use sdio::{
-    aicbsp_current_product_id, aicbsp_driver_fw_init, aicbsp_minimal_ipc_verify, aicbsp_power_on,
+    aicbsp_attach_sim_device, aicbsp_detach_sim_device, aicbsp_set_sim_power_cycle, sdio_tx_process_once,
+    EmuBootrom8801, EmuFirmware, EmuReply,
+};
+// 板上录制 SdioOps trace（调试用）
+#[cfg(feature = "record")]
+pub use sdio::{aicbsp_sdio_record_start, aicbsp_sdio_record_stop};
+pub use sdio::{
+    aicbsp_current_product_id, aicbsp_driver_fw_init, aicbsp_minimal_ipc_verify,
+    aicbsp_power_on,
+    aicbsp_set_cmd_queue_depth, aicbsp_set_rf_gain_tables_8800dc, RfGainTables8800dc,
     aicbsp_sdio_exit, aicbsp_sdio_init, aicbsp_sdio_probe, aicbsp_sdio_release, chipmatch,
+    aicbsp_txdata_enqueue, aicbsp_txdata_stats, TxAggrBuf, TxDataPriv, TxSender, MAX_AGGR_TXPKT_LEN,
+    TXPKT_BLOCKSIZE, TXQLEN, TX_FRAME_MAX_LEN,
     parse_cis_for_manfid, probe_from_sdio_cis, read_fbr_cis_ptr, read_vendor_device, sdio_fbr_base,
     submit_cmd_tx_and_wait_tx_done, with_cmd_mgr, set_e2a_indication_cb, set_rx_data_indication_cb,
-    sdio_poll_rx_once, E2aIndicationCb, RxDataIndicationCb,
-    Aic8800Sdio, Aic8800SdioHost, BspSdioFuncRef, BspSdioHost, ProductId, SdioOps, SdioState, SdioType,
+    sdio_poll_rx_once, ipc_serialize_lmac_msg, E2aIndicationCb, RxDataIndicationCb, PENDING_CMD_TX_CAP,
+    SdioDeviceRef, Aic8800Sdio, Aic8800SdioHost, SdioOpKind, SdioRecorder, SdioReplay, SdioReplayError, SdioTrace, SdioTraceOp,
+    SdioTraceParseError, BspSdioFuncRef, BspSdioHost, ProductId, SdioOps, SdioState, SdioType,
     CISTPL_MANFID, SDIO_FBR_CIS, reg as sdio_reg, reg_v3 as sdio_reg_v3, sdio_ids,
 };
 pub use sync::{delay_spin_ms, delay_spin_us, power_lock, probe_reset, probe_signal, probe_wait_timeout_ms, LOOPS_PER_MS};
@@ -72,6 +137,28 @@ pub const DEFAULT_FW_PATH: &str = "/lib/firmware/aic";
 /// aicbsp_init 时写入，aicbsp_set_subsys 内 aicbsp_driver_fw_init 读取/更新
 static BSP_INFO: spin::Mutex<AicBspInfo> = spin::Mutex::new(AicBspInfo::default_const());
 
+/// BSP 特性配置（对应 aic_bsp_main.c aicbsp_feature），aicbsp_driver_fw_init 读取
+static BSP_FEATURE: spin::Mutex<AicBspFeature> = spin::Mutex::new(AicBspFeature::default_const());
+
+/// 设置 BSP 特性，须在 aicbsp_set_subsys(Wifi, On) 前调用
+pub fn aicbsp_set_feature(feature: &AicBspFeature) {
+    *BSP_FEATURE.lock() = feature.clone();
+}
+
+/// 取当前 BSP 特性（对应 aicbsp_get_feature）
+pub fn aicbsp_get_feature() -> AicBspFeature {
+    BSP_FEATURE.lock().clone()
+}
+
+/// aicbsp_init 设置的固件模式（对应 aicbsp_info.cpmode）
+pub fn aicbsp_get_cpmode() -> AicBspCpMode {
+    if BSP_INFO.lock().cpmode == AicBspCpMode::Test as u8 {
+        AicBspCpMode::Test
+    } else {
+        AicBspCpMode::Work
+    }
+}
+
 /// 预留内存初始化（对应 aic_bsp_driver.c aicbsp_resv_mem_init）
 /// 预分配 skb 等供 TX 路径使用；无平台实现时为空操作
 fn aicbsp_resv_mem_init() -> AxResult<()> {
@@ -141,28 +228,63 @@ pub fn aicbsp_platform_deinit() -> AxResult<()> {
     Ok(())
 }
 
+/// 已上电的子系统位图（bit0 = BT，bit1 = WiFi；对应 aicsdio.c aicbsp_power_state）
+static SUBSYS_POWER_MAP: AtomicU32 = AtomicU32::new(0);
+
+/// 上电 → sdio_init → driver_fw_init（WiFi 与 BT 固件一并加载），调用方持 power_lock
+fn aicbsp_bus_up() -> AxResult<()> {
+    log::info!(target: "wireless::bsp", "步骤1: GPIO复位和电源控制");
+    sdio::aicbsp_power_on()?;
+    log::info!(target: "wireless::bsp", "步骤2: SDIO 接口初始化（sdio_register_driver 在 aicbsp_sdio_init 内）");
+    sdio::aicbsp_sdio_init()?;
+    log::info!(target: "wireless::bsp", "步骤3: 驱动固件初始化");
+    sdio::aicbsp_driver_fw_init(&mut *BSP_INFO.lock())
+}
+
+/// sdio_exit → aicbsp_platform_power_off（当前为空，可后续接 GPIO 下电），调用方持 power_lock
+fn aicbsp_bus_down() {
+    sdio::aicbsp_sdio_exit();
+}
+
 /// 设置子系统电源（对应 aicsdio.c aicbsp_set_subsys，157–225 行）
 ///
-/// 与 LicheeRV 一致：整段“上电 → sdio_init → driver_fw_init”在 **power_lock** 内执行。
-/// - AIC_WIFI AIC_PWR_ON：aicbsp_power_on → aicbsp_sdio_init（内建 sdio_register_driver + probe）→ aicbsp_driver_fw_init。
-/// - AIC_WIFI AIC_PWR_OFF：aicbsp_sdio_exit → aicbsp_platform_power_off（当前为空，可后续接 GPIO 下电）。
+/// 与 LicheeRV 一致：WiFi 与 BT 共用芯片电源，按位图记录各子系统状态，整段在 **power_lock** 内执行。
+/// - 位图由 0 变非 0：aicbsp_power_on → aicbsp_sdio_init（内建 sdio_register_driver + probe）→ aicbsp_driver_fw_init。
+/// - 位图由非 0 变 0：aicbsp_sdio_exit → aicbsp_platform_power_off。
+/// - 其余情况只更新位图（另一子系统仍在用，芯片保持上电）。
+///
+/// BT 上电后若 BT 固件未加载（芯片禁用 BT、固件缺失或 RF 测试模式）返回 Unsupported，且不记入位图。
 pub fn aicbsp_set_subsys(subsys: AicBspSubsys, state: AicBspPwrState) -> AxResult<()> {
-    if subsys != AicBspSubsys::Wifi {
-        return Ok(());
+    let _guard = sync::power_lock();
+    let bit = 1u32 << (subsys as u32);
+    let pre = SUBSYS_POWER_MAP.load(Ordering::Acquire);
+    let cur = if state == AicBspPwrState::On { pre | bit } else { pre & !bit };
+    log::info!(target: "wireless::bsp", "aicbsp_set_subsys: {:?} {:?} (power map 0x{:x} -> 0x{:x})", subsys, state, pre, cur);
+    if pre == 0 && cur != 0 {
+        aicbsp_bus_up()?;
+    } else if pre != 0 && cur == 0 {
+        aicbsp_bus_down();
     }
-    if state == AicBspPwrState::On {
-        let _guard = sync::power_lock();
-        log::info!(target: "wireless::bsp", "aicbsp_set_subsys: AIC_WIFI AIC_PWR_ON (power_on → sdio_init → driver_fw_init)");
-        log::info!(target: "wireless::bsp", "步骤1: GPIO复位和电源控制");
-        sdio::aicbsp_power_on()?;
-        log::info!(target: "wireless::bsp", "步骤2: SDIO 接口初始化（sdio_register_driver 在 aicbsp_sdio_init 内）");
-        sdio::aicbsp_sdio_init()?;
-        log::info!(target: "wireless::bsp", "步骤3: 驱动固件初始化");
-        sdio::aicbsp_driver_fw_init(&mut *BSP_INFO.lock())?;
-    } else {
-        log::info!(target: "wireless::bsp", "aicbsp_set_subsys: AIC_WIFI AIC_PWR_OFF");
-        sdio::aicbsp_sdio_exit();
-        // aicbsp_platform_power_off()：LicheeRV 下拉电源等，当前无 GPIO 下电接口则留空
+    if subsys == AicBspSubsys::Bluetooth && state == AicBspPwrState::On && !bt::aicbsp_bt_ready() {
+        log::warn!(target: "wireless::bsp", "aicbsp_set_subsys: bt firmware not loaded");
+        if pre == 0 {
+            aicbsp_bus_down();
+        }
+        return Err(AxError::Unsupported);
     }
+    SUBSYS_POWER_MAP.store(cur, Ordering::Release);
     Ok(())
 }
+
+/// 不论位图，整颗芯片下电再上电并重新加载固件（供 WiFi 崩溃恢复使用；BT 固件一并重载）
+/// 挂有软件设备时改调 aicbsp_set_sim_power_cycle 登记的钩子
+pub(crate) fn aicbsp_power_cycle() -> AxResult<()> {
+    let _guard = sync::power_lock();
+    #[cfg(any(test, feature = "sim"))]
+    if let Some(hook) = sdio::sim_power_cycle_hook() {
+        hook();
+        return Ok(());
+    }
+    aicbsp_bus_down();
+    aicbsp_bus_up()
+}
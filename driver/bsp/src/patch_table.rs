@@ -0,0 +1,345 @@
+//! fw_patch_table*.bin 解析与下发（对应 LicheeRV aic_bsp_driver.c aicbt_patch_table_alloc / aicbt_patch_table_load）
+//!
+//! 文件格式：16 字节 "AICBT_PT_TAG" 头（0 补齐），其后若干段，每段为 tag[16] + type(u32 LE) + len(u32 LE) + len 个 (addr, value) 对。
+//! 只接受 AICBT_PT_INF..AICBT_PT_VER 七种段且 tag 与 type 相符。POWER_ON 段的对为 (寄存器, 置位位)，解析为 MaskWrite
+//! （mask = value，只置位不清其它位），其余段为 Write。解析结果由 apply_patch_table 经 DBG_MEM_WRITE_REQ /
+//! DBG_MEM_MASK_WRITE_REQ 写入芯片，更新 patch 表只需替换固件来源中的文件。
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// 文件头（aic_bsp_driver.h AICBT_PT_TAG）
+pub const AICBT_PT_TAG: &[u8] = b"AICBT_PT_TAG";
+/// 文件头长度（tag 以 0 补齐到 16 字节）
+const AICBT_PT_TAG_LEN: usize = 16;
+/// 段头：tag[16] + type(4) + len(4)
+const AICBT_PT_HDR_LEN: usize = 24;
+
+/// 段类型（AICBT_PT_INF .. AICBT_PT_VER）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchSectionType {
+    /// AICBT_PINF_T：adid/patch/ext patch 地址等信息，同样逐对写入
+    Inf,
+    /// AICBT_TRAP_T
+    Trap,
+    /// AICBT_PATCH_TB4
+    B4,
+    /// AICBT_MODE_T
+    BtMode,
+    /// AICBT_POWER_ON：写完后须等 500us
+    PwrOn,
+    /// AICBT_PATCH_TAF
+    Af,
+    /// AICBT_VER_INFO：版本字符串，不下发
+    Ver,
+}
+
+impl PatchSectionType {
+    /// 文件中的 type 值；不在 AICBT_PT_INF..AICBT_PT_VER 内返回 None
+    pub fn from_raw(ty: u32) -> Option<Self> {
+        Some(match ty {
+            0x00 => Self::Inf,
+            0x01 => Self::Trap,
+            0x02 => Self::B4,
+            0x03 => Self::BtMode,
+            0x04 => Self::PwrOn,
+            0x05 => Self::Af,
+            0x06 => Self::Ver,
+            _ => return None,
+        })
+    }
+
+    pub fn raw(self) -> u32 {
+        match self {
+            Self::Inf => 0x00,
+            Self::Trap => 0x01,
+            Self::B4 => 0x02,
+            Self::BtMode => 0x03,
+            Self::PwrOn => 0x04,
+            Self::Af => 0x05,
+            Self::Ver => 0x06,
+        }
+    }
+
+    /// 厂商 patch 表中该类型段的 tag
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Inf => "AICBT_PINF_T",
+            Self::Trap => "AICBT_TRAP_T",
+            Self::B4 => "AICBT_PATCH_TB4",
+            Self::BtMode => "AICBT_MODE_T",
+            Self::PwrOn => "AICBT_POWER_ON",
+            Self::Af => "AICBT_PATCH_TAF",
+            Self::Ver => "AICBT_VER_INFO",
+        }
+    }
+}
+
+/// 一条 patch 操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOp {
+    /// DBG_MEM_WRITE_REQ
+    Write { addr: u32, value: u32 },
+    /// DBG_MEM_MASK_WRITE_REQ：只改 mask 覆盖的位
+    MaskWrite { addr: u32, mask: u32, value: u32 },
+}
+
+/// 一段 patch（文件中的一个 tag 段）
+#[derive(Debug, Clone)]
+pub struct PatchSection {
+    pub tag: String,
+    pub ty: PatchSectionType,
+    pub ops: Vec<PatchOp>,
+}
+
+impl PatchSection {
+    /// 第 i 条 Write 的 (addr, value)；MaskWrite 或越界返回 None
+    pub fn pair(&self, i: usize) -> Option<(u32, u32)> {
+        match self.ops.get(i)? {
+            PatchOp::Write { addr, value } => Some((*addr, *value)),
+            PatchOp::MaskWrite { .. } => None,
+        }
+    }
+}
+
+/// 解析后的 patch 表，段顺序与文件一致（下发顺序即文件顺序）
+#[derive(Debug, Clone, Default)]
+pub struct PatchTable {
+    pub sections: Vec<PatchSection>,
+}
+
+/// 解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchTableError {
+    /// 文件头不是 AICBT_PT_TAG
+    BadTag,
+    /// offset 处的段头或段数据超出文件末尾
+    Truncated { offset: usize },
+    /// offset 处的段 type 不是 AICBT_PT_INF..AICBT_PT_VER
+    UnknownType { offset: usize, ty: u32 },
+    /// offset 处的段 tag 与 type 不符
+    TagMismatch { offset: usize, ty: PatchSectionType },
+}
+
+impl fmt::Display for PatchTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchTableError::BadTag => write!(f, "patch table: bad header tag"),
+            PatchTableError::Truncated { offset } => write!(f, "patch table: section at 0x{:x} truncated", offset),
+            PatchTableError::UnknownType { offset, ty } => {
+                write!(f, "patch table: section at 0x{:x} has unknown type {}", offset, ty)
+            }
+            PatchTableError::TagMismatch { offset, ty } => {
+                write!(f, "patch table: section at 0x{:x} is not tagged {}", offset, ty.tag())
+            }
+        }
+    }
+}
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+impl PatchTable {
+    /// 解析 fw_patch_table*.bin
+    pub fn parse(data: &[u8]) -> Result<Self, PatchTableError> {
+        if data.len() < AICBT_PT_TAG_LEN
+            || !data.starts_with(AICBT_PT_TAG)
+            || data[AICBT_PT_TAG.len()..AICBT_PT_TAG_LEN].iter().any(|&b| b != 0)
+        {
+            return Err(PatchTableError::BadTag);
+        }
+        let mut sections = Vec::new();
+        let mut p = AICBT_PT_TAG_LEN;
+        while p < data.len() {
+            if p + AICBT_PT_HDR_LEN > data.len() {
+                return Err(PatchTableError::Truncated { offset: p });
+            }
+            let raw_tag = &data[p..p + 16];
+            let raw_tag = &raw_tag[..raw_tag.iter().position(|&b| b == 0).unwrap_or(16)];
+            let raw_ty = le32(&data[p + 16..]);
+            let ty = PatchSectionType::from_raw(raw_ty).ok_or(PatchTableError::UnknownType { offset: p, ty: raw_ty })?;
+            if raw_tag != ty.tag().as_bytes() {
+                return Err(PatchTableError::TagMismatch { offset: p, ty });
+            }
+            let len = le32(&data[p + 20..]) as usize;
+            let start = p + AICBT_PT_HDR_LEN;
+            let end = len
+                .checked_mul(8)
+                .and_then(|n| start.checked_add(n))
+                .filter(|&e| e <= data.len())
+                .ok_or(PatchTableError::Truncated { offset: p })?;
+            let ops = data[start..end]
+                .chunks_exact(8)
+                .map(|c| {
+                    let (addr, value) = (le32(c), le32(&c[4..]));
+                    if ty == PatchSectionType::PwrOn {
+                        PatchOp::MaskWrite { addr, mask: value, value }
+                    } else {
+                        PatchOp::Write { addr, value }
+                    }
+                })
+                .collect();
+            sections.push(PatchSection {
+                tag: String::from(ty.tag()),
+                ty,
+                ops,
+            });
+            p = end;
+        }
+        Ok(Self { sections })
+    }
+
+    /// 第一个指定类型的段
+    pub fn section(&self, ty: PatchSectionType) -> Option<&PatchSection> {
+        self.sections.iter().find(|s| s.ty == ty)
+    }
+
+    /// AICBT_VER_INFO 段中的版本字符串（以 0 结尾）
+    pub fn version(&self) -> Option<String> {
+        let sec = self.section(PatchSectionType::Ver)?;
+        let mut bytes = Vec::with_capacity(sec.ops.len() * 8);
+        for i in 0..sec.ops.len() {
+            let (addr, value) = sec.pair(i)?;
+            bytes.extend_from_slice(&addr.to_le_bytes());
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
+
+    /// 下发的操作总数（不含 VER 段）
+    pub fn op_count(&self) -> usize {
+        self.sections
+            .iter()
+            .filter(|s| s.ty != PatchSectionType::Ver)
+            .map(|s| s.ops.len())
+            .sum()
+    }
+}
+
+/// patch 表下发所需的两种 DBG 写操作
+pub trait PatchTableWriter {
+    /// DBG_MEM_WRITE_REQ
+    fn mem_write(&mut self, addr: u32, data: u32) -> Result<(), i32>;
+    /// DBG_MEM_MASK_WRITE_REQ
+    fn mem_mask_write(&mut self, addr: u32, mask: u32, data: u32) -> Result<(), i32>;
+}
+
+/// aicbt_patch_table_load：按段顺序逐条下发，跳过 VER 段，PWRON 段写完后等 500us
+pub fn apply_patch_table<W: PatchTableWriter + ?Sized>(w: &mut W, table: &PatchTable) -> Result<(), i32> {
+    if let Some(ver) = table.version() {
+        log::info!(target: "wireless::bsp", "patch table version: {}", ver);
+    }
+    for sec in &table.sections {
+        if sec.ty == PatchSectionType::Ver {
+            continue;
+        }
+        for op in &sec.ops {
+            match *op {
+                PatchOp::Write { addr, value } => w.mem_write(addr, value)?,
+                PatchOp::MaskWrite { addr, mask, value } => w.mem_mask_write(addr, mask, value)?,
+            }
+        }
+        log::debug!(target: "wireless::bsp", "patch table: {} type={:?} {} ops", sec.tag, sec.ty, sec.ops.len());
+        if sec.ty == PatchSectionType::PwrOn {
+            crate::delay_spin_us(500);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PatchSectionType::*;
+
+    const FW_PATCH_TABLE: &[u8] = include_bytes!("../../../firmware/fw_patch_table.bin");
+    const FW_PATCH_TABLE_U03: &[u8] = include_bytes!("../../../firmware/fw_patch_table_u03.bin");
+    const FW_PATCH_TABLE_8800DC: &[u8] = include_bytes!("../../../firmware/fw_patch_table_8800dc_u02.bin");
+    const FW_PATCH_TABLE_8800D80: &[u8] = include_bytes!("../../../firmware/fw_patch_table_8800d80_u02.bin");
+
+    fn layout(table: &PatchTable) -> Vec<(PatchSectionType, usize)> {
+        table.sections.iter().map(|s| (s.ty, s.ops.len())).collect()
+    }
+
+    #[test]
+    fn parses_vendor_tables_in_tree() {
+        let t = PatchTable::parse(FW_PATCH_TABLE).unwrap();
+        assert_eq!(layout(&t), [(Trap, 33), (B4, 40), (BtMode, 9), (PwrOn, 2), (Af, 44), (Ver, 8)]);
+        assert_eq!(t.sections[0].tag, "AICBT_TRAP_T");
+        assert_eq!(t.sections[0].pair(0), Some((0x0016_f000, 0xe025_0793)));
+        assert_eq!(
+            t.section(PwrOn).unwrap().ops,
+            [
+                PatchOp::MaskWrite { addr: 0x4050_003c, mask: 0x0008_0000, value: 0x0008_0000 },
+                PatchOp::MaskWrite { addr: 0x4050_0124, mask: 0x0000_0040, value: 0x0000_0040 },
+            ]
+        );
+        assert_eq!(t.version().as_deref(), Some("- Apr 03 2023 15:59:28 - git e46501e"));
+        assert_eq!(t.op_count(), 33 + 40 + 9 + 2 + 44);
+
+        let t = PatchTable::parse(FW_PATCH_TABLE_U03).unwrap();
+        assert_eq!(layout(&t), [(Trap, 35), (B4, 53), (BtMode, 9), (PwrOn, 2), (Af, 47), (Ver, 8)]);
+
+        let t = PatchTable::parse(FW_PATCH_TABLE_8800DC).unwrap();
+        assert_eq!(layout(&t), [(Inf, 6), (Trap, 29), (B4, 28), (BtMode, 9), (PwrOn, 2), (Af, 15), (Ver, 8)]);
+        // PINF 前两对为 (adid_addrinf, addr_adid)、(patch_addrinf, addr_patch)
+        let inf = t.section(Inf).unwrap();
+        assert_eq!(inf.pair(0), Some((0x0019_4b44, 0x0010_17d8)));
+        assert_eq!(inf.pair(1), Some((0x0019_4b48, 0x0010_a800)));
+        assert_eq!(t.version().as_deref(), Some("- Jul 23 2025 11:12:55 - git 277faf8"));
+
+        let t = PatchTable::parse(FW_PATCH_TABLE_8800D80).unwrap();
+        assert_eq!(layout(&t), [(Inf, 6), (Trap, 27), (B4, 57), (BtMode, 18), (PwrOn, 3), (Af, 31), (Ver, 8)]);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_aicbt_tables() {
+        assert_eq!(PatchTable::parse(b"AICBT_PT").unwrap_err(), PatchTableError::BadTag);
+        let mut bad = FW_PATCH_TABLE.to_vec();
+        bad[12] = b'X';
+        assert_eq!(PatchTable::parse(&bad).unwrap_err(), PatchTableError::BadTag);
+
+        // 首段（offset 16）type 改为 7
+        let mut bad = FW_PATCH_TABLE.to_vec();
+        bad[32] = 7;
+        assert_eq!(PatchTable::parse(&bad).unwrap_err(), PatchTableError::UnknownType { offset: 16, ty: 7 });
+
+        // TRAP 段标成 B4 类型
+        let mut bad = FW_PATCH_TABLE.to_vec();
+        bad[32] = B4.raw() as u8;
+        assert_eq!(PatchTable::parse(&bad).unwrap_err(), PatchTableError::TagMismatch { offset: 16, ty: B4 });
+
+        let truncated = &FW_PATCH_TABLE[..FW_PATCH_TABLE.len() - 4];
+        assert!(matches!(PatchTable::parse(truncated), Err(PatchTableError::Truncated { .. })));
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<PatchOp>);
+
+    impl PatchTableWriter for Recorder {
+        fn mem_write(&mut self, addr: u32, value: u32) -> Result<(), i32> {
+            self.0.push(PatchOp::Write { addr, value });
+            Ok(())
+        }
+
+        fn mem_mask_write(&mut self, addr: u32, mask: u32, value: u32) -> Result<(), i32> {
+            self.0.push(PatchOp::MaskWrite { addr, mask, value });
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn apply_writes_in_file_order_and_skips_version() {
+        let t = PatchTable::parse(FW_PATCH_TABLE_8800DC).unwrap();
+        let mut w = Recorder::default();
+        apply_patch_table(&mut w, &t).unwrap();
+        let expected: Vec<PatchOp> =
+            t.sections.iter().filter(|s| s.ty != Ver).flat_map(|s| s.ops.iter().copied()).collect();
+        assert_eq!(w.0, expected);
+        assert_eq!(w.0.len(), t.op_count());
+        assert_eq!(w.0.iter().filter(|op| matches!(op, PatchOp::MaskWrite { .. })).count(), 2);
+    }
+}
@@ -0,0 +1,78 @@
+//! 固件崩溃检测与恢复（对应 LicheeRV rwnx_cmd_mgr 进入 RWNX_CMD_MGR_STATE_CRASHED 后的处理）
+//!
+//! LicheeRV 在 cmd 超时后仅置 CRASHED 并拒绝后续命令，需重新加载驱动；此处提供 BSP 侧的最小恢复序列：
+//! 记录现场（超时命令、队列、F1 寄存器）→ aicbsp_power_cycle
+//!（即 sdio_exit → power_on → sdio_init → driver_fw_init，重建 cmd_mgr；BT 在用时一并重载）。接口/密钥/连接的重放由 FDRV 完成。
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use axerrno::AxResult;
+
+use crate::cmd::CmdCrashInfo;
+use crate::sdio::{aicbsp_current_product_id, reg, reg_v3, with_cmd_mgr, with_sdio, ProductId, SdioOps};
+
+/// 已完成的恢复次数
+static RECOVERY_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// 崩溃现场：cmd_mgr 超时信息 + 当时的 F1 寄存器
+#[derive(Debug, Clone, Copy)]
+pub struct AicBspCrashInfo {
+    pub product_id: Option<ProductId>,
+    pub cmd: Option<CmdCrashInfo>,
+    /// 8801/DC/DW 为 BLOCK_CNT(0x12)；D80 系列为 MISC_INT_STATUS(0x04)
+    pub f1_block_cnt: Option<u8>,
+    /// 8801/DC/DW 为 BYTEMODE_LEN(0x02)；D80 系列为 BYTEMODE_LEN(0x05)
+    pub f1_bytemode_len: Option<u8>,
+    /// 8801/DC/DW 为 FLOW_CTRL(0x0A)；D80 系列为 FLOW_CTRL_Q1(0x03)
+    pub f1_flow_ctrl: Option<u8>,
+}
+
+/// cmd_mgr 是否已因命令超时进入 Crashed（cmd_mgr 不存在时为 false）
+pub fn aicbsp_wifi_crashed() -> bool {
+    with_cmd_mgr(|c| c.is_crashed()).unwrap_or(false)
+}
+
+/// 采集崩溃现场；SDIO 设备已不在时寄存器字段为 None
+pub fn aicbsp_crash_dump() -> AicBspCrashInfo {
+    let product_id = aicbsp_current_product_id();
+    let cmd = with_cmd_mgr(|c| c.crash_info()).flatten();
+    let is_v3 = matches!(product_id, Some(ProductId::Aic8800D80) | Some(ProductId::Aic8800D80X2));
+    let (block_cnt_reg, bytemode_len_reg, flow_ctrl_reg) = if is_v3 {
+        (reg_v3::MISC_INT_STATUS, reg_v3::BYTEMODE_LEN, reg_v3::FLOW_CTRL_Q1)
+    } else {
+        (reg::BLOCK_CNT, reg::BYTEMODE_LEN, reg::FLOW_CTRL)
+    };
+    let regs = with_sdio(|sdio| {
+        (
+            sdio.readb(block_cnt_reg as u32).ok(),
+            sdio.readb(bytemode_len_reg as u32).ok(),
+            sdio.readb(flow_ctrl_reg as u32).ok(),
+        )
+    });
+    let (f1_block_cnt, f1_bytemode_len, f1_flow_ctrl) = regs.unwrap_or((None, None, None));
+    let info = AicBspCrashInfo {
+        product_id,
+        cmd,
+        f1_block_cnt,
+        f1_bytemode_len,
+        f1_flow_ctrl,
+    };
+    log::error!(target: "wireless::bsp", "crash dump: {:?}", info);
+    info
+}
+
+/// 下电再上电并重新加载固件（aicbsp_power_cycle，不受子系统位图影响）。
+/// 返回下电前采集的现场；成功后 cmd_mgr 已重建为 Inited。
+pub fn aicbsp_wifi_recover() -> AxResult<AicBspCrashInfo> {
+    let info = aicbsp_crash_dump();
+    log::warn!(target: "wireless::bsp", "aicbsp_wifi_recover: power cycle + driver_fw_init");
+    crate::aicbsp_power_cycle()?;
+    let n = RECOVERY_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
+    log::info!(target: "wireless::bsp", "aicbsp_wifi_recover: firmware reloaded (recovery #{})", n);
+    Ok(info)
+}
+
+/// 自启动以来成功恢复的次数
+pub fn aicbsp_recovery_count() -> u32 {
+    RECOVERY_COUNT.load(Ordering::SeqCst)
+}
@@ -0,0 +1,715 @@
+//! 8801 bootrom 软件模型（主机侧测试用，无需 LicheeRV Nano 板卡）
+//!
+//! 实现 SdioOps，模拟 aicsdio.c 访问的 F1 寄存器（BLOCK_CNT、BYTEMODE_LEN、BYTEMODE_ENABLE、FLOW_CTRL）、
+//! WR_FIFO/RD_FIFO，以及 bootrom 对 DBG_* 消息的处理：MEM_READ、MEM_WRITE、MASK_WRITE、BLOCK_WRITE、START_APP。
+//! WR_FIFO 上的数据聚合包（aicwf_sdio_aggr 格式）按帧拆开记录，供 TX 聚合测试检查。
+//! A2E 按 serialize_8801 格式解析（8B SDIO 头 + lmac_msg），E2A 以 SDIO_TYPE_CFG_CMD_RSP 帧放入 RD_FIFO，
+//! 与 poll_rx_one 期望的 [len, 0x11, 0] + ipc_e2a_msg（含 pattern）布局一致。
+//! bootrom 不处理的消息交给 with_firmware 挂上的 EmuFirmware（如 FDRV 的 LMAC 模拟器），模拟 START_APP 后的运行固件。
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use super::ops::SdioOps;
+use super::types::reg;
+use crate::cmd::DRV_TASK_ID;
+use crate::fw_load::{
+    CHIP_REV_MEM_ADDR, DBG_MEM_BLOCK_WRITE_CFM, DBG_MEM_BLOCK_WRITE_REQ, DBG_MEM_MASK_WRITE_CFM, DBG_MEM_MASK_WRITE_REQ,
+    DBG_MEM_READ_CFM, DBG_MEM_READ_REQ, DBG_MEM_WRITE_CFM, DBG_MEM_WRITE_REQ, DBG_START_APP_CFM, DBG_START_APP_REQ,
+};
+
+/// Function 1 基址（完整 SDIO 地址 0x100 + reg）
+const FUNC1_BASE: u32 = 0x100;
+/// A2E：8B SDIO 头（[len+4 LE, 0x11, 0, dummy4]）+ 8B lmac_msg 头
+const A2E_HDR_LEN: usize = 16;
+/// SDIO_TYPE_CFG_CMD_RSP
+const SDIO_TYPE_CFG_CMD_RSP: u8 = 0x11;
+/// 数据帧聚合包的 SDIO 头类型（aicwf_sdio_aggr）
+const SDIO_TYPE_DATA_TX: u8 = 0x01;
+/// ipc_e2a_msg.pattern（IPC_MSGE2A_VALID_PATTERN）
+const IPC_MSGE2A_VALID_PATTERN: u32 = 0xADDE_DE2A;
+/// RD_FIFO 按块读出
+const BLOCKSIZE: usize = 512;
+/// BLOCK_CNT 不小于该值时表示 byte mode，长度见 BYTEMODE_LEN
+const BYTEMODE_THRESH: u8 = 64;
+/// 默认 FLOW_CTRL：可用缓冲数
+const FLOW_CTRL_DEFAULT: u8 = 8;
+
+/// 运行固件模型：处理 bootrom 不认识的 A2E 消息（MM_*、SCANU_*、SM_*、APM_* 等）
+pub trait EmuFirmware: Send {
+    /// 处理一条 A2E 消息，CFM/IND 经 reply 放入 RD_FIFO；返回 false 表示不认识该消息（计入 dropped）
+    fn on_msg(&mut self, id: u16, param: &[u8], reply: &mut EmuReply<'_>) -> bool;
+    /// 主机每次 recv_pkt 前调用一次，用于按轮询次数延迟的 CFM/IND
+    fn on_poll(&mut self, _reply: &mut EmuReply<'_>) {}
+    /// EmuBootrom8801::power_cycle 时调用：丢弃运行期状态，如同芯片重新上电、固件重新加载
+    fn on_power_cycle(&mut self) {}
+}
+
+/// EmuFirmware 回包入口：每次 push 在 RD_FIFO 末尾放一帧 CMD_RSP
+pub struct EmuReply<'a> {
+    rd_fifo: &'a mut VecDeque<Vec<u8>>,
+}
+
+impl EmuReply<'_> {
+    /// 放入一条 E2A 消息；src 取 id 所属任务（id >> 10）
+    pub fn push(&mut self, id: u16, param: &[u8]) {
+        self.rd_fifo.push_back(e2a_frame(id, param));
+    }
+}
+
+struct EmuState {
+    /// F1 普通寄存器（INTR_CONFIG、BYTEMODE_ENABLE 等），BLOCK_CNT/BYTEMODE_LEN/FLOW_CTRL 另行计算
+    f1: [u8; 256],
+    flow_ctrl: u8,
+    /// 待主机读出的 E2A 帧（已按 4 字节对齐）
+    rd_fifo: VecDeque<Vec<u8>>,
+    /// 设备内存，按 4 字节对齐地址存字（小端）
+    mem: BTreeMap<u32, u32>,
+    /// 已处理的 A2E 消息 id（按到达顺序）
+    msg_log: Vec<u16>,
+    /// 头部不合法或 id 不认识、未回 CFM 的消息数
+    dropped: usize,
+    /// DBG_START_APP_REQ 的 (bootaddr, boottype)
+    started: Option<(u32, u32)>,
+    /// bootrom 不处理的消息交给它
+    firmware: Option<Box<dyn EmuFirmware>>,
+    /// 收到的数据帧（txdesc_api + payload，含帧内对齐填充）
+    data_frames: Vec<Vec<u8>>,
+    /// 每次数据 CMD53 携带的帧数
+    data_xfers: Vec<usize>,
+}
+
+impl EmuState {
+    fn mem_read_word(&self, addr: u32) -> u32 {
+        self.mem.get(&(addr & !3)).copied().unwrap_or(0)
+    }
+
+    fn mem_write_word(&mut self, addr: u32, data: u32) {
+        self.mem.insert(addr & !3, data);
+    }
+
+    fn mem_write_bytes(&mut self, addr: u32, data: &[u8]) {
+        for (i, &b) in data.iter().enumerate() {
+            let a = addr.wrapping_add(i as u32);
+            let shift = (a & 3) * 8;
+            let word = (self.mem_read_word(a) & !(0xff << shift)) | ((b as u32) << shift);
+            self.mem_write_word(a, word);
+        }
+    }
+
+    /// RD_FIFO 首帧长度（未读出时为 0）
+    fn rx_len(&self) -> usize {
+        self.rd_fifo.front().map_or(0, |f| f.len())
+    }
+
+    fn block_cnt(&self) -> u8 {
+        let len = self.rx_len();
+        if len == 0 {
+            0
+        } else if self.f1[reg::BYTEMODE_ENABLE as usize] != 0 {
+            BYTEMODE_THRESH
+        } else {
+            ((len + BLOCKSIZE - 1) / BLOCKSIZE) as u8
+        }
+    }
+
+    fn read_f1(&self, regaddr: u32) -> u8 {
+        match regaddr as u8 {
+            reg::BLOCK_CNT => self.block_cnt(),
+            reg::BYTEMODE_LEN => ((self.rx_len() + 3) / 4) as u8,
+            reg::FLOW_CTRL => self.flow_ctrl & reg::FLOWCTRL_MASK,
+            r => self.f1[r as usize],
+        }
+    }
+
+    fn push_cfm(&mut self, id: u16, param: &[u8]) {
+        self.rd_fifo.push_back(e2a_frame(id, param));
+    }
+
+    /// 拆开一个数据聚合包：逐帧读 SDIO 头 len，遇 len 为 0（块尾填充）结束
+    fn handle_data(&mut self, buf: &[u8]) {
+        let mut off = 0;
+        let mut count = 0;
+        while off + 4 <= buf.len() && buf[off + 2] == SDIO_TYPE_DATA_TX {
+            let len = u16::from_le_bytes([buf[off], buf[off + 1] & 0x0f]) as usize;
+            if len == 0 || off + 4 + len > buf.len() {
+                break;
+            }
+            self.data_frames.push(buf[off + 4..off + 4 + len].to_vec());
+            off += 4 + len;
+            count += 1;
+        }
+        if count == 0 || buf[off..].iter().any(|&b| b != 0) {
+            log::warn!(target: "wireless::bsp::sdio", "emu: bad data aggregate len={} at offset {}", buf.len(), off);
+            self.dropped += 1;
+        }
+        self.data_xfers.push(count);
+    }
+
+    /// bootrom 处理一条 A2E 消息（serialize_8801 格式，可带对齐/尾部填充）
+    fn handle_a2e(&mut self, buf: &[u8]) {
+        if buf.len() < A2E_HDR_LEN || buf[2] != SDIO_TYPE_CFG_CMD_RSP {
+            log::warn!(target: "wireless::bsp::sdio", "emu: bad a2e header len={}", buf.len());
+            self.dropped += 1;
+            return;
+        }
+        let sdio_len = u16::from_le_bytes([buf[0], buf[1] & 0x0f]) as usize;
+        let id = u16::from_le_bytes([buf[8], buf[9]]);
+        let param_len = u16::from_le_bytes([buf[14], buf[15]]) as usize;
+        if sdio_len != 8 + param_len + 4 || buf.len() < A2E_HDR_LEN + param_len {
+            log::warn!(target: "wireless::bsp::sdio", "emu: a2e id={} length mismatch (sdio_len={} param_len={})", id, sdio_len, param_len);
+            self.dropped += 1;
+            return;
+        }
+        let param = &buf[A2E_HDR_LEN..A2E_HDR_LEN + param_len];
+        let word = |i: usize| param.get(i * 4..i * 4 + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+        self.msg_log.push(id);
+        match (id, word(0), word(1), word(2)) {
+            (DBG_MEM_READ_REQ, Some(addr), _, _) => {
+                let data = self.mem_read_word(addr);
+                self.push_cfm(DBG_MEM_READ_CFM, &cfm_words(addr, data));
+            }
+            (DBG_MEM_WRITE_REQ, Some(addr), Some(data), _) => {
+                self.mem_write_word(addr, data);
+                self.push_cfm(DBG_MEM_WRITE_CFM, &cfm_words(addr, data));
+            }
+            (DBG_MEM_MASK_WRITE_REQ, Some(addr), Some(mask), Some(data)) => {
+                let v = (self.mem_read_word(addr) & !mask) | (data & mask);
+                self.mem_write_word(addr, v);
+                self.push_cfm(DBG_MEM_MASK_WRITE_CFM, &cfm_words(addr, v));
+            }
+            (DBG_MEM_BLOCK_WRITE_REQ, Some(addr), Some(size), _) if param_len >= 8 + size as usize => {
+                self.mem_write_bytes(addr, &param[8..8 + size as usize]);
+                self.push_cfm(DBG_MEM_BLOCK_WRITE_CFM, &0u32.to_le_bytes());
+            }
+            (DBG_START_APP_REQ, Some(boot_addr), Some(boot_type), _) => {
+                self.started = Some((boot_addr, boot_type));
+                self.push_cfm(DBG_START_APP_CFM, &0u32.to_le_bytes());
+            }
+            _ => {
+                let handled = match self.firmware.as_mut() {
+                    Some(fw) => fw.on_msg(id, param, &mut EmuReply { rd_fifo: &mut self.rd_fifo }),
+                    None => false,
+                };
+                if !handled {
+                    log::warn!(target: "wireless::bsp::sdio", "emu: unhandled a2e id={} param_len={}", id, param_len);
+                    self.dropped += 1;
+                }
+            }
+        }
+    }
+}
+
+/// CMD_RSP 帧：[len LE, 0x11, 0] + id/dest/src/param_len + pattern + param，按 4 字节对齐
+fn e2a_frame(id: u16, param: &[u8]) -> Vec<u8> {
+    let e2a_len = 12 + param.len();
+    let mut frame = Vec::with_capacity(4 + e2a_len + 3);
+    frame.extend_from_slice(&[(e2a_len & 0xff) as u8, ((e2a_len >> 8) & 0x0f) as u8, SDIO_TYPE_CFG_CMD_RSP, 0]);
+    frame.extend_from_slice(&id.to_le_bytes());
+    frame.extend_from_slice(&DRV_TASK_ID.to_le_bytes());
+    frame.extend_from_slice(&(id >> 10).to_le_bytes());
+    frame.extend_from_slice(&(param.len() as u16).to_le_bytes());
+    frame.extend_from_slice(&IPC_MSGE2A_VALID_PATTERN.to_le_bytes());
+    frame.extend_from_slice(param);
+    frame.resize((frame.len() + 3) & !3, 0);
+    frame
+}
+
+/// dbg_mem_*_cfm：{ memaddr, memdata }
+fn cfm_words(addr: u32, data: u32) -> [u8; 8] {
+    let mut p = [0u8; 8];
+    p[..4].copy_from_slice(&addr.to_le_bytes());
+    p[4..].copy_from_slice(&data.to_le_bytes());
+    p
+}
+
+/// 8801 bootrom 软件模型：经 aicbsp_attach_sim_device 挂上后可直接跑 aicbsp_driver_fw_init
+pub struct EmuBootrom8801 {
+    state: Mutex<EmuState>,
+}
+
+impl EmuBootrom8801 {
+    /// chip_rev 写入 CHIP_REV_MEM_ADDR 的 bit 16..24（U02 为 3，U03 为 7）
+    pub fn new(chip_rev: u8) -> Self {
+        Self::build(chip_rev, None)
+    }
+
+    /// 带运行固件模型：bootrom 不处理的消息交给 firmware（不要求先 START_APP，控制面测试可跳过固件下载）
+    pub fn with_firmware(chip_rev: u8, firmware: Box<dyn EmuFirmware>) -> Self {
+        Self::build(chip_rev, Some(firmware))
+    }
+
+    fn build(chip_rev: u8, firmware: Option<Box<dyn EmuFirmware>>) -> Self {
+        let mut mem = BTreeMap::new();
+        mem.insert(CHIP_REV_MEM_ADDR, (chip_rev as u32) << 16);
+        Self {
+            state: Mutex::new(EmuState {
+                f1: [0; 256],
+                flow_ctrl: FLOW_CTRL_DEFAULT,
+                rd_fifo: VecDeque::new(),
+                mem,
+                msg_log: Vec::new(),
+                dropped: 0,
+                started: None,
+                firmware,
+                data_frames: Vec::new(),
+                data_xfers: Vec::new(),
+            }),
+        }
+    }
+
+    /// 模拟下电再上电：清空 RD_FIFO、F1 寄存器与 START_APP 记录，运行固件模型 on_power_cycle；
+    /// 设备内存（含 chip_rev）与 msg_log/dropped 保留，便于测试比较恢复前后收到的消息
+    pub fn power_cycle(&self) {
+        let mut st = self.state.lock();
+        st.f1 = [0; 256];
+        st.flow_ctrl = FLOW_CTRL_DEFAULT;
+        st.rd_fifo.clear();
+        st.started = None;
+        if let Some(fw) = st.firmware.as_mut() {
+            fw.on_power_cycle();
+        }
+    }
+
+    /// 设置 FLOW_CTRL 读数；0 表示设备无空闲缓冲，send_msg 返回 -ETIMEDOUT
+    pub fn set_flow_ctrl(&self, fc: u8) {
+        self.state.lock().flow_ctrl = fc;
+    }
+
+    pub fn mem_read_word(&self, addr: u32) -> u32 {
+        self.state.lock().mem_read_word(addr)
+    }
+
+    /// 读设备内存 [addr, addr+len)
+    pub fn mem_read(&self, addr: u32, len: usize) -> Vec<u8> {
+        let st = self.state.lock();
+        (0..len)
+            .map(|i| {
+                let a = addr.wrapping_add(i as u32);
+                (st.mem_read_word(a) >> ((a & 3) * 8)) as u8
+            })
+            .collect()
+    }
+
+    pub fn mem_write_word(&self, addr: u32, data: u32) {
+        self.state.lock().mem_write_word(addr, data);
+    }
+
+    /// 已处理的 A2E 消息 id（按到达顺序）
+    pub fn msg_log(&self) -> Vec<u16> {
+        self.state.lock().msg_log.clone()
+    }
+
+    /// id 为 msg_id 的已处理消息数
+    pub fn msg_count(&self, msg_id: u16) -> usize {
+        self.state.lock().msg_log.iter().filter(|&&id| id == msg_id).count()
+    }
+
+    /// 被丢弃（头部不合法或未实现）的消息数
+    pub fn dropped(&self) -> usize {
+        self.state.lock().dropped
+    }
+
+    /// DBG_START_APP_REQ 收到的 (bootaddr, boottype)
+    pub fn started(&self) -> Option<(u32, u32)> {
+        self.state.lock().started
+    }
+
+    /// 在 RD_FIFO 末尾放入一个原始突发（可为多帧拼接，如聚合数据帧或多条 E2A），主机一次读出
+    pub fn push_rx_burst(&self, burst: Vec<u8>) {
+        self.state.lock().rd_fifo.push_back(burst);
+    }
+
+    /// RD_FIFO 中尚未读出的帧数
+    pub fn pending_rx(&self) -> usize {
+        self.state.lock().rd_fifo.len()
+    }
+
+    /// 已收到的数据帧（按到达顺序）
+    pub fn data_frames(&self) -> Vec<Vec<u8>> {
+        self.state.lock().data_frames.clone()
+    }
+
+    /// 每次数据 CMD53 携带的帧数
+    pub fn data_xfers(&self) -> Vec<usize> {
+        self.state.lock().data_xfers.clone()
+    }
+
+    fn write_fifo(&self, buf: &[u8]) -> Result<usize, i32> {
+        let mut st = self.state.lock();
+        if buf.len() > 2 && buf[2] == SDIO_TYPE_DATA_TX {
+            st.handle_data(buf);
+        } else {
+            st.handle_a2e(buf);
+        }
+        Ok(buf.len())
+    }
+}
+
+impl SdioOps for EmuBootrom8801 {
+    fn writeb(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        self.state.lock().f1[(regaddr & 0xff) as usize] = val;
+        Ok(())
+    }
+
+    fn readb(&self, regaddr: u32) -> Result<u8, i32> {
+        Ok(self.state.lock().read_f1(regaddr & 0xff))
+    }
+
+    /// 与 Aic8800Sdio 的 8801 分支一致：先读 BLOCK_CNT 定长，再读 RD_FIFO（size 不足时截断）
+    fn recv_pkt(&self, buf: &mut [u8], size: u32, _msg: u8) -> Result<usize, i32> {
+        let n = size as usize;
+        if n > buf.len() {
+            return Err(-22);
+        }
+        let data_len = self.rx_pending_len()?;
+        if data_len == 0 {
+            return Ok(0);
+        }
+        self.read_rx_fifo(&mut buf[..n.min(data_len)])
+    }
+
+    /// BLOCK_CNT 定长（>=64 时用 BYTEMODE_LEN*4）；每次调用先给 EmuFirmware 一次 on_poll
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        let mut st = self.state.lock();
+        let st = &mut *st;
+        if let Some(fw) = st.firmware.as_mut() {
+            fw.on_poll(&mut EmuReply { rd_fifo: &mut st.rd_fifo });
+        }
+        let block_cnt = st.block_cnt();
+        Ok(if block_cnt == 0 {
+            0
+        } else if block_cnt >= BYTEMODE_THRESH {
+            st.read_f1(reg::BYTEMODE_LEN as u32) as usize * 4
+        } else {
+            block_cnt as usize * BLOCKSIZE
+        })
+    }
+
+    /// 读出 RD_FIFO 首个突发，多出部分补 0
+    fn read_rx_fifo(&self, buf: &mut [u8]) -> Result<usize, i32> {
+        let frame = self.state.lock().rd_fifo.pop_front().unwrap_or_default();
+        let copy = buf.len().min(frame.len());
+        buf[..copy].copy_from_slice(&frame[..copy]);
+        buf[copy..].fill(0);
+        Ok(buf.len())
+    }
+
+    fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        self.write_fifo(&buf[..count])
+    }
+
+    /// 8801 IPC 走 F1 WR_FIFO：FLOW_CTRL 为 0 时与 wait_flow_ctrl 重试耗尽一样返回 -ETIMEDOUT
+    fn send_msg(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        if self.state.lock().flow_ctrl & reg::FLOWCTRL_MASK == 0 {
+            return Err(-110);
+        }
+        self.write_fifo(&buf[..count])
+    }
+
+    fn write_block(&self, addr: u32, buf: &[u8]) -> Result<usize, i32> {
+        if addr == FUNC1_BASE + reg::WR_FIFO_ADDR as u32 {
+            return self.write_fifo(buf);
+        }
+        for (i, &b) in buf.iter().enumerate() {
+            self.write_byte(addr.wrapping_add(i as u32), b)?;
+        }
+        Ok(buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::{cmd_flags, RwnxCmdMgr};
+    use crate::export::AicBspInfo;
+    use crate::fw_load::{
+        fw_upload_blocks, send_dbg_mem_mask_write, send_dbg_mem_read, FW_UPLOAD_BLOCK_SIZE, RAM_FMAC_FW_ADDR,
+        RAM_FMAC_FW_PATCH_ADDR, HOST_START_APP_AUTO,
+    };
+    use crate::sdio::flow::ipc_serialize_lmac_msg;
+    use crate::sdio::types::ProductId;
+    use crate::cmd::LmacMsg;
+    use alloc::sync::Arc;
+    use axerrno::{AxError, AxResult};
+
+    const TIMEOUT_MS: u32 = 100;
+
+    fn tx(emu: &EmuBootrom8801) -> impl FnMut(&LmacMsg) -> Result<(), i32> + '_ {
+        move |msg| {
+            let mut buf = [0u8; 1536];
+            let (_, send_len) = ipc_serialize_lmac_msg(ProductId::Aic8801, msg, &mut buf)?;
+            emu.send_msg(&buf[..send_len], send_len).map(|_| ())
+        }
+    }
+
+    fn poll(emu: &EmuBootrom8801) -> impl FnMut(&mut RwnxCmdMgr) + '_ {
+        move |c| {
+            let _ = super::super::flow::poll_rx_one(emu, c);
+        }
+    }
+
+    /// 把 emu 挂为 8801 软件设备跑 aicbsp_driver_fw_init；调用方须持 GLOBAL_TEST_LOCK
+    fn boot(emu: &Arc<EmuBootrom8801>, info: &mut AicBspInfo) -> AxResult<()> {
+        crate::sdio::driver_fw_init_on_sim_for_test(emu.clone(), ProductId::Aic8801, info)
+    }
+
+    #[test]
+    fn f1_registers_track_rd_fifo() {
+        let emu = EmuBootrom8801::new(3);
+        assert_eq!(emu.readb(reg::BLOCK_CNT as u32), Ok(0));
+        assert_eq!(emu.readb(reg::FLOW_CTRL as u32), Ok(FLOW_CTRL_DEFAULT));
+        tx(&emu)(&crate::fw_load::build_dbg_mem_read_req(CHIP_REV_MEM_ADDR)).unwrap();
+        assert_eq!(emu.readb(reg::BLOCK_CNT as u32), Ok(1));
+        // 4B 头 + 12B ipc_e2a_msg 头 + 8B param
+        assert_eq!(emu.readb(reg::BYTEMODE_LEN as u32), Ok(6));
+        emu.writeb(reg::BYTEMODE_ENABLE as u32, 1).unwrap();
+        assert_eq!(emu.readb(reg::BLOCK_CNT as u32), Ok(BYTEMODE_THRESH));
+
+        let mut buf = [0u8; 1536];
+        assert_eq!(emu.recv_pkt(&mut buf, 1536, 1), Ok(24));
+        assert_eq!(buf[2], SDIO_TYPE_CFG_CMD_RSP);
+        assert_eq!(u16::from_le_bytes([buf[4], buf[5]]), DBG_MEM_READ_CFM);
+        assert_eq!(emu.readb(reg::BLOCK_CNT as u32), Ok(0));
+        assert_eq!(emu.recv_pkt(&mut buf, 1536, 1), Ok(0));
+    }
+
+    #[test]
+    fn dbg_read_write_mask_and_block_write() {
+        let emu = EmuBootrom8801::new(7);
+        let mut cmd_mgr = RwnxCmdMgr::new();
+        let memdata = send_dbg_mem_read(&mut cmd_mgr, tx(&emu), CHIP_REV_MEM_ADDR, TIMEOUT_MS, &mut poll(&emu), None);
+        assert_eq!(memdata, Ok(7 << 16));
+
+        emu.mem_write_word(0x4050_6024, 0x1234_56ff);
+        send_dbg_mem_mask_write(&mut cmd_mgr, tx(&emu), 0x4050_6024, 0xff, 0xdf, TIMEOUT_MS, &mut poll(&emu)).unwrap();
+        assert_eq!(emu.mem_read_word(0x4050_6024), 0x1234_56df);
+
+        // 非 4 字节对齐的长度：最后一块 3 字节
+        let data: Vec<u8> = (0..2051u32).map(|i| (i * 7) as u8).collect();
+        let cmd_mgr = core::cell::RefCell::new(cmd_mgr);
+        fw_upload_blocks(
+            tx(&emu),
+            0x0010_0000,
+            &data,
+            || cmd_mgr.borrow_mut().push(DBG_MEM_BLOCK_WRITE_CFM, cmd_flags::REQ_CFM),
+            |token| {
+                let mut c = cmd_mgr.borrow_mut();
+                c.wait_done(token, TIMEOUT_MS, &mut poll(&emu), None)?;
+                c.take_cfm(token, &mut [0u8; 16]).map(|_| ()).ok_or(-5)
+            },
+        )
+        .unwrap();
+        assert_eq!(emu.mem_read(0x0010_0000, data.len()), data);
+        assert_eq!(emu.msg_count(DBG_MEM_BLOCK_WRITE_REQ), 3);
+        assert_eq!(emu.dropped(), 0);
+        assert_eq!(emu.pending_rx(), 0);
+    }
+
+    #[test]
+    fn malformed_a2e_is_dropped_without_cfm() {
+        let emu = EmuBootrom8801::new(3);
+        let mut buf = [0u8; 512];
+        let (_, send_len) =
+            ipc_serialize_lmac_msg(ProductId::Aic8801, &crate::fw_load::build_dbg_mem_write_req(0, 1), &mut buf).unwrap();
+        buf[0] = buf[0].wrapping_add(4);
+        emu.send_msg(&buf[..send_len], send_len).unwrap();
+        assert_eq!(emu.dropped(), 1);
+        assert_eq!(emu.pending_rx(), 0);
+        assert!(emu.msg_log().is_empty());
+    }
+
+    #[test]
+    fn boot_fails_when_flow_ctrl_has_no_buffer() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        emu.set_flow_ctrl(0);
+        let mut info = AicBspInfo::default();
+        assert!(boot(&emu, &mut info).is_err());
+        assert!(emu.msg_log().is_empty());
+    }
+
+    #[test]
+    fn boot_rejects_unsupported_chip_rev() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let emu = Arc::new(EmuBootrom8801::new(1));
+        let mut info = AicBspInfo::default();
+        assert_eq!(boot(&emu, &mut info), Err(AxError::BadState));
+        assert_eq!(emu.msg_log(), alloc::vec![DBG_MEM_READ_REQ]);
+        assert_eq!(emu.started(), None);
+    }
+
+    /// 多条 E2A 拼成一个突发：param 长度不是 4 的倍数，帧间按 RX_ALIGNMENT 对齐
+    fn cfm_burst(ids: &[u16], param_len: usize) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let mut burst = Vec::new();
+        let mut params = Vec::new();
+        for (i, &id) in ids.iter().enumerate() {
+            let param: Vec<u8> = (0..param_len + i).map(|b| (b as u8) ^ (i as u8)).collect();
+            burst.extend_from_slice(&e2a_frame(id, &param));
+            params.push(param);
+        }
+        (burst, params)
+    }
+
+    #[test]
+    fn poll_rx_reads_whole_burst_and_splits_frames() {
+        let emu = EmuBootrom8801::new(3);
+        let ids = [DBG_MEM_READ_CFM, DBG_MEM_WRITE_CFM, DBG_MEM_MASK_WRITE_CFM, DBG_MEM_BLOCK_WRITE_CFM, DBG_START_APP_CFM];
+        let (burst, params) = cfm_burst(&ids, 601);
+        // 远超过旧的 1536 字节定长缓冲
+        assert!(burst.len() > 3 * BLOCKSIZE);
+        emu.push_rx_burst(burst);
+        assert_eq!(emu.rx_pending_len(), Ok(7 * BLOCKSIZE));
+
+        let mut cmd_mgr = RwnxCmdMgr::new();
+        let tokens: Vec<usize> = ids.iter().map(|&id| cmd_mgr.push(id, cmd_flags::REQ_CFM).unwrap()).collect();
+        super::super::flow::poll_rx_one(&emu, &mut cmd_mgr).unwrap();
+        assert_eq!(emu.pending_rx(), 0);
+        for (token, param) in tokens.into_iter().zip(&params) {
+            assert!(cmd_mgr.is_done(token));
+            assert_eq!(cmd_mgr.take_cfm_vec(token).as_deref(), Some(&param[..]));
+        }
+    }
+
+    #[test]
+    fn poll_rx_byte_mode_burst() {
+        let emu = EmuBootrom8801::new(3);
+        emu.writeb(reg::BYTEMODE_ENABLE as u32, 1).unwrap();
+        let (burst, params) = cfm_burst(&[DBG_MEM_READ_CFM, DBG_MEM_WRITE_CFM], 97);
+        let len = burst.len();
+        emu.push_rx_burst(burst);
+        assert_eq!(emu.rx_pending_len(), Ok(len));
+
+        let mut cmd_mgr = RwnxCmdMgr::new();
+        let t0 = cmd_mgr.push(DBG_MEM_READ_CFM, cmd_flags::REQ_CFM).unwrap();
+        let t1 = cmd_mgr.push(DBG_MEM_WRITE_CFM, cmd_flags::REQ_CFM).unwrap();
+        super::super::flow::poll_rx_one(&emu, &mut cmd_mgr).unwrap();
+        assert_eq!(cmd_mgr.take_cfm_vec(t0).as_deref(), Some(&params[0][..]));
+        assert_eq!(cmd_mgr.take_cfm_vec(t1).as_deref(), Some(&params[1][..]));
+        assert_eq!(emu.rx_pending_len(), Ok(0));
+    }
+
+    static DATA_IND: spin::Mutex<Vec<Vec<u8>>> = spin::Mutex::new(Vec::new());
+
+    unsafe fn record_data_ind(ptr: *const u8, len: usize) {
+        DATA_IND.lock().push(unsafe { core::slice::from_raw_parts(ptr, len) }.to_vec());
+    }
+
+    /// 数据帧：[pkt_len LE, type 0, 0] + 60 字节 RX 硬件头 + pkt_len 字节正文，按 RX_ALIGNMENT 对齐
+    fn data_frame(pkt_len: usize) -> Vec<u8> {
+        let mut f = alloc::vec![0u8; 3 + ((pkt_len + 60 + 3) & !3)];
+        f[..2].copy_from_slice(&(pkt_len as u16).to_le_bytes());
+        for (i, b) in f[3..3 + 60 + pkt_len].iter_mut().enumerate() {
+            *b = (i as u8) | 1;
+        }
+        f
+    }
+
+    #[test]
+    fn poll_rx_stops_at_zero_padding_after_data_frames() {
+        use crate::sdio::flow::set_rx_data_indication_cb;
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        DATA_IND.lock().clear();
+        set_rx_data_indication_cb(Some(record_data_ind));
+
+        let emu = EmuBootrom8801::new(3);
+        let (a, b) = (data_frame(100), data_frame(37));
+        let (cfm, params) = cfm_burst(&[DBG_MEM_READ_CFM], 8);
+        let mut burst = a.clone();
+        burst.extend_from_slice(&cfm);
+        burst.extend_from_slice(&b);
+        // 块模式：读满 512 字节，b 之后全是 0 填充
+        assert!(burst.len() < BLOCKSIZE);
+        emu.push_rx_burst(burst);
+
+        let mut cmd_mgr = RwnxCmdMgr::new();
+        let t = cmd_mgr.push(DBG_MEM_READ_CFM, cmd_flags::REQ_CFM).unwrap();
+        super::super::flow::poll_rx_one(&emu, &mut cmd_mgr).unwrap();
+        set_rx_data_indication_cb(None);
+
+        assert_eq!(cmd_mgr.take_cfm_vec(t).as_deref(), Some(&params[0][..]));
+        let got = core::mem::take(&mut *DATA_IND.lock());
+        assert_eq!(got, alloc::vec![a[..3 + 60 + 100].to_vec(), b[..3 + 60 + 37].to_vec()]);
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn boot_8801_uploads_firmware_and_starts_app() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        let mut info = AicBspInfo::default();
+        boot(&emu, &mut info).unwrap();
+        assert_eq!(info.chip_rev, 3);
+
+        let wl_fw = crate::fw_load::get_firmware_by_name("fmacfw.bin").unwrap();
+        assert_eq!(emu.mem_read(RAM_FMAC_FW_ADDR, wl_fw.len()), &wl_fw[..]);
+        let patch = crate::fw_load::get_firmware_by_name("fmacfw_patch.bin").unwrap();
+        assert_eq!(emu.mem_read(RAM_FMAC_FW_PATCH_ADDR, patch.len()), &patch[..]);
+        // aicbsp_system_config 首项与 aicwifi_patch_config 的 patch 基址
+        assert_eq!(emu.mem_read_word(0x4050_0014), 0x0000_0101);
+        assert_eq!(emu.mem_read_word(0x001e_5318), 0x001e_6000);
+        assert_eq!(emu.started(), Some((RAM_FMAC_FW_ADDR, HOST_START_APP_AUTO)));
+        assert_eq!(emu.msg_log().last(), Some(&DBG_START_APP_REQ));
+        assert_eq!(emu.dropped(), 0);
+        assert_eq!(emu.pending_rx(), 0);
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn boot_8801_reads_back_uploaded_blocks_when_verify_enabled() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let saved = crate::aicbsp_get_feature();
+        crate::aicbsp_set_feature(&crate::AicBspFeature { fw_verify: crate::FwVerifyMode::Sampled, ..saved });
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        let r = boot(&emu, &mut AicBspInfo::default());
+        crate::aicbsp_set_feature(&saved);
+        r.unwrap();
+
+        let wl_fw = crate::fw_load::get_firmware_by_name("fmacfw.bin").unwrap();
+        // chip_rev 与 patch config_base 各一次，其余为每 1KB 块首/中/尾回读
+        assert!(emu.msg_count(DBG_MEM_READ_REQ) >= 2 + 3 * (wl_fw.len() / 1024));
+        assert_eq!(emu.started(), Some((RAM_FMAC_FW_ADDR, HOST_START_APP_AUTO)));
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn boot_8801_pipelines_upload_when_window_enabled() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let wl_fw = crate::fw_load::get_firmware_by_name("fmacfw.bin").unwrap();
+        let wl_stats = || {
+            crate::fw_load::aicbsp_fw_upload_stats().into_iter().find(|s| s.mem_addr == RAM_FMAC_FW_ADDR).unwrap()
+        };
+
+        // 默认：8801 与 LicheeRV 一致逐块等 CFM
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        boot(&emu, &mut AicBspInfo::default()).unwrap();
+        let baseline = wl_stats();
+        assert_eq!((baseline.window, baseline.max_in_flight), (1, 1));
+        assert_eq!(baseline.blocks, wl_fw.len().div_ceil(FW_UPLOAD_BLOCK_SIZE));
+
+        crate::fw_load::aicbsp_set_fw_upload_window(ProductId::Aic8801, 4);
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        let r = boot(&emu, &mut AicBspInfo::default());
+        crate::fw_load::aicbsp_set_fw_upload_window(ProductId::Aic8801, crate::fw_load::FW_UPLOAD_WINDOW_8801);
+        r.unwrap();
+        let piped = wl_stats();
+        assert_eq!(piped.blocks, baseline.blocks);
+        assert_eq!((piped.window, piped.max_in_flight), (4, 4));
+        assert_eq!(emu.started(), Some((RAM_FMAC_FW_ADDR, HOST_START_APP_AUTO)));
+        assert_eq!(emu.dropped(), 0);
+    }
+
+    #[test]
+    fn testmode_without_rf_firmware_fails_before_start_app() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        assert!(crate::fw_load::get_firmware_by_name("fmacfw_rf.bin").is_none());
+        let emu = Arc::new(EmuBootrom8801::new(3));
+        let mut info = AicBspInfo { cpmode: crate::firmware::AicBspCpMode::Test as u8, ..AicBspInfo::default() };
+        assert_eq!(boot(&emu, &mut info), Err(AxError::NotFound));
+        assert_eq!(emu.started(), None);
+        assert_eq!(emu.msg_count(DBG_MEM_BLOCK_WRITE_REQ), 0);
+    }
+}
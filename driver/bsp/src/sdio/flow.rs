@@ -4,27 +4,40 @@
 //! “平台上电 → sdio_init → probe 等待 → driver_fw_init → sdio_release”及 sdio_exit 对应。
 //! 多线程：与 LicheeRV 100% 对齐 — bustx_thread（wait(bustx_trgg) + tx_process）+ busrx_thread（wait(busrx_trgg) + process_rxframes）。
 
-use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 use axerrno::{AxError, AxResult};
-use spin::{Mutex, Once};
+use spin::Mutex;
 
-use crate::cmd::{LmacMsg, RwnxCmdMgr};
-use crate::export::AicBspInfo;
+use crate::cmd::{cmd_flags, LmacMsg, RwnxCmdMgr, IPC_E2A_MSG_PARAM_SIZE, RWNX_CMD_MAX_QUEUED};
+use crate::export::{AicBspInfo, FwVerifyMode};
 use crate::fw_load::{
-    build_dbg_mem_read_req, build_dbg_mem_write_req, send_dbg_mem_read, send_dbg_mem_write, send_dbg_mem_mask_write,
-    fw_upload_blocks, fw_start_app, get_firmware_by_name,
+    build_dbg_mem_mask_write_req, build_dbg_mem_read_req, build_dbg_mem_write_req, build_dbg_start_app_req,
+    fw_upload_blocks, fw_upload_blocks_windowed, fw_upload_stats_record, fw_upload_stats_reset, fw_upload_window, fw_upload_block_size,
+    fw_verify_blocks, get_firmware_by_name,
     parse_dbg_mem_read_cfm_with_addr,
-    DBG_MEM_BLOCK_WRITE_REQ, DBG_MEM_READ_CFM, DBG_MEM_WRITE_CFM, DBG_MEM_WRITE_REQ, DBG_MEM_BLOCK_WRITE_CFM, CHIP_REV_MEM_ADDR, RAM_FMAC_FW_ADDR, RAM_FMAC_FW_PATCH_ADDR, HOST_START_APP_AUTO,
+    DBG_MEM_BLOCK_WRITE_REQ, DBG_MEM_MASK_WRITE_CFM, DBG_MEM_READ_CFM, DBG_START_APP_CFM, DBG_MEM_WRITE_CFM, DBG_MEM_WRITE_REQ, DBG_MEM_BLOCK_WRITE_CFM, CHIP_REV_MEM_ADDR, RAM_FMAC_FW_ADDR, RAM_FMAC_FW_PATCH_ADDR, RAM_LMAC_FW_ADDR, HOST_START_APP_AUTO,
+    FW_RAM_ADID_BASE_ADDR, FW_RAM_ADID_BASE_ADDR_8800D80, FW_RAM_PATCH_BASE_ADDR, FW_RAM_PATCH_BASE_ADDR_8800D80,
 };
-use crate::firmware::get_firmware_list;
+use crate::chip_info::{clear_chip_info, set_chip_ident};
+use crate::bt::{set_bt_fw_ready, BtModeCfg};
+use crate::firmware::{check_firmware_image, get_firmware_list, AicBspCpMode};
+use crate::fw_source::FirmwareBlob;
+use crate::fwlog::FwLogLevel;
 use crate::gpio::WifiGpioControl;
+use crate::patch_table::PatchTableWriter;
+use crate::ipc_trace::{ipc_trace_record, ipc_trace_record_a2e_buf, IpcTraceDir};
 use crate::sync;
 
 use skb::SkBuff;
 
 use super::backend::Aic8800SdioHost;
+use super::fw_8800dc::{aicbsp_fw_init_8800dc, aicbt_patch_load, DbgMemIo};
 use super::ops::{CisReadOps, SdioOps};
 use super::ops::Aic8800Sdio;
+#[cfg(feature = "record")]
+use super::record::SdioRecorder;
+use super::tx_aggr::{TxDataPriv, TxSender};
 use super::types::ProductId;
 
 /// 未 probe 时使用的产品 ID 占位值（用于静态存储）
@@ -35,18 +48,110 @@ pub(super) static CURRENT_PRODUCT_ID: AtomicU32 = AtomicU32::new(PRODUCT_ID_NONE
 
 /// 已 probe 的 SDIO 设备（用于 aicbsp_driver_fw_init 发送 IPC、固件上传等）
 static SDIO_DEVICE: Mutex<Option<Aic8800Sdio>> = Mutex::new(None);
+/// 代替 SDIO_DEVICE 的 SdioOps：软件设备（sim，如带 LMAC 固件模型的 EmuBootrom8801）或板上录制包装（record，
+/// SdioRecorder<SdioDeviceRef>）。挂上后 aicbsp_driver_fw_init 以 FwInitBus::Poll 在它上面执行，
+/// submit_cmd_tx_and_wait_tx_done 直接 send_msg，E2A 收包与数据 TX 也都经它
+#[cfg(any(test, feature = "sim", feature = "record"))]
+static OPS_OVERRIDE: Mutex<Option<Arc<dyn SdioOps + Send + Sync>>> = Mutex::new(None);
 /// 命令管理器（与 SDIO 设备配对，用于 DBG_* 请求-确认）
 static CMD_MGR: Mutex<Option<RwnxCmdMgr>> = Mutex::new(None);
+/// 数据 TX 队列（tx_priv->txq），首次 aicbsp_txdata_enqueue 时创建，sdio_exit 时丢弃
+static TX_DATA: Mutex<Option<TxDataPriv>> = Mutex::new(None);
+/// 数据 TX 发送侧（聚合缓冲与 fw_avail_bufcnt），首次发送时按当前 product_id 创建，与 TX_DATA 一起丢弃
+static TX_SENDER: Mutex<Option<TxSender>> = Mutex::new(None);
+/// 单元测试中读写 CMD_MGR 等全局状态的用例须先持此锁，避免并行用例互相替换 cmd_mgr
+#[cfg(test)]
+pub(crate) static GLOBAL_TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// 单元测试用：替换全局 cmd_mgr，返回旧值（须持 GLOBAL_TEST_LOCK）
+#[cfg(test)]
+pub(crate) fn replace_cmd_mgr_for_test(cmd_mgr: Option<RwnxCmdMgr>) -> Option<RwnxCmdMgr> {
+    core::mem::replace(&mut *CMD_MGR.lock(), cmd_mgr)
+}
+
+/// 下次 aicbsp_sdio_init 创建 CMD_MGR 时使用的队列深度
+static CMD_QUEUE_DEPTH: AtomicUsize = AtomicUsize::new(RWNX_CMD_MAX_QUEUED);
+
+/// 设置命令队列深度（默认 RWNX_CMD_MAX_QUEUED），在下次 aicbsp_sdio_init（含崩溃恢复）重建 cmd_mgr 时生效
+pub fn aicbsp_set_cmd_queue_depth(depth: usize) {
+    CMD_QUEUE_DEPTH.store(depth.max(1), Ordering::Relaxed);
+}
+
+/// 挂上软件设备：记录 product_id 并新建 cmd_mgr，此后 FDRV 的 LMAC 命令与 E2A 收包都走 dev（主机侧集成测试用）
+#[cfg(any(test, feature = "sim"))]
+pub fn aicbsp_attach_sim_device(dev: Arc<dyn SdioOps + Send + Sync>, product_id: ProductId) {
+    *OPS_OVERRIDE.lock() = Some(dev);
+    CMD_MGR.lock().replace(RwnxCmdMgr::with_max_queued(CMD_QUEUE_DEPTH.load(Ordering::Relaxed)));
+    CURRENT_PRODUCT_ID.store(product_id as u32, Ordering::SeqCst);
+    log::info!(target: "wireless::bsp::sdio", "sim device attached as {:?}", product_id);
+}
+
+/// 挂有软件设备时 aicbsp_power_cycle 改调的钩子，由模拟固件提供（复位模型状态并重新挂载，代替下电/上电/driver_fw_init）
+#[cfg(any(test, feature = "sim"))]
+static SIM_POWER_CYCLE: Mutex<Option<fn()>> = Mutex::new(None);
+
+/// 登记软件设备的下电/上电钩子，崩溃恢复（aicbsp_wifi_recover）经它重启模拟固件；None 取消
+#[cfg(any(test, feature = "sim"))]
+pub fn aicbsp_set_sim_power_cycle(hook: Option<fn()>) {
+    *SIM_POWER_CYCLE.lock() = hook;
+}
+
+/// 已挂软件设备且登记了钩子时返回钩子，供 aicbsp_power_cycle 代替真实的 bus_down/bus_up
+#[cfg(any(test, feature = "sim"))]
+pub(crate) fn sim_power_cycle_hook() -> Option<fn()> {
+    if OPS_OVERRIDE.lock().is_none() {
+        return None;
+    }
+    *SIM_POWER_CYCLE.lock()
+}
+
+/// 卸下软件设备，与 aicbsp_sdio_exit 一样清空 product_id 并 flush cmd_mgr
+#[cfg(any(test, feature = "sim"))]
+pub fn aicbsp_detach_sim_device() {
+    if OPS_OVERRIDE.lock().take().is_none() {
+        return;
+    }
+    CURRENT_PRODUCT_ID.store(PRODUCT_ID_NONE, Ordering::SeqCst);
+    TX_DATA.lock().take();
+    TX_SENDER.lock().take();
+    let cmd_mgr = CMD_MGR.lock().take();
+    if let Some(mut cmd_mgr) = cmd_mgr {
+        cmd_mgr.flush();
+        cmd_mgr.take_completions().run();
+    }
+}
+
+/// 开始板上录制：之后 aicbsp_sdio_init 的 F1 配置与 bus_start、aicbsp_driver_fw_init、E2A 收包与数据 TX
+/// 都经返回的 SdioRecorder 访问真实设备。须在 aicbsp_sdio_init 之前调用；启动后用 take_trace().to_text() 导出
+#[cfg(feature = "record")]
+pub fn aicbsp_sdio_record_start() -> Arc<SdioRecorder<SdioDeviceRef>> {
+    let rec = Arc::new(SdioRecorder::new(SdioDeviceRef));
+    *OPS_OVERRIDE.lock() = Some(rec.clone());
+    log::info!(target: "wireless::bsp::sdio", "sdio recording started");
+    rec
+}
+
+/// 结束板上录制，此后恢复直接访问 SDIO_DEVICE
+#[cfg(feature = "record")]
+pub fn aicbsp_sdio_record_stop() {
+    if OPS_OVERRIDE.lock().take().is_some() {
+        log::info!(target: "wireless::bsp::sdio", "sdio recording stopped");
+    }
+}
 
 /// 是否已启动 busrx 线程（对齐 LicheeRV busrx_thread）
 static BUSRX_RUNNING: AtomicBool = AtomicBool::new(false);
 /// 是否已启动 bustx 线程（对齐 LicheeRV aicwf_sdio_bustx_thread）
 static BUSTX_RUNNING: AtomicBool = AtomicBool::new(false);
+/// 线程是否仍在运行（线程函数退出前清零）；sdio_exit 后再次 sdio_init（崩溃恢复）时，须等旧线程退出再启动新线程
+static BUSRX_ALIVE: AtomicBool = AtomicBool::new(false);
+static BUSTX_ALIVE: AtomicBool = AtomicBool::new(false);
+static SDIO_IRQ_WORK_ALIVE: AtomicBool = AtomicBool::new(false);
 
 /// 待发送的 CMD 消息（LicheeRV tx_priv->cmd_buf/cmd_len/cmd_txstate），bustx 线程取走后执行 send_msg
 /// 主线程只写 payload_len 字节（如 24），bustx 内照抄 aicwf_sdio_tx_msg 做 align+TAIL+512
-/// 与 LicheeRV CMD_BUF_MAX 对齐：须容纳 DBG_MEM_BLOCK_WRITE_REQ 整包（16+1032=1048，向上取整 1536）
-const PENDING_CMD_TX_CAP: usize = 1536;
+/// 与 LicheeRV CMD_BUF_MAX 对齐：须容纳 DBG_MEM_BLOCK_WRITE_REQ 整包（16+1288=1304，向上取整 1536）
+pub const PENDING_CMD_TX_CAP: usize = 1536;
 static PENDING_CMD_TX: Mutex<Option<([u8; PENDING_CMD_TX_CAP], usize)>> = Mutex::new(None);
 /// bustx 完成 send_msg 后的结果（LicheeRV cmd_tx_succ），调用方 wait_tx_done 后取
 static TX_RESULT: Mutex<Option<i32>> = Mutex::new(None);
@@ -84,8 +189,12 @@ fn aicwf_sdio_tx_msg_pad(buf: &mut [u8], payload_len: usize) -> usize {
     send_len
 }
 
-/// IPC 接收缓冲区大小。LicheeRV 按 block_cnt*512 读满整块以排空 RD_FIFO，否则芯片可能不响应下一包 IPC；故至少 512。
-const IPC_RX_BUF_SIZE: usize = 512;
+/// 未实现 rx_pending_len 的 SdioOps 每次定长读取的长度（Aic8800Sdio 各芯片均按 BLOCK_CNT / MISC_INT_STATUS 定长，不走此路径）
+const IPC_RX_FIXED_READ_LEN: usize = 512;
+
+/// E2A 消息 param 最大长度：ipc_e2a_msg.param 为 u32[IPC_E2A_MSG_PARAM_SIZE]（1024 字节）。
+/// sm_connect_ind（含 assoc IE）、efuse/校准 dump 等均超过 RWNX_CMD_E2AMSG_LEN_MAX(256)，须整段交给 cmd_mgr 与指示回调。
+const E2A_MSG_PARAM_MAX: usize = IPC_E2A_MSG_PARAM_SIZE * 4;
 
 /// LicheeRV 8801 IPC 发送长度：与 aicwf_sdio_tx_msg 完全一致（aicsdio.c 964-978）
 /// 1) 先 4 字节对齐（TX_ALIGNMENT=4）；2) 未满 512 时加 TAIL_LEN(4) 再向上取整到 512。
@@ -102,6 +211,34 @@ fn ipc_send_len_8801(serialized_len: usize) -> usize {
     }
 }
 
+/// A2E 缓冲中 lmac_msg 头的偏移：8801 为 8（serialize_8801 的 SDIO 头），DC/DW/D80/D80X2 为 0
+#[inline]
+fn ipc_lmac_hdr_offset(product_id: ProductId) -> usize {
+    if product_id == ProductId::Aic8801 { 8 } else { 0 }
+}
+
+/// 按芯片序列化 A2E 消息，返回 (序列化长度, 发送长度)：
+/// 8801 为 serialize_8801 + ipc_send_len_8801 补齐；DC/DW/D80/D80X2 一律为 serialize（lmac_msg 起始，不补齐）。
+/// 所有发送路径（BSP DBG_*、FDRV LMAC 命令）经此选择格式；buf 放不下时返回 -EINVAL
+pub fn ipc_serialize_lmac_msg(product_id: ProductId, msg: &LmacMsg, buf: &mut [u8]) -> Result<(usize, usize), i32> {
+    let len = ipc_lmac_hdr_offset(product_id) + 8 + msg.header.param_len as usize;
+    let send_len = if product_id == ProductId::Aic8801 {
+        ipc_send_len_8801(len)
+    } else {
+        len
+    };
+    if send_len > buf.len() {
+        return Err(-22);
+    }
+    if product_id == ProductId::Aic8801 {
+        msg.serialize_8801(buf);
+    } else {
+        msg.serialize(buf);
+    }
+    buf[len..send_len].fill(0);
+    Ok((len, send_len))
+}
+
 /// 从 SDIO 收一包并解析为 E2A 消息，若为 CFM 则调用 cmd_mgr.on_cfm（对应 LicheeRV RX 路径）
 /// LicheeRV 接收时用 skb->data+4 作为 ipc_e2a_msg，即前 4 字节为前缀，E2A 头从 offset 4 开始
 /// 用于 log 的十六进制前缀（最多 32 字节），避免分配
@@ -148,6 +285,8 @@ pub fn set_rx_data_indication_cb(cb: RxDataIndicationCb) {
 
 /// 与 LicheeRV aicsdio.h 一致：仅 SDIO_TYPE_CFG_CMD_RSP(0x11) 时调用 rwnx_rx_handle_msg → msgind → on_cfm
 const SDIO_TYPE_CFG_CMD_RSP: u8 = 0x11;
+/// 固件 printf 输出帧（aicwf_sdio.h SDIO_TYPE_CFG_PRINT）
+const SDIO_TYPE_CFG_PRINT: u8 = 0x13;
 
 /// 从 buf[offset..n] 解析一帧并 on_cfm；返回本帧长度（含头），无法解析返回 0
 ///
@@ -160,11 +299,19 @@ fn parse_one_cfm_at(buf: &[u8], n: usize, offset: usize, cmd_mgr: &mut RwnxCmdMg
     let type_bits = buf[offset + 2] & 0x7f;
     if type_bits == SDIO_TYPE_CFG_CMD_RSP {
         let param_len = u16::from_le_bytes([buf[offset + 10], buf[offset + 11]]) as usize;
-        if param_len <= 256 {
+        if param_len <= E2A_MSG_PARAM_MAX {
             let total = 16 + param_len; // 8B e2a header + 4B pattern + param（LicheeRV ipc_e2a_msg）
             if offset + total <= n {
                 let msg_id = u16::from_le_bytes([buf[offset + 4], buf[offset + 5]]);
                 let param_start = offset + 16;
+                ipc_trace_record(
+                    IpcTraceDir::E2A,
+                    msg_id,
+                    u16::from_le_bytes([buf[offset + 6], buf[offset + 7]]),
+                    u16::from_le_bytes([buf[offset + 8], buf[offset + 9]]),
+                    param_len as u16,
+                    &buf[param_start..offset + total],
+                );
                 log::info!(target: "wireless::bsp::sdio", "poll_rx_one: CFM (4B prefix) msg_id=0x{:04x} param_len={}", msg_id, param_len);
                 if msg_id == DBG_MEM_BLOCK_WRITE_CFM {
                     log::info!(target: "wireless::bsp::sdio", "poll_rx_one: CFM 已收到 DBG_MEM_BLOCK_WRITE_CFM (0x040b)");
@@ -173,18 +320,27 @@ fn parse_one_cfm_at(buf: &[u8], n: usize, offset: usize, cmd_mgr: &mut RwnxCmdMg
                 if let Some(cb) = *E2A_INDICATION_CB.lock() {
                     unsafe { cb(msg_id, buf[param_start..].as_ptr(), param_len) };
                 }
-                return total;
+                // 突发内下一帧从 RX_ALIGNMENT 对齐处开始（aicwf_sdio_process_rxframes skb_pull(adjust_len + 4)）
+                return (total + RX_ALIGNMENT - 1) & !(RX_ALIGNMENT - 1);
             }
         }
     }
     // 与 LicheeRV 一致：仅接受合法的 IPC CFM；param_len=0 的 8 字节多为缓冲区尾随垃圾，若接受会误触发 on_cfm(0x8800/0x0000 等) 并刷屏
     if offset + 8 <= n {
         let param_len = u16::from_le_bytes([buf[offset + 6], buf[offset + 7]]) as usize;
-        if param_len > 0 && param_len <= 256 {
+        if param_len > 0 && param_len <= E2A_MSG_PARAM_MAX {
             let total = 8 + param_len;
             if offset + total <= n {
                 let msg_id = u16::from_le_bytes([buf[offset], buf[offset + 1]]);
                 let param_start = offset + 8;
+                ipc_trace_record(
+                    IpcTraceDir::E2A,
+                    msg_id,
+                    u16::from_le_bytes([buf[offset + 2], buf[offset + 3]]),
+                    u16::from_le_bytes([buf[offset + 4], buf[offset + 5]]),
+                    param_len as u16,
+                    &buf[param_start..offset + total],
+                );
                 log::info!(target: "wireless::bsp::sdio", "poll_rx_one: CFM (no prefix) msg_id=0x{:04x} param_len={}", msg_id, param_len);
                 if msg_id == DBG_MEM_BLOCK_WRITE_CFM {
                     log::info!(target: "wireless::bsp::sdio", "poll_rx_one: CFM 已收到 DBG_MEM_BLOCK_WRITE_CFM (0x040b)");
@@ -200,17 +356,33 @@ fn parse_one_cfm_at(buf: &[u8], n: usize, offset: usize, cmd_mgr: &mut RwnxCmdMg
     0
 }
 
-/// 与 LicheeRV aicwf_process_rxframes 一致：一次 recv_pkt 可能读到多包，须循环解析直至无完整帧。
+/// 与 LicheeRV aicwf_process_rxframes 一致：一次突发读可能含多帧，须循环解析直至无完整帧。
 /// 数据帧：(buf[2] & SDIO_TYPE_CFG) != SDIO_TYPE_CFG 时调用 set_rx_data_indication_cb 注册的回调；
-/// 配置帧：0x10..0x13 走 parse_one_cfm_at（on_cfm + E2A 指示回调）。
+/// 配置帧：0x13 PRINT 为 4 字节头 + pkt_len 字节文本（按 RX_ALIGNMENT 对齐），交给 fwlog；其余走 parse_one_cfm_at（on_cfm + E2A 指示回调）。
 /// 返回 Err(EAGAIN) 时调用方应释放锁并 wait_sdio_irq_work_done 后重试。
-fn poll_rx_one(sdio: &dyn SdioOps, cmd_mgr: &mut RwnxCmdMgr) -> Result<(), i32> {
+pub(super) fn poll_rx_one(sdio: &dyn SdioOps, cmd_mgr: &mut RwnxCmdMgr) -> Result<(), i32> {
     const SDIO_TYPE_CFG: u8 = 0x10;
-    let mut skb = SkBuff::alloc(IPC_RX_BUF_SIZE);
-    let n = match sdio.recv_pkt(skb.data_mut(), IPC_RX_BUF_SIZE as u32, 1) {
+    // 与 LicheeRV aicwf_sdio_hal_irqhandler 一致：先读 BLOCK_CNT（V3 为 MISC_INT_STATUS）得到整个突发长度，
+    // 按该长度分配 skb 并一次多块读满（排空 RD_FIFO），再逐帧拆开；DC/DW 读 F2 的 BLOCK_CNT。
+    // 只有不提供 rx_pending_len 的 SdioOps 才退回 512 字节定长 recv_pkt
+    let (mut skb, n) = match sdio.rx_pending_len() {
+        Ok(0) => return Ok(()),
+        Ok(data_len) => {
+            let mut skb = SkBuff::alloc(data_len);
+            let n = sdio.read_rx_fifo(&mut skb.data_mut()[..data_len]);
+            (skb, n)
+        }
+        Err(-19) => {
+            let mut skb = SkBuff::alloc(IPC_RX_FIXED_READ_LEN);
+            let n = sdio.recv_pkt(skb.data_mut(), IPC_RX_FIXED_READ_LEN as u32, 1);
+            (skb, n)
+        }
+        Err(e) => (SkBuff::alloc(0), Err(e)),
+    };
+    let n = match n {
         Ok(s) => s,
         Err(e) => {
-            log::warn!(target: "wireless::bsp::sdio", "poll_rx_one: recv_pkt err={} (e.g. BUF_RRDY timeout / EAGAIN)", e);
+            log::warn!(target: "wireless::bsp::sdio", "poll_rx_one: rx read err={} (e.g. BUF_RRDY timeout / EAGAIN)", e);
             return Err(e);
         }
     };
@@ -223,6 +395,10 @@ fn poll_rx_one(sdio: &dyn SdioOps, cmd_mgr: &mut RwnxCmdMgr) -> Result<(), i32>
     while offset < n {
         if offset + 3 <= n {
             let pkt_len = u16::from_le_bytes([buf[offset], buf[offset + 1]]) as usize;
+            // 突发按块读满，末尾是 0 填充；pkt_len 为 0 即已无帧，不能当作空数据帧交给回调
+            if pkt_len == 0 {
+                break;
+            }
             let type_byte = buf[offset + 2];
             if (type_byte & SDIO_TYPE_CFG) != SDIO_TYPE_CFG {
                 let aggr_len = pkt_len + RX_HWHRD_LEN_DATA;
@@ -235,6 +411,22 @@ fn poll_rx_one(sdio: &dyn SdioOps, cmd_mgr: &mut RwnxCmdMgr) -> Result<(), i32>
                     offset += total;
                     continue;
                 }
+            } else if (type_byte & 0x7f) == SDIO_TYPE_CFG_PRINT {
+                let adjust_len = (pkt_len + RX_ALIGNMENT - 1) & !(RX_ALIGNMENT - 1);
+                if offset + 4 + pkt_len <= n {
+                    crate::fwlog::fwlog_handle_print(&buf[offset + 4..offset + 4 + pkt_len]);
+                    offset += 4 + adjust_len;
+                    continue;
+                }
+                // 帧头已是 PRINT，正文被截断时不能再当 CFM 解析，丢弃本次剩余数据
+                log::warn!(
+                    target: "wireless::bsp::sdio",
+                    "poll_rx_one: truncated PRINT frame len={} at offset={} n={}",
+                    pkt_len,
+                    offset,
+                    n
+                );
+                break;
             }
         }
         let consumed = parse_one_cfm_at(buf, n, offset, cmd_mgr);
@@ -255,11 +447,34 @@ fn poll_rx_one(sdio: &dyn SdioOps, cmd_mgr: &mut RwnxCmdMgr) -> Result<(), i32>
 }
 
 /// 在持有 SDIO_DEVICE 与 CMD_MGR 锁时执行 f，供 RX 线程与多线程 IPC 路径使用（短暂持锁）
+/// 命令完成回调（CmdCompleteCb）在释放 CMD_MGR 锁之后、返回之前执行，回调内可再 with_cmd_mgr
 pub fn with_cmd_mgr<R, F>(f: F) -> Option<R>
 where
     F: FnOnce(&mut RwnxCmdMgr) -> R,
 {
-    CMD_MGR.lock().as_mut().map(f)
+    run_on_cmd_mgr(CMD_MGR.lock(), f)
+}
+
+/// 与 with_cmd_mgr 相同，但 CMD_MGR 锁已被占用（例如在 with_cmd_mgr 闭包内）时不等待、返回 None；
+/// 取得锁但 cmd_mgr 不存在时返回 Some(None)
+pub(crate) fn try_with_cmd_mgr<R, F>(f: F) -> Option<Option<R>>
+where
+    F: FnOnce(&mut RwnxCmdMgr) -> R,
+{
+    CMD_MGR.try_lock().map(|guard| run_on_cmd_mgr(guard, f))
+}
+
+fn run_on_cmd_mgr<R, F>(mut guard: spin::MutexGuard<'_, Option<RwnxCmdMgr>>, f: F) -> Option<R>
+where
+    F: FnOnce(&mut RwnxCmdMgr) -> R,
+{
+    let cmd_mgr = guard.as_mut()?;
+    cmd_mgr.cancel_dropped_handles();
+    let r = f(cmd_mgr);
+    let completions = cmd_mgr.take_completions();
+    drop(guard);
+    completions.run();
+    Some(r)
 }
 
 /// 在持有 SDIO_DEVICE 锁时执行 f（短暂持锁）
@@ -270,81 +485,113 @@ where
     SDIO_DEVICE.lock().as_ref().map(f)
 }
 
+/// 以 &dyn SdioOps 访问设备：挂有 OPS_OVERRIDE（软件设备或录制包装）时经它，否则短暂持 SDIO_DEVICE 锁。
+/// BSP 自身的 SdioOps 层访问都走这里，板上录制时才能全部记进 trace
+fn with_sdio_ops<R>(f: impl FnOnce(&dyn SdioOps) -> R) -> Option<R> {
+    #[cfg(any(test, feature = "sim", feature = "record"))]
+    {
+        let ops = OPS_OVERRIDE.lock().clone();
+        if let Some(ops) = ops {
+            return Some(f(&*ops));
+        }
+    }
+    with_sdio(|sdio| f(sdio))
+}
+
 /// 获取 SDIO_DEVICE 锁，供 mmc crate 的 MmcHost 实现使用（claim_host 返回此 guard）
 pub(super) fn lock_sdio_device() -> spin::MutexGuard<'static, Option<Aic8800Sdio>> {
     SDIO_DEVICE.lock()
 }
 
-/// 读 F1 寄存器并打日志：8801 在 no byte mode(0x11=1) 下收 CFM 看 BLOCK_CNT(0x12)；BYTEMODE_LEN(0x02)/FLOW_CTRL(0x0A) 一并打出便于对照 LicheeRV
+/// 读 F1 寄存器并打日志：8801 在 no byte mode(0x11=1) 下收 CFM 看 BLOCK_CNT(0x12)；BYTEMODE_LEN(0x02)/FLOW_CTRL(0x0A) 一并打出便于对照 LicheeRV。
+/// D80/D80X2 按 V3 布局读 MISC_INT_STATUS(0x04)/BYTEMODE_LEN(0x05)/FLOW_CTRL_Q1(0x03)
 pub fn log_f1_block_cnt_flow_ctrl(waited_ms: u32) {
     with_sdio(|sdio| {
-        let bc = sdio.read_byte(0x112).unwrap_or(0xff); // F1 0x12 BLOCK_CNT（8801 主用，非 0 表示有数据）
-        let bm = sdio.read_byte(0x102).unwrap_or(0xff); // F1 0x02 BYTEMODE_LEN（block_cnt>=64 时用）
-        let fc = sdio.read_byte(0x10A).unwrap_or(0xff); // F1 0x0A FLOW_CTRL
+        let (block_cnt_reg, bytemode_len_reg, flow_ctrl_reg) = sdio.f1_status_regs();
+        let bc = sdio.readb(block_cnt_reg as u32).unwrap_or(0xff); // 非 0 表示有数据
+        let bm = sdio.readb(bytemode_len_reg as u32).unwrap_or(0xff); // block_cnt>=64 时用
+        let fc = sdio.readb(flow_ctrl_reg as u32).unwrap_or(0xff);
         log::warn!(
             target: "wireless::bsp::sdio",
-            "F1 @ {}ms: BLOCK_CNT(0x12)=0x{:02x} BYTEMODE_LEN(0x02)=0x{:02x} FLOW_CTRL(0x0A)=0x{:02x}",
-            waited_ms, bc, bm, fc
+            "F1 @ {}ms: BLOCK_CNT(0x{:02x})=0x{:02x} BYTEMODE_LEN(0x{:02x})=0x{:02x} FLOW_CTRL(0x{:02x})=0x{:02x}",
+            waited_ms, block_cnt_reg, bc, bytemode_len_reg, bm, flow_ctrl_reg, fc
         );
     });
 }
 
 /// 从 SDIO 收一包并解析 E2A（on_cfm + E2A 指示回调）。供 FDRV 在 wait_done_until 的 poll_fn 中调用，以在等待 CFM 时收包。
+/// 无 busrx 线程（sim、轮询模式）时，每次调用同时按 RX_POLL_MS 推进 cmd_mgr 的命令超时；busrx 运行时由其计时。
 pub fn sdio_poll_rx_once() {
-    run_poll_rx_one();
+    let tick_ms = if BUSRX_ALIVE.load(Ordering::Acquire) { 0 } else { RX_POLL_MS as u32 };
+    run_poll_rx_one(tick_ms);
 }
 
 const EAGAIN: i32 = -11;
 const IRQ_WORK_DONE_WAIT_MS_RX: u64 = 2000;
+/// busrx 每轮等待时长，亦是 tick_timeouts 每轮计入的时间
+const RX_POLL_MS: u64 = 1;
 
 /// RX 线程循环体：从 SDIO 收一包并解析、on_cfm（对齐 LicheeRV aicwf_process_rxframes）；锁顺序 CMD_MGR → SDIO_DEVICE 避免死锁。
 /// 若 poll_rx_one 返回 EAGAIN（CARD_INT 已入队），释放锁、等待 work 完成后再返回，下次 busrx 迭代重试。
-fn run_poll_rx_one() {
-    let mut cmd_guard = CMD_MGR.lock();
-    let cmd_mgr = match cmd_guard.as_mut() {
-        Some(c) => c,
-        None => return,
-    };
-    let sdio_guard = SDIO_DEVICE.lock();
-    let sdio = match sdio_guard.as_ref() {
-        Some(s) => s,
-        None => return,
-    };
-    if let Err(e) = poll_rx_one(sdio as &dyn SdioOps, cmd_mgr) {
-        if e == EAGAIN {
-            drop(sdio_guard);
-            drop(cmd_guard);
-            let _ = crate::sdio_irq::wait_sdio_irq_work_done_timeout(
-                core::time::Duration::from_millis(IRQ_WORK_DONE_WAIT_MS_RX),
-            );
-        }
+/// 收包后按 tick_ms 推进命令超时，到期命令在同一次持锁内 complete_timeout 并唤醒其 CmdHandle。
+fn run_poll_rx_one(tick_ms: u32) {
+    let r = with_cmd_mgr(|cmd_mgr| {
+        let r = with_sdio_ops(|sdio| poll_rx_one(sdio, cmd_mgr));
+        cmd_mgr.tick_timeouts(tick_ms);
+        r
+    });
+    // 锁已在返回前释放，再等 sdio_irq_work 完成
+    if let Some(Some(Err(EAGAIN))) = r {
+        let _ = crate::sdio_irq::wait_sdio_irq_work_done_timeout(core::time::Duration::from_millis(IRQ_WORK_DONE_WAIT_MS_RX));
     }
 }
 
 /// busrx 线程：wait(busrx_trgg 等价) + run_poll_rx_one，与 LicheeRV aicwf_sdio_busrx_thread 对齐
 fn busrx_thread_fn() {
-    const RX_POLL_MS: u64 = 1;
     while BUSRX_RUNNING.load(Ordering::Relaxed) {
         crate::sdio_irq::wait_sdio_or_timeout(core::time::Duration::from_millis(RX_POLL_MS));
-        run_poll_rx_one();
+        run_poll_rx_one(RX_POLL_MS as u32);
         // 无 PLIC/软中断时 wait_sdio_or_timeout 直接返回，busrx 会占满 CPU、main 无法调度到 send_msg，故每轮主动 yield
         if !crate::sdio_irq::use_sdio_irq() {
             axtask::sleep(core::time::Duration::from_millis(RX_POLL_MS));
         }
     }
     log::debug!(target: "wireless::bsp::sdio", "busrx_thread exit");
+    BUSRX_ALIVE.store(false, Ordering::Release);
 }
 
-/// 确保已启动 busrx 线程（仅启动一次，对齐 LicheeRV aicwf_bus_init 里 kthread_run(sdio_busrx_thread)）
-pub fn ensure_busrx_thread_started() {
-    static STARTED: Once<()> = Once::new();
-    STARTED.call_once(|| {
-        BUSRX_RUNNING.store(true, Ordering::Relaxed);
-        let _ = axtask::spawn(busrx_thread_fn);
-        log::info!(target: "wireless::bsp::sdio", "busrx_thread started (align LicheeRV aicwf_sdio_busrx_thread)");
-        // 让出 CPU，确保 busrx 至少被调度一次后再发首包，避免主线程持 SDIO 锁时 busrx 从未运行
+/// 等待上一轮 sdio_exit 停掉的线程真正退出（对应 LicheeRV aicwf_bus_deinit 内 kthread_stop 的同步语义）。
+/// 超时仍未退出返回 -EBUSY：不能再起一个新线程与旧线程同时收发 SDIO。
+fn wait_thread_exit(alive: &AtomicBool, name: &str) -> Result<(), i32> {
+    const THREAD_EXIT_WAIT_MS: u32 = 200;
+    let mut waited_ms = 0;
+    while alive.load(Ordering::Acquire) && waited_ms < THREAD_EXIT_WAIT_MS {
         axtask::sleep(core::time::Duration::from_millis(1));
-    });
+        waited_ms += 1;
+    }
+    if alive.load(Ordering::Acquire) {
+        log::error!(target: "wireless::bsp::sdio", "{} still alive after {}ms, refusing to start a second one", name, THREAD_EXIT_WAIT_MS);
+        return Err(-16); // -EBUSY
+    }
+    Ok(())
+}
+
+/// 确保已启动 busrx 线程（对齐 LicheeRV aicwf_bus_init 里 kthread_run(sdio_busrx_thread)）；sdio_exit 后可再次启动。
+/// 上一轮的 busrx 线程尚未退出时返回 -EBUSY，不启动新线程。
+pub fn ensure_busrx_thread_started() -> Result<(), i32> {
+    if BUSRX_RUNNING.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+    if let Err(e) = wait_thread_exit(&BUSRX_ALIVE, "busrx_thread") {
+        BUSRX_RUNNING.store(false, Ordering::Release);
+        return Err(e);
+    }
+    BUSRX_ALIVE.store(true, Ordering::Release);
+    let _ = axtask::spawn(busrx_thread_fn);
+    log::info!(target: "wireless::bsp::sdio", "busrx_thread started (align LicheeRV aicwf_sdio_busrx_thread)");
+    // 让出 CPU，确保 busrx 至少被调度一次后再发首包，避免主线程持 SDIO 锁时 busrx 从未运行
+    axtask::sleep(core::time::Duration::from_millis(1));
+    Ok(())
 }
 
 /// CARD_INT 排队 work 线程：与 LicheeRV sdio_irq.c sdio_irq_work 一致，异步执行 sdio_run_irqs（读 0x05 + ack）。
@@ -365,6 +612,7 @@ fn sdio_irq_work_thread_fn() {
         }
     }
     log::debug!(target: "wireless::bsp::sdio", "sdio_irq_work_thread exit");
+    SDIO_IRQ_WORK_ALIVE.store(false, Ordering::Release);
 }
 
 /// bustx 线程：wait(bustx_trgg) + tx_process，与 LicheeRV aicwf_sdio_bustx_thread 一致。
@@ -402,21 +650,150 @@ fn bustx_thread_fn() {
                     );
                 }
             }
+            // 与 aicwf_sdio_tx_process 一致：CMD 优先，之后发数据队列
+            run_tx_data_process();
         }
     }
     log::debug!(target: "wireless::bsp::sdio", "bustx_thread exit");
+    BUSTX_ALIVE.store(false, Ordering::Release);
 }
 
-/// 确保已启动 bustx 线程与 CARD_INT work 线程（仅启动一次，对齐 LicheeRV aicwf_sdio_bustx_thread + sdio_irq_work）
-pub fn ensure_bustx_thread_started() {
-    static STARTED: Once<()> = Once::new();
-    STARTED.call_once(|| {
-        BUSTX_RUNNING.store(true, Ordering::Relaxed);
-        let _ = axtask::spawn(sdio_irq_work_thread_fn);
-        let _ = axtask::spawn(bustx_thread_fn);
-        log::info!(target: "wireless::bsp::sdio", "bustx_thread + sdio_irq_work_thread started (align LicheeRV aicwf_sdio_bustx_thread + sdio_irq_work)");
-        axtask::sleep(core::time::Duration::from_millis(1));
-    });
+/// 确保已启动 bustx 线程与 CARD_INT work 线程（对齐 LicheeRV aicwf_sdio_bustx_thread + sdio_irq_work）；sdio_exit 后可再次启动。
+/// 上一轮的任一线程尚未退出时返回 -EBUSY，两个线程都不启动。
+pub fn ensure_bustx_thread_started() -> Result<(), i32> {
+    if BUSTX_RUNNING.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+    let prev = wait_thread_exit(&SDIO_IRQ_WORK_ALIVE, "sdio_irq_work_thread")
+        .and_then(|()| wait_thread_exit(&BUSTX_ALIVE, "bustx_thread"));
+    if let Err(e) = prev {
+        BUSTX_RUNNING.store(false, Ordering::Release);
+        return Err(e);
+    }
+    SDIO_IRQ_WORK_ALIVE.store(true, Ordering::Release);
+    BUSTX_ALIVE.store(true, Ordering::Release);
+    let _ = axtask::spawn(sdio_irq_work_thread_fn);
+    let _ = axtask::spawn(bustx_thread_fn);
+    log::info!(target: "wireless::bsp::sdio", "bustx_thread + sdio_irq_work_thread started (align LicheeRV aicwf_sdio_bustx_thread + sdio_irq_work)");
+    axtask::sleep(core::time::Duration::from_millis(1));
+    Ok(())
+}
+
+/// 每次访问才短暂持 SDIO_DEVICE 锁的设备句柄：数据 TX 在流控重试之间会 sleep，固件初始化等 CFM 时 busrx 要收包，都不能整段占着设备。
+/// 板上录制时由 SdioRecorder 包住它（见 aicbsp_sdio_record_start）
+pub struct SdioDeviceRef;
+
+impl SdioOps for SdioDeviceRef {
+    fn writeb(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        with_sdio(|sdio| sdio.writeb(regaddr, val)).unwrap_or(Err(-19))
+    }
+
+    fn readb(&self, regaddr: u32) -> Result<u8, i32> {
+        with_sdio(|sdio| sdio.readb(regaddr)).unwrap_or(Err(-19))
+    }
+
+    fn readb_func2(&self, regaddr: u32) -> Result<u8, i32> {
+        with_sdio(|sdio| sdio.readb_func2(regaddr)).unwrap_or(Err(-19))
+    }
+
+    fn writeb_func2(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        with_sdio(|sdio| sdio.writeb_func2(regaddr, val)).unwrap_or(Err(-19))
+    }
+
+    fn read_byte(&self, addr: u32) -> Result<u8, i32> {
+        with_sdio(|sdio| sdio.read_byte(addr)).unwrap_or(Err(-19))
+    }
+
+    fn write_byte(&self, addr: u32, val: u8) -> Result<(), i32> {
+        with_sdio(|sdio| sdio.write_byte(addr, val)).unwrap_or(Err(-19))
+    }
+
+    fn recv_pkt(&self, buf: &mut [u8], size: u32, msg: u8) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.recv_pkt(buf, size, msg)).unwrap_or(Err(-19))
+    }
+
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.rx_pending_len()).unwrap_or(Err(-19))
+    }
+
+    fn read_rx_fifo(&self, buf: &mut [u8]) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.read_rx_fifo(buf)).unwrap_or(Err(-19))
+    }
+
+    fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.send_pkt(buf, count)).unwrap_or(Err(-19))
+    }
+
+    fn send_msg(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.send_msg(buf, count)).unwrap_or(Err(-19))
+    }
+
+    fn read_block(&self, addr: u32, buf: &mut [u8]) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.read_block(addr, buf)).unwrap_or(Err(-19))
+    }
+
+    fn write_block(&self, addr: u32, buf: &[u8]) -> Result<usize, i32> {
+        with_sdio(|sdio| sdio.write_block(addr, buf)).unwrap_or(Err(-19))
+    }
+}
+
+/// 发送数据队列（aicwf_sdio_tx_process 数据部分）：聚合后一次 send_pkt。
+/// 锁顺序 TX_SENDER → TX_DATA / SDIO_DEVICE；后两者只在取帧、读 FLOW_CTRL、send_pkt 时各自短暂持有，从不同时持有
+fn run_tx_data_process() {
+    if TX_DATA.lock().as_ref().is_none_or(|tx| tx.txq.is_empty()) {
+        return;
+    }
+    let mut guard = TX_SENDER.lock();
+    if guard.is_none() {
+        let Some(product_id) = aicbsp_current_product_id() else {
+            return;
+        };
+        *guard = Some(TxSender::new(product_id));
+    }
+    let Some(sender) = guard.as_mut() else {
+        return;
+    };
+    #[cfg(any(test, feature = "sim", feature = "record"))]
+    let ops = OPS_OVERRIDE.lock().clone();
+    #[cfg(not(any(test, feature = "sim", feature = "record")))]
+    let ops: Option<Arc<dyn SdioOps + Send + Sync>> = None;
+    let r = match ops {
+        Some(ops) => sender.process(&TX_DATA, &*ops),
+        None => sender.process(&TX_DATA, &SdioDeviceRef),
+    };
+    if let Err(e) = r {
+        let queued = TX_DATA.lock().as_ref().map_or(0, |tx| tx.txq.len());
+        log::warn!(target: "wireless::bsp::sdio", "tx_process: send_pkt err={}, {} frames queued", e, queued);
+    }
+}
+
+/// 提交一帧数据（txdesc_api + 802.3 payload）到 TX 队列并唤醒 bustx（aicwf_bus_txdata）。
+/// bustx 把队列中的帧聚合成一次 CMD53 发送；队列满返回 -ENOBUFS，未 probe 返回 -ENODEV。
+pub fn aicbsp_txdata_enqueue(frame: &[u8]) -> Result<(), i32> {
+    {
+        let mut guard = TX_DATA.lock();
+        if guard.is_none() {
+            aicbsp_current_product_id().ok_or(-19)?;
+            *guard = Some(TxDataPriv::new());
+        }
+        guard.as_mut().map_or(Err(-19), |tx| tx.enqueue(frame))?;
+    }
+    // 软件设备上没有 bustx 线程，由调用方 sdio_tx_process_once 发送
+    if BUSTX_RUNNING.load(Ordering::Acquire) {
+        crate::sdio_irq::notify_bustx();
+    }
+    Ok(())
+}
+
+/// 软件设备上没有 bustx 线程：由调用方（测试）显式发送 TX 队列，与 sdio_poll_rx_once 对应
+#[cfg(any(test, feature = "sim"))]
+pub fn sdio_tx_process_once() {
+    run_tx_data_process();
+}
+
+/// 数据 TX 统计：(已发送帧数, CMD53 次数, 队列中帧数)
+pub fn aicbsp_txdata_stats() -> (u64, u64, usize) {
+    TX_DATA.lock().as_ref().map_or((0, 0, 0), |tx| (tx.tx_frames, tx.tx_xfers, tx.txq.len()))
 }
 
 /// 与 LicheeRV aicwf_sdio_bus_txmsg 对齐：提交 CMD 到 bustx 线程，等待 CMD53 写完成后返回（再等 CFM 由调用方 wait_done_until）。
@@ -430,10 +807,21 @@ pub fn submit_cmd_tx_and_wait_tx_done(buf: &[u8], len: usize) -> Result<(), i32>
     if len > PENDING_CMD_TX_CAP {
         return Err(-22);
     }
+    // 8801 为 serialize_8801（8B SDIO 头 + lmac_msg），其余芯片为 serialize（lmac_msg 起始）
+    let hdr_off = aicbsp_current_product_id().map_or(0, ipc_lmac_hdr_offset);
+    ipc_trace_record_a2e_buf(&buf[..len], hdr_off);
     *TX_RESULT.lock() = None;
     // 与 LicheeRV 一致：rwnx_set_cmd_tx 内 memset(buffer,0,CMD_BUF_MAX)，再填 [0..len]；此处整块零初始化后拷贝前 len 字节
     let mut arr = [0u8; PENDING_CMD_TX_CAP];
     arr[..len].copy_from_slice(buf);
+    #[cfg(any(test, feature = "sim", feature = "record"))]
+    {
+        let ops = OPS_OVERRIDE.lock().clone();
+        if let Some(ops) = ops {
+            let send_len = aicwf_sdio_tx_msg_pad(&mut arr, len);
+            return ops.send_msg(&arr[..send_len], send_len).map(|_| ());
+        }
+    }
     *PENDING_CMD_TX.lock() = Some((arr, len));
     crate::sdio_irq::notify_bustx();
     let total_ms = TX_DONE_TIMEOUT_MS;
@@ -455,48 +843,31 @@ pub fn submit_cmd_tx_and_wait_tx_done(buf: &[u8], len: usize) -> Result<(), i32>
     }
 }
 
-/// 多线程模式下发送 DBG_MEM_READ_REQ 并等待 CFM（与 LicheeRV 一致：经 bustx 线程写 WR_FIFO，busrx 收 CFM）
-pub fn send_dbg_mem_read_busrx(mem_addr: u32, timeout_ms: u32, after_delay: Option<&mut dyn FnMut()>) -> Result<u32, i32> {
-    use crate::fw_load::{build_dbg_mem_read_req, parse_dbg_mem_read_cfm_full, parse_dbg_mem_read_cfm_with_addr, DBG_MEM_READ_CFM};
-    let token = with_cmd_mgr(|c| c.push(DBG_MEM_READ_CFM)).flatten().ok_or(-12)?;
+/// 固件启动后经 bustx 发一条 DBG_* 请求并等 CFM（如 fwlog 过滤器设置）；调用方不得持有 CMD_MGR
+pub(crate) fn send_dbg_req(msg: &LmacMsg, cfm_id: u16, timeout_ms: u32) -> Result<(), i32> {
     let product_id = aicbsp_current_product_id().ok_or(-22)?;
-    log::warn!(target: "wireless::bsp", "dbg_mem_read_busrx: sending request (mem_addr=0x{:08x})", mem_addr);
-    let msg = build_dbg_mem_read_req(mem_addr);
-    let mut buf = [0u8; 512];
-    let len = if product_id == ProductId::Aic8801 {
-        msg.serialize_8801(&mut buf)
-    } else {
-        msg.serialize(&mut buf)
+    let mut tx_fn = |m: &LmacMsg| -> Result<(), i32> {
+        let mut buf = [0u8; PENDING_CMD_TX_CAP];
+        let (len, _) = ipc_serialize_lmac_msg(product_id, m, &mut buf)?;
+        submit_cmd_tx_and_wait_tx_done(&buf[..len], len)
     };
-    // 与 LicheeRV 一致：主线程只传 payload_len，bustx 内 aicwf_sdio_tx_msg_pad 做 align+TAIL+512
-    submit_cmd_tx_and_wait_tx_done(&buf[..len], len).map_err(|e| {
-        log::warn!(target: "wireless::bsp", "dbg_mem_read_busrx: submit_cmd_tx (bustx) failed, err={}", e);
-        e
-    })?;
-    log::warn!(target: "wireless::bsp", "dbg_mem_read_busrx: request sent, waiting CFM (timeout {}ms)", timeout_ms);
-    axtask::sleep(core::time::Duration::from_millis(100));
-    if let Some(f) = after_delay {
-        f();
+    let mut poll = || {
+        with_cmd_mgr(|m| {
+            with_sdio_ops(|s| poll_rx_one(s, m));
+        });
+    };
+    let mut wait_fn = |token: usize| wait_token(token, timeout_ms, &mut poll, None, None);
+    send_dbg_req_busrx(&mut tx_fn, &mut wait_fn, msg, cfm_id, &mut [0u8; 16]).map(|_| ())
+}
+
+/// fwlog_en（aicbsp_info 或 aicbsp_feature）置位时经 io 打开固件日志；失败只告警，不影响 WiFi 启动
+fn fwlog_enable_if_configured(io: &mut dyn DbgMemIo, info: &AicBspInfo) {
+    if !info.fwlog_en && !crate::aicbsp_get_feature().fwlog_en {
+        return;
+    }
+    if let Err(e) = crate::fwlog::fwlog_enable_with(&mut |msg, cfm_id| io.lmac_req(msg, cfm_id), FwLogLevel::default()) {
+        log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: fwlog enable failed, err={}", e);
     }
-    // 使用完整 timeout_ms（如 2000ms），与 aicbsp_system_config 等一致，便于：1) 每 500ms 打 F1 BLOCK_CNT/FLOW_CTRL；2) 给设备足够时间回 CFM
-    // 不传 tick：周期性读 F1(0x02/0x12/0x0A) 时 CMD52 可能在某些板上挂死并持锁，导致整机卡住；仅保留“still waiting”日志
-    let ok = RwnxCmdMgr::wait_done_until(
-        timeout_ms,
-        || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
-        None,
-        None,
-        None,
-    );
-    ok.map_err(|_| -62)?;
-    let mut cfm_buf = [0u8; 16];
-    let len = with_cmd_mgr(|c| c.take_cfm(token, &mut cfm_buf)).flatten().ok_or(-5)?;
-    let memdata = parse_dbg_mem_read_cfm_with_addr(&cfm_buf[..len], mem_addr).ok_or(-5)?;
-    if let Some((a, d)) = parse_dbg_mem_read_cfm_full(&cfm_buf[..len]) {
-        let order = if d == mem_addr { "swap[memdata][memaddr]" } else { "[memaddr][memdata]" };
-        log::info!(target: "wireless::bsp::sdio", "dbg_mem_read CFM: raw param [0]=0x{:08x} [1]=0x{:08x} requested=0x{:08x} {} -> memdata=0x{:08x} (chip_rev_byte=0x{:02x})",
-            a, d, mem_addr, order, memdata, (memdata >> 16) as u8);
-    }
-    Ok(memdata)
 }
 
 /// 上电/复位后到首次 SDIO 访问前的稳定延时(ms)。
@@ -564,8 +935,9 @@ pub fn aicbsp_minimal_ipc_verify(stable_ms: u64) -> AxResult<()> {
     }
 
     // 与 LicheeRV 一致：首条 SDIO 命令由 bustx 发出（send_msg：读 FLOW_CTRL + 写 WR_FIFO），避免 busrx 先轮询 CMD52 读 BLOCK_CNT 导致超时并占满 inhibit
-    ensure_bustx_thread_started();
-    let token = with_cmd_mgr(|c| c.push(DBG_MEM_READ_CFM)).flatten().ok_or(AxError::BadState)?;
+    ensure_bustx_thread_started().map_err(|_| AxError::ResourceBusy)?;
+    let handle = with_cmd_mgr(|c| c.push_handle(DBG_MEM_READ_CFM, cmd_flags::REQ_CFM)).flatten().ok_or(AxError::BadState)?;
+    let token = handle.token();
     let msg = build_dbg_mem_read_req(CHIP_REV_MEM_ADDR);
     let mut buf = [0u8; 512];
     let len = msg.serialize_8801(&mut buf);
@@ -577,25 +949,26 @@ pub fn aicbsp_minimal_ipc_verify(stable_ms: u64) -> AxResult<()> {
         log::error!(target: "wireless::bsp::sdio", "aicbsp_minimal_ipc_verify: submit_cmd_tx (bustx) failed {}", e);
         return Err(AxError::BadState);
     }
-    ensure_busrx_thread_started();
+    ensure_busrx_thread_started().map_err(|_| AxError::ResourceBusy)?;
     const MINIMAL_VERIFY_CFM_TIMEOUT_MS: u32 = 1500;
     log::info!(target: "wireless::bsp::sdio", "aicbsp_minimal_ipc_verify: request sent, waiting CFM ({}ms), F1 logged every 500ms", MINIMAL_VERIFY_CFM_TIMEOUT_MS);
     axtask::sleep(core::time::Duration::from_millis(100));
     let mut tick = |waited_ms: u32| {
         let mut bc = 0u8;
         with_sdio(|sdio| {
-            bc = sdio.read_byte(0x112).unwrap_or(0xff);
-            let bm = sdio.read_byte(0x102).unwrap_or(0xff);
-            let fc = sdio.read_byte(0x10A).unwrap_or(0xff);
-            log::info!(target: "wireless::bsp::sdio", "minimal_verify @ {}ms: F1 BLOCK_CNT(0x12)=0x{:02x} BYTEMODE_LEN(0x02)=0x{:02x} FLOW_CTRL(0x0A)=0x{:02x}", waited_ms, bc, bm, fc);
+            let (block_cnt_reg, bytemode_len_reg, flow_ctrl_reg) = sdio.f1_status_regs();
+            bc = sdio.readb(block_cnt_reg as u32).unwrap_or(0xff);
+            let bm = sdio.readb(bytemode_len_reg as u32).unwrap_or(0xff);
+            let fc = sdio.readb(flow_ctrl_reg as u32).unwrap_or(0xff);
+            log::info!(target: "wireless::bsp::sdio", "minimal_verify @ {}ms: F1 BLOCK_CNT(0x{:02x})=0x{:02x} BYTEMODE_LEN(0x{:02x})=0x{:02x} FLOW_CTRL(0x{:02x})=0x{:02x}", waited_ms, block_cnt_reg, bc, bytemode_len_reg, bm, flow_ctrl_reg, fc);
         });
         // 与 LicheeRV 一致：有数据时主动收包。先 yield 让 busrx 有机会先取走 FIFO，再主线程收一次，避免锁竞争下只有主线程抢到锁且 recv_pkt 超时
         if bc > 0 {
             axtask::sleep(core::time::Duration::from_millis(1));
-            run_poll_rx_one();
+            run_poll_rx_one(0);
         }
     };
-    let mut do_poll = || run_poll_rx_one();
+    let mut do_poll = || run_poll_rx_one(0);
     let ok = RwnxCmdMgr::wait_done_until(
         MINIMAL_VERIFY_CFM_TIMEOUT_MS,
         || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
@@ -714,27 +1087,16 @@ pub fn aicbsp_sdio_init() -> AxResult<()> {
         AxError::BadState
     })?;
 
-    // 3.2 在 1-bit 下完成 8801 的 F1 配置，避免切 4-bit 后首条 CMD52 超时（inhibit_cmd=1、INT_STS=0）
+    // 3.2 在 1-bit 下完成 8801 的 F1 配置，避免切 4-bit 后首条 CMD52 超时（inhibit_cmd=1、INT_STS=0）；
+    //     block size 属主机侧枚举，F1 寄存器写在 5 之后经 SdioOps 完成（8801 始终 1-bit，顺序不变）
     let pid = aicbsp_current_product_id().ok_or(AxError::BadState)?;
     if pid == ProductId::Aic8801 {
-        use super::types::reg;
-        let f1_base = 0x100u32;
         host.set_block_size(1, 512).map_err(|e| {
             log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: set_block_size(1, 512) failed (1-bit) {}", e);
             AxError::BadState
         })?;
         log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 F1 block size=512 in 1-bit (align LicheeRV sdio_set_block_size(func))");
         sync::delay_spin_us(100);
-        host.write_byte(f1_base + u32::from(reg::REGISTER_BLOCK), 1).map_err(|e| {
-            log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: F1 REGISTER_BLOCK(0x0B)=1 failed {}", e);
-            AxError::BadState
-        })?;
-        host.write_byte(f1_base + u32::from(reg::BYTEMODE_ENABLE), 1).map_err(|e| {
-            log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: F1 BYTEMODE_ENABLE(0x11)=1 failed {}", e);
-            AxError::BadState
-        })?;
-        // F1 INTR_CONFIG(0x04)=0x07 与 LicheeRV 一致在 bus_start 中、在 claim_irq 之后写入，见 driver_fw_init 内 enable_8801_sdio_intr
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 F1 0x0B=1 0x11=1 in 1-bit (0x04=0x07 deferred to driver_fw_init after busrx+IRQ)");
     }
 
     // 3.5 与 LicheeRV 差异：本 SoC 上 4-bit 下 CMD52 均超时（INT_STS=0 inhibit_cmd=1），故 8801 在 init 内不切 4-bit，首包 IPC（FLOW_CTRL+WR_FIFO）在 1-bit 下完成；非 8801 仍按 Linux 顺序切 4-bit
@@ -746,8 +1108,17 @@ pub fn aicbsp_sdio_init() -> AxResult<()> {
         log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 — keep 1-bit for first IPC (4-bit CMD52 timeout on this SoC), LicheeRV uses 4-bit after MMC set_ios)");
     }
 
-    // 4. 启用 SDIO Function 2 并等待 IO_READY（仅非 8801：LicheeRV 对 8801 仅用 F1，不 enable F2，避免芯片侧行为差异）
-    if pid != ProductId::Aic8801 {
+    // 3.6 D80/D80X2（V3）：对应 LicheeRV aicwf_sdiov3_func_init，4-bit 下写 F0 扩展寄存器、F1 block size 与 BYTEMODE_ENABLE
+    let is_v3 = matches!(pid, ProductId::Aic8800D80 | ProductId::Aic8800D80X2);
+    if is_v3 {
+        sdiov3_func_init(&host).map_err(|e| {
+            log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: sdiov3_func_init failed {}", e);
+            AxError::BadState
+        })?;
+    }
+
+    // 4. 启用 SDIO Function 2 并等待 IO_READY（仅 V1/V2 非 8801：LicheeRV 对 8801 仅用 F1，V3 无 func_msg，均不 enable F2）
+    if pid != ProductId::Aic8801 && !is_v3 {
         let io_enable = host.read_byte(0x02).map_err(|e| {
             log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: read CCCR 0x02 failed {}", e);
             AxError::BadState
@@ -777,13 +1148,29 @@ pub fn aicbsp_sdio_init() -> AxResult<()> {
             AxError::BadState
         })?;
     } else {
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 — skip F2 enable (LicheeRV 8801 only F1)");
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: {:?} — skip F2 enable (LicheeRV 8801/V3 only F1)", pid);
     }
 
     // 5. 按当前 product_id 构造 Aic8800Sdio 与 cmd_mgr，存入静态供 driver_fw_init 使用
     let sdio = Aic8800Sdio::new(host, pid);
     SDIO_DEVICE.lock().replace(sdio);
 
+    // 5.0 8801 F1 REGISTER_BLOCK/BYTEMODE_ENABLE：此后对芯片的寄存器访问都经 SdioOps（板上录制时记入 trace）
+    if pid == ProductId::Aic8801 {
+        use super::types::reg;
+        let r = with_sdio_ops(|ops| {
+            ops.writeb(u32::from(reg::REGISTER_BLOCK), 1).map_err(|e| ("REGISTER_BLOCK(0x0B)", e))?;
+            ops.writeb(u32::from(reg::BYTEMODE_ENABLE), 1).map_err(|e| ("BYTEMODE_ENABLE(0x11)", e))
+        });
+        if let Some(Err((name, e))) = r {
+            log::error!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: F1 {}=1 failed {}", name, e);
+            SDIO_DEVICE.lock().take();
+            return Err(AxError::BadState);
+        }
+        // F1 INTR_CONFIG(0x04)=0x07 与 LicheeRV 一致在 bus_start 中、在 claim_irq 之后写入，见 7
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 F1 0x0B=1 0x11=1 in 1-bit (0x04=0x07 deferred to bus_start)");
+    }
+
     // 5.1 与 LicheeRV 一致：枚举到卡后按 id_table 调用 probe。LicheeRV 在 aicbsp_sdio_init 内 sdio_register_driver，故此处先确保已注册（minimal_ipc_verify 不经过 aicbsp_init 时也成立）
     let _ = super::mmc_impl::register_aicbsp_sdio_driver();
     if let Some(ref sdio) = *SDIO_DEVICE.lock() {
@@ -793,27 +1180,55 @@ pub fn aicbsp_sdio_init() -> AxResult<()> {
         }
     }
 
-    CMD_MGR.lock().replace(RwnxCmdMgr::new());
+    CMD_MGR.lock().replace(RwnxCmdMgr::with_max_queued(CMD_QUEUE_DEPTH.load(Ordering::Relaxed)));
 
     // 6. 与 LicheeRV 一致：bustx 在 bus_init 里启动，首条 SDIO 命令由 bustx 发出（send_msg），避免 busrx 先轮询 CMD52 导致超时
     //    busrx 由调用方在“需要收包前”启动：minimal_verify 在 submit 后、aicbsp_driver_fw_init 在发首包前
-    ensure_bustx_thread_started();
+    ensure_bustx_thread_started().map_err(|_| AxError::ResourceBusy)?;
 
     // 7. 与 LicheeRV 一致：bus_start（claim_irq + F1 INTR_CONFIG=0x07）在 probe 完成后、首包 IPC 前执行。
     //    LicheeRV 在 aicwf_sdio_probe → bus_init → aicwf_bus_start 中完成；若此处不做，设备可能不对 MEM_WRITE 回 CFM（BLOCK_CNT 恒 0）。见 BLOCK_CNT流程与LicheeRV对照.md
     if pid == ProductId::Aic8801 {
         crate::sdio_irq::ensure_sdio_irq_registered();
         let f1_intr = 0x100u32 + u32::from(super::types::reg::INTR_CONFIG);
-        if let Some(Err(e)) = with_sdio(|sdio| sdio.write_byte(f1_intr, 0x07)) {
+        if let Some(Err(e)) = with_sdio_ops(|sdio| sdio.write_byte(f1_intr, 0x07)) {
             log::warn!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 F1 INTR_CONFIG(0x04)=0x07 failed {} (bus_start)", e);
         } else {
             log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: Aic8801 bus_start (claim_irq + F1 0x04=0x07) done, align LicheeRV");
         }
+    } else if is_v3 {
+        // LicheeRV aicwf_sdio_bus_start V3 分支：sdio_f0_writeb(0x07, 0x04) + F1 INTR_ENABLE_REG_V3=0x07
+        crate::sdio_irq::ensure_sdio_irq_registered();
+        let r = with_sdio_ops(|sdio| {
+            sdio.write_byte(u32::from(mmc::sdio_f0_reg::SDIO_F0_04), 0x07)?;
+            sdio.writeb(u32::from(super::types::reg_v3::INTR_ENABLE), 0x07)
+        });
+        if let Some(Err(e)) = r {
+            log::warn!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: {:?} bus_start (F0 0x04 / F1 INTR_ENABLE_V3) failed {}", pid, e);
+        } else {
+            log::info!(target: "wireless::bsp::sdio", "aicbsp_sdio_init: {:?} bus_start (claim_irq + F0 0x04=0x07 + F1 0x00=0x07) done", pid);
+        }
     }
 
     Ok(())
 }
 
+/// D80/D80X2 F1 初始化，对应 LicheeRV aicwf_sdiov3_func_init：
+/// F0 0xF2=0x7F、0xF0=0x01|SDIOCLK_FREE_RUNNING、0xF8=0x00、0xF1=0x20，延时 1ms 后设 F1 block size=512 并开 BYTEMODE_ENABLE_V3
+fn sdiov3_func_init(host: &Aic8800SdioHost) -> Result<(), i32> {
+    use mmc::sdio_f0_reg::{SDIO_F0_F0, SDIO_F0_F1, SDIO_F0_F2, SDIO_F0_F8};
+    const SDIOCLK_FREE_RUNNING: u8 = 1 << 6;
+    host.write_byte(u32::from(SDIO_F0_F2), 0x7F)?;
+    host.write_byte(u32::from(SDIO_F0_F0), 0x01 | SDIOCLK_FREE_RUNNING)?;
+    host.write_byte(u32::from(SDIO_F0_F8), 0x00)?;
+    host.write_byte(u32::from(SDIO_F0_F1), 0x20)?;
+    sync::delay_spin_ms(1);
+    host.set_block_size(1, 512)?;
+    host.write_byte_at_func(1, u32::from(super::types::reg_v3::BYTEMODE_ENABLE), 1)?;
+    log::info!(target: "wireless::bsp::sdio", "sdiov3_func_init: F0 0xF2/0xF0/0xF8/0xF1 set, F1 block size=512, BYTEMODE_ENABLE_V3=1");
+    Ok(())
+}
+
 /// **aicbsp_sdio_probe** — SDIO 设备探测成功后的收尾（BSP 侧）
 ///
 /// **作用**：在“SDIO 设备已被发现并完成 chipmatch、func/bus 初始化、cmd_mgr 初始化”之后调用，
@@ -878,136 +1293,283 @@ const RD_PATCH_ADDR_8801: u32 = RAM_FMAC_FW_ADDR + 0x0180;
 const PATCH_START_ADDR_8801: u32 = 0x1e6000;
 const PATCH_ADDR_REG_8801: u32 = 0x1e5318;
 const PATCH_NUM_REG_8801: u32 = 0x1e531c;
-/// patch_tbl 与 LicheeRV 一致：!CONFIG_LINK_DET_5G 一项 + CONFIG_MCU_MESSAGE 两项
+/// patch_tbl 与 LicheeRV 一致：!CONFIG_LINK_DET_5G 一项 + CONFIG_MCU_MESSAGE 两项，各对为 (相对 config_base 的偏移, 值)
 const PATCH_TBL_8801: &[(u32, u32)] = &[
     (0x0104, 0x0000_0000),   // link_det_5g
     (0x004c, 0x0000_004B),   // pkt_cnt_1724=0x4B
     (0x0050, 0x0011_FC00),   // ipc_base_addr
 ];
 
-/// 主线程直接发 DBG_MEM_READ_REQ + wait_done_until，不经过 bustx，供 init 路径避免与 busrx 争用（与 LicheeRV 发送时总线独占一致）
-fn send_dbg_mem_read_direct(mem_addr: u32, timeout_ms: u32, product_id: ProductId) -> Result<u32, i32> {
-    let token = with_cmd_mgr(|c| c.push(DBG_MEM_READ_CFM)).flatten().ok_or(-12)?;
-    let msg = build_dbg_mem_read_req(mem_addr);
-    let mut buf = [0u8; 512];
-    let len = if product_id == ProductId::Aic8801 {
-        msg.serialize_8801(&mut buf)
-    } else {
-        msg.serialize(&mut buf)
-    };
-    let send_len = if product_id == ProductId::Aic8801 {
-        ipc_send_len_8801(len)
-    } else {
-        len
-    };
-    if send_len > len {
-        buf[len..send_len].fill(0);
+/// 8801 aicwifi_patch_config：读 config_base，写 patch 表地址/数量与各 (config_base + 偏移, 值) 对
+fn aicwifi_patch_config_8801(io: &mut dyn DbgMemIo) -> Result<(), i32> {
+    let config_base = io.mem_read(RD_PATCH_ADDR_8801)?;
+    io.mem_write(PATCH_ADDR_REG_8801, PATCH_START_ADDR_8801)?;
+    let patch_num = (PATCH_TBL_8801.len() * 2) as u32; // sizeof(patch_tbl)/4 in C
+    io.mem_write(PATCH_NUM_REG_8801, patch_num)?;
+    for (cnt, &(off, val)) in PATCH_TBL_8801.iter().enumerate() {
+        let addr = PATCH_START_ADDR_8801 + (cnt as u32) * 8;
+        io.mem_write(addr, off + config_base)?;
+        io.mem_write(addr + 4, val)?;
     }
-    match with_sdio(|sdio| sdio.send_msg(&buf[..send_len], send_len)) {
-        Some(Ok(_)) => {}
-        Some(Err(e)) => return Err(e),
-        None => return Err(-5),
+    Ok(())
+}
+
+/// 8801 aicwifi_sys_config：写 syscfg_tbl_masked 与 rf_tbl_masked（DBG_MEM_MASK_WRITE）
+fn aicwifi_sys_config_8801(io: &mut dyn DbgMemIo) -> Result<(), i32> {
+    for &(addr, mask, data) in SYSCFG_TBL_MASKED_8801.iter().chain(RF_TBL_MASKED_8801) {
+        io.mem_mask_write(addr, mask, data)?;
     }
-    axtask::sleep(core::time::Duration::from_millis(100));
-    RwnxCmdMgr::wait_done_until(
-        timeout_ms,
-        || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
-        None,
-        None,
-        None,
-    )
-    .map_err(|_| -62)?;
-    let mut cfm_buf = [0u8; 16];
-    let len = with_cmd_mgr(|c| c.take_cfm(token, &mut cfm_buf)).flatten().ok_or(-5)?;
-    parse_dbg_mem_read_cfm_with_addr(&cfm_buf[..len], mem_addr).ok_or(-5)
+    Ok(())
 }
 
-/// 主线程直接发 DBG_MEM_WRITE + wait_done_until，不持 CMD_MGR，供 init 路径避免与 busrx 争用
-fn send_dbg_mem_write_direct(addr: u32, data: u32, timeout_ms: u32, product_id: ProductId) -> Result<(), i32> {
-    let msg = build_dbg_mem_write_req(addr, data);
-    let token = with_cmd_mgr(|mgr| mgr.push(DBG_MEM_WRITE_CFM)).flatten().ok_or(-12)?;
-    let mut buf = [0u8; 512];
-    let len = if product_id == ProductId::Aic8801 {
-        msg.serialize_8801(&mut buf)
-    } else {
-        msg.serialize(&mut buf)
-    };
-    let send_len = if product_id == ProductId::Aic8801 {
-        ipc_send_len_8801(len)
-    } else {
-        len
-    };
-    if send_len > len {
-        buf[len..send_len].fill(0);
+/// 回读校验后重传不符块的最大轮数
+const FW_VERIFY_MAX_RETRY: usize = 2;
+/// 回读校验时每次 DBG_MEM_READ 等 CFM 的超时
+const FW_VERIFY_READ_TIMEOUT_MS: u32 = 500;
+
+/// 上传后按 AicBspFeature::fw_verify 回读校验（read_fn 读一个字）；不符的块重传后再校验，超过 FW_VERIFY_MAX_RETRY 轮仍不符返回 -EIO
+/// 首轮按 block_size/window 窗口化上传并记入 aicbsp_fw_upload_stats；重传按 1KB 块逐块进行
+#[allow(clippy::too_many_arguments)]
+fn fw_upload_verified(
+    name: &str,
+    mem_addr: u32,
+    data: &[u8],
+    block_size: usize,
+    window: usize,
+    mode: FwVerifyMode,
+    tx_fn: &mut dyn FnMut(&LmacMsg) -> Result<(), i32>,
+    push_fn: &mut dyn FnMut() -> Option<usize>,
+    wait_fn: &mut dyn FnMut(usize) -> Result<(), i32>,
+    read_fn: &mut dyn FnMut(u32) -> Result<u32, i32>,
+) -> Result<(), i32> {
+    let stats = fw_upload_blocks_windowed(&mut *tx_fn, mem_addr, data, block_size, window, &mut *push_fn, &mut *wait_fn)?;
+    fw_upload_stats_record(stats);
+    if mode == FwVerifyMode::Off {
+        return Ok(());
     }
-    match with_sdio(|sdio| sdio.send_msg(&buf[..send_len], send_len)) {
-        Some(Ok(_)) => {}
-        Some(Err(e)) => return Err(e),
-        None => return Err(-5),
+    let mut retry = 0;
+    loop {
+        let bad = fw_verify_blocks(mem_addr, data, mode, &mut *read_fn)?;
+        if bad.is_empty() {
+            log::info!(target: "wireless::bsp::sdio", "fw_verify: {} at 0x{:08x} OK ({:?})", name, mem_addr, mode);
+            return Ok(());
+        }
+        if retry == FW_VERIFY_MAX_RETRY {
+            for m in &bad {
+                log::error!(target: "wireless::bsp::sdio", "fw_verify: {} block 0x{:08x} (+{} len {}) still mismatched after {} retries",
+                    name, m.addr, m.offset, m.len, retry);
+            }
+            return Err(-5);
+        }
+        retry += 1;
+        log::warn!(target: "wireless::bsp::sdio", "fw_verify: {} has {} bad blocks, re-upload (retry {}/{})",
+            name, bad.len(), retry, FW_VERIFY_MAX_RETRY);
+        for m in &bad {
+            fw_upload_blocks(&mut *tx_fn, m.addr, &data[m.offset..m.offset + m.len], &mut *push_fn, &mut *wait_fn)?;
+        }
     }
+}
+
+/// 经 tx_fn 发一条 DBG_* 请求，wait_fn 等到 cfm_id 完成后取 CFM；返回 CFM param 长度
+fn send_dbg_req_busrx(
+    tx_fn: &mut dyn FnMut(&LmacMsg) -> Result<(), i32>,
+    wait_fn: &mut dyn FnMut(usize) -> Result<(), i32>,
+    msg: &LmacMsg,
+    cfm_id: u16,
+    cfm_buf: &mut [u8],
+) -> Result<usize, i32> {
+    // 句柄在返回时 drop：发送失败、超时或 CFM 取走后都会释放 slot
+    let handle = with_cmd_mgr(|c| c.push_handle(cfm_id, cmd_flags::REQ_CFM)).flatten().ok_or(-12)?;
+    let token = handle.token();
+    tx_fn(msg)?;
+    wait_fn(token)?;
+    with_cmd_mgr(|c| c.take_cfm(token, cfm_buf)).flatten().ok_or(-5)
+}
+
+/// 等 token 完成：仅检查与收包时短暂持 CMD_MGR，wait_done_until 内 1ms sleep 时不持锁，busrx 可 poll_rx_one → on_cfm
+fn wait_token(
+    token: usize,
+    timeout_ms: u32,
+    poll: &mut dyn FnMut(),
+    tick: Option<&mut dyn FnMut(u32)>,
+    log_every_ms: Option<u32>,
+) -> Result<(), i32> {
     RwnxCmdMgr::wait_done_until(
         timeout_ms,
         || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
-        None,
-        None,
-        None,
+        tick,
+        Some(poll),
+        log_every_ms,
     )
     .map_err(|_| -62)
 }
 
-/// init 路径：不持 CMD_MGR，用 send_dbg_mem_read_direct + send_dbg_mem_write_direct（主线程直接发，不经过 bustx），避免与 busrx 争用 SDIO
-fn aicwifi_patch_config_8801_init(product_id: ProductId, timeout_ms: u32) -> Result<(), i32> {
-    let config_base = send_dbg_mem_read_direct(RD_PATCH_ADDR_8801, timeout_ms, product_id)?;
-    send_dbg_mem_write_direct(PATCH_ADDR_REG_8801, PATCH_START_ADDR_8801, timeout_ms, product_id)?;
-    let patch_num = (PATCH_TBL_8801.len() * 2) as u32;
-    send_dbg_mem_write_direct(PATCH_NUM_REG_8801, patch_num, timeout_ms, product_id)?;
-    for (cnt, &(off, val)) in PATCH_TBL_8801.iter().enumerate() {
-        let addr = PATCH_START_ADDR_8801 + (cnt as u32) * 8;
-        send_dbg_mem_write_direct(addr, off + config_base, timeout_ms, product_id)?;
-        send_dbg_mem_write_direct(addr + 4, val, timeout_ms, product_id)?;
+/// aicbsp_driver_fw_init 的收发方式；两种方式的 cmd_mgr 都是全局 CMD_MGR，只在 push、检查完成与收包时短暂持锁
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FwInitBus {
+    /// 板上：bustx/busrx 已启动。8801 的 A2E 交 bustx 发送（bustx 内 flow_ctrl + send_pkt，避免与 busrx 争用导致芯片不回 CFM），
+    /// 其余芯片直接 send_msg；CFM 由 busrx 收，等待时本线程也经 SDIO_DEVICE 轮询一次
+    Threads,
+    /// 单线程：A2E 直接 send_msg，等 CFM 时由本线程在同一 SdioOps 上 poll_rx_one（OPS_OVERRIDE：软件设备或板上录制）
+    #[cfg(any(test, feature = "sim", feature = "record"))]
+    Poll,
+}
+
+/// 每块等 DBG_MEM_BLOCK_WRITE_CFM 的超时
+const BLOCK_WRITE_CFM_TIMEOUT_MS: u32 = 500;
+/// 单条 DBG_* 请求等 CFM 的超时
+const DBG_CMD_TIMEOUT_MS: u32 = 2000;
+/// Threads 方式下等 CFM 时打 F1 BLOCK_CNT/FLOW_CTRL 的间隔，便于确认设备是否回包
+const DBG_CMD_LOG_EVERY_MS: u32 = 500;
+
+/// 固件初始化用 DbgMemIo：单条命令 push 句柄 → 发送 → 等 CFM，固件上传走 fw_upload_verified（窗口化 + 回读校验）
+struct SdioDbgMemIo<'a> {
+    sdio: &'a dyn SdioOps,
+    bus: FwInitBus,
+    product_id: ProductId,
+    block_size: usize,
+    window: usize,
+    fw_verify: FwVerifyMode,
+    mem_write_logged: core::cell::Cell<bool>,
+    block_write_logged: core::cell::Cell<bool>,
+}
+
+impl<'a> SdioDbgMemIo<'a> {
+    fn new(sdio: &'a dyn SdioOps, bus: FwInitBus, product_id: ProductId) -> Self {
+        Self {
+            sdio,
+            bus,
+            product_id,
+            // 8801 与 LicheeRV 一致 1KB 块逐块等 CFM，DC/DW/D80 大块流水线；窗口不超过 cmd_mgr 槽位数
+            block_size: fw_upload_block_size(product_id),
+            window: fw_upload_window(product_id).min(with_cmd_mgr(|c| c.max_queued()).unwrap_or(1)),
+            fw_verify: crate::aicbsp_get_feature().fw_verify,
+            mem_write_logged: core::cell::Cell::new(false),
+            block_write_logged: core::cell::Cell::new(false),
+        }
+    }
+
+    /// 序列化并发送一条 A2E
+    fn tx(&self, msg: &LmacMsg) -> Result<(), i32> {
+        let mut buf = [0u8; PENDING_CMD_TX_CAP];
+        let (len, send_len) = ipc_serialize_lmac_msg(self.product_id, msg, &mut buf)?;
+        // 与 LicheeRV rwnx_set_cmd_tx 对齐：8801 首包 DBG_MEM_WRITE / DBG_MEM_BLOCK_WRITE 打前 24B 便于逐字节对照
+        let logged = match msg.header.id {
+            DBG_MEM_WRITE_REQ => Some(("DBG_MEM_WRITE_REQ", &self.mem_write_logged)),
+            DBG_MEM_BLOCK_WRITE_REQ => Some(("DBG_MEM_BLOCK_WRITE_REQ", &self.block_write_logged)),
+            _ => None,
+        };
+        if let Some((name, flag)) = logged {
+            if self.product_id == ProductId::Aic8801 && len >= 24 && !flag.replace(true) {
+                log::warn!(target: "wireless::bsp::sdio", "{} first 24B (LicheeRV rwnx_set_cmd_tx): {}", name, HexPrefix(&buf, 24));
+            }
+        }
+        if self.bus == FwInitBus::Threads && self.product_id == ProductId::Aic8801 {
+            // 主线程只传 payload_len，bustx 内 aicwf_sdio_tx_msg_pad 做 align+TAIL+512；IPC trace 由 submit_cmd_tx_and_wait_tx_done 记录
+            return submit_cmd_tx_and_wait_tx_done(&buf[..len], len).inspect_err(|e| {
+                log::warn!(target: "wireless::bsp::sdio", "fw_init tx: submit_cmd_tx (bustx) err={} (e.g. -110=FLOW_CTRL)", e);
+            });
+        }
+        ipc_trace_record_a2e_buf(&buf[..len], ipc_lmac_hdr_offset(self.product_id));
+        self.sdio.send_msg(&buf[..send_len], send_len).map(|_| ()).inspect_err(|e| {
+            log::warn!(target: "wireless::bsp::sdio", "fw_init tx: send_msg err={} (e.g. -110=FLOW_CTRL/-5=EIO)", e);
+        })
+    }
+
+    /// 等 CFM 期间收一包：Threads 经 SDIO_DEVICE（与 busrx 互斥），Poll 在 self.sdio 上
+    fn poll(&self) {
+        with_cmd_mgr(|m| match self.bus {
+            FwInitBus::Threads => {
+                with_sdio_ops(|s| poll_rx_one(s, m));
+            }
+            #[cfg(any(test, feature = "sim", feature = "record"))]
+            FwInitBus::Poll => {
+                let _ = poll_rx_one(self.sdio, m);
+            }
+        });
+    }
+
+    fn wait(&self, token: usize, timeout_ms: u32) -> Result<(), i32> {
+        let mut poll = || self.poll();
+        match self.bus {
+            FwInitBus::Threads => {
+                let mut tick = |waited_ms: u32| log_f1_block_cnt_flow_ctrl(waited_ms);
+                wait_token(token, timeout_ms, &mut poll, Some(&mut tick), Some(DBG_CMD_LOG_EVERY_MS))
+            }
+            #[cfg(any(test, feature = "sim", feature = "record"))]
+            FwInitBus::Poll => wait_token(token, timeout_ms, &mut poll, None, None),
+        }
+    }
+
+    fn send(&self, msg: &LmacMsg, cfm_id: u16, timeout_ms: u32, cfm_buf: &mut [u8]) -> Result<usize, i32> {
+        send_dbg_req_busrx(&mut |m| self.tx(m), &mut |t| self.wait(t, timeout_ms), msg, cfm_id, cfm_buf)
+    }
+
+    fn read_word(&self, addr: u32, timeout_ms: u32) -> Result<u32, i32> {
+        let mut cfm_buf = [0u8; 16];
+        let len = self.send(&build_dbg_mem_read_req(addr), DBG_MEM_READ_CFM, timeout_ms, &mut cfm_buf)?;
+        parse_dbg_mem_read_cfm_with_addr(&cfm_buf[..len], addr).ok_or(-5)
     }
-    Ok(())
 }
 
-fn aicwifi_patch_config_8801(
-    cmd_mgr: &mut RwnxCmdMgr,
-    tx_fn: &mut dyn FnMut(&LmacMsg) -> Result<(), i32>,
-    timeout_ms: u32,
-    poll_fn: &mut dyn FnMut(&mut RwnxCmdMgr),
-) -> Result<(), i32> {
-    let config_base = send_dbg_mem_read(
-        cmd_mgr,
-        &mut *tx_fn,
-        RD_PATCH_ADDR_8801,
-        timeout_ms,
-        poll_fn,
-        None,
-    )?;
-    send_dbg_mem_write(cmd_mgr, &mut *tx_fn, PATCH_ADDR_REG_8801, PATCH_START_ADDR_8801, timeout_ms, poll_fn, None)?;
-    let patch_num = (PATCH_TBL_8801.len() * 2) as u32; // sizeof(patch_tbl)/4 in C
-    send_dbg_mem_write(cmd_mgr, &mut *tx_fn, PATCH_NUM_REG_8801, patch_num, timeout_ms, poll_fn, None)?;
-    for (cnt, &(off, val)) in PATCH_TBL_8801.iter().enumerate() {
-        let addr = PATCH_START_ADDR_8801 + (cnt as u32) * 8;
-        send_dbg_mem_write(cmd_mgr, &mut *tx_fn, addr, off + config_base, timeout_ms, poll_fn, None)?;
-        send_dbg_mem_write(cmd_mgr, &mut *tx_fn, addr + 4, val, timeout_ms, poll_fn, None)?;
+impl PatchTableWriter for SdioDbgMemIo<'_> {
+    fn mem_write(&mut self, addr: u32, data: u32) -> Result<(), i32> {
+        self.send(&build_dbg_mem_write_req(addr, data), DBG_MEM_WRITE_CFM, DBG_CMD_TIMEOUT_MS, &mut [0u8; 16]).map(|_| ())
+    }
+
+    fn mem_mask_write(&mut self, addr: u32, mask: u32, data: u32) -> Result<(), i32> {
+        self.send(&build_dbg_mem_mask_write_req(addr, mask, data), DBG_MEM_MASK_WRITE_CFM, DBG_CMD_TIMEOUT_MS, &mut [0u8; 16])
+            .map(|_| ())
     }
-    Ok(())
 }
 
-/// 8801 aicwifi_sys_config：写 syscfg_tbl_masked 与 rf_tbl_masked（DBG_MEM_MASK_WRITE）
-fn aicwifi_sys_config_8801(
-    cmd_mgr: &mut RwnxCmdMgr,
-    tx_fn: &mut dyn FnMut(&LmacMsg) -> Result<(), i32>,
-    timeout_ms: u32,
-    poll_fn: &mut dyn FnMut(&mut RwnxCmdMgr),
-) -> Result<(), i32> {
-    for &(addr, mask, data) in SYSCFG_TBL_MASKED_8801 {
-        send_dbg_mem_mask_write(cmd_mgr, &mut *tx_fn, addr, mask, data, timeout_ms, poll_fn)?;
+impl DbgMemIo for SdioDbgMemIo<'_> {
+    fn mem_read(&mut self, addr: u32) -> Result<u32, i32> {
+        self.read_word(addr, DBG_CMD_TIMEOUT_MS)
     }
-    for &(addr, mask, data) in RF_TBL_MASKED_8801 {
-        send_dbg_mem_mask_write(cmd_mgr, &mut *tx_fn, addr, mask, data, timeout_ms, poll_fn)?;
+
+    /// 固件缺失返回 -ENOENT，完整性检查失败返回 -EBADMSG
+    fn upload(&mut self, name: &str, addr: u32) -> Result<(), i32> {
+        let data = get_firmware_by_name(name).ok_or(-2)?;
+        check_firmware_image(name, &data, crate::aicbsp_get_feature().fw_integrity).map_err(|e| {
+            log::error!(target: "wireless::bsp::sdio", "fw upload: {}", e);
+            -74
+        })?;
+        log::info!(target: "wireless::bsp::sdio", "fw upload: {} ({} bytes) -> 0x{:08x}", name, data.len(), addr);
+        let this = &*self;
+        let mut push_fn = || with_cmd_mgr(|m| m.push(DBG_MEM_BLOCK_WRITE_CFM, cmd_flags::REQ_CFM)).flatten();
+        let mut wait_fn = |token: usize| {
+            this.wait(token, BLOCK_WRITE_CFM_TIMEOUT_MS)?;
+            // wait 后 take_cfm 释放 slot，否则多块上传会占满 cmd_mgr
+            let _ = with_cmd_mgr(|c| c.take_cfm(token, &mut [0u8; 16]));
+            Ok(())
+        };
+        fw_upload_verified(
+            name,
+            addr,
+            &data,
+            this.block_size,
+            this.window,
+            this.fw_verify,
+            &mut |m| this.tx(m),
+            &mut push_fn,
+            &mut wait_fn,
+            &mut |a| this.read_word(a, FW_VERIFY_READ_TIMEOUT_MS),
+        )
+    }
+
+    fn firmware(&mut self, name: &str) -> Option<FirmwareBlob> {
+        get_firmware_by_name(name)
+    }
+
+    fn start_app(&mut self, boot_addr: u32, boot_type: u32) -> Result<(), i32> {
+        log::info!(target: "wireless::bsp::sdio", "fw_start_app addr=0x{:08x} type={}", boot_addr, boot_type);
+        self.send(&build_dbg_start_app_req(boot_addr, boot_type), DBG_START_APP_CFM, DBG_CMD_TIMEOUT_MS, &mut [0u8; 16]).map(|_| ())
+    }
+
+    fn lmac_req(&mut self, msg: &LmacMsg, cfm_id: u16) -> Result<(), i32> {
+        self.send(msg, cfm_id, DBG_CMD_TIMEOUT_MS, &mut [0u8; 16]).map(|_| ())
     }
-    Ok(())
 }
 
 /// **aicbsp_driver_fw_init** — 固件与芯片初始化（读 chip rev、选固件表、固件上传、patch/sys 配置、START_APP）
@@ -1019,21 +1581,38 @@ fn aicwifi_sys_config_8801(
 /// 设置 `aicbsp_firmware_list`；3）8801 先 `aicbsp_system_config(sdiodev)`（写 aicbsp_syscfg_tbl）；4）可选 `aicbt_init`；
 /// 5）`aicwifi_init(sdiodev)`（固件上传、patch、aicwifi_sys_config、start_from_bootrom）。本函数不调用 `rwnx_cfg80211_init`。
 ///
-/// **本实现**：读 chip_rev → 选固件表 → 8801 **aicbsp_system_config** → 按名取固件（本地/注册表）
-/// → 3a wl_fw 上传 → 3b patch 上传 → 3c aicwifi_patch_config → 4 aicwifi_sys_config → 5 fw_start_app。
-/// 固件由 `get_firmware_by_name` 从本地（embed）或 `set_wifi_firmware` 注册表提供；phy_cfg 在 FDRV 固件就绪后按 ini 应用。
+/// **本实现**：启动 bustx/busrx（8801 另注册 IRQ 并写 F1 INTR_CONFIG），再以 `FwInitBus::Threads` 在 SDIO_DEVICE 上执行
+/// `driver_fw_init_on`；挂有软件设备（`sim` feature）时改以 `FwInitBus::Poll` 在该设备上执行。失败映射：RF 测试固件缺失 → NotFound，固件完整性检查失败 → InvalidData，其余 → BadState。
 pub fn aicbsp_driver_fw_init(info: &mut AicBspInfo) -> AxResult<()> {
     let product_id = aicbsp_current_product_id().ok_or(AxError::BadState)?;
+    let to_ax = |e: i32| match e {
+        -2 => AxError::NotFound,
+        -74 => AxError::InvalidData,
+        _ => AxError::BadState,
+    };
+
+    // 软件设备与板上录制：同一流程以 FwInitBus::Poll 单线程收发（录制的 trace 顺序因此确定）；
+    // 板上录制完成后照常起 busrx 供 FDRV 收包
+    #[cfg(any(test, feature = "sim", feature = "record"))]
+    {
+        let ops = OPS_OVERRIDE.lock().clone();
+        if let Some(ops) = ops {
+            driver_fw_init_on(&*ops, FwInitBus::Poll, product_id, info).map_err(to_ax)?;
+            if with_sdio(|_| ()).is_some() {
+                ensure_busrx_thread_started().map_err(|_| AxError::ResourceBusy)?;
+            }
+            return Ok(());
+        }
+    }
 
     // 与 LicheeRV 一致：driver_fw_init 在 aicbsp_sdio_init 之后调用，此时 SDIO_DEVICE 已设置；先检查避免持锁后 bustx 拿不到
     if with_sdio(|_| ()).is_none() {
         log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: SDIO_DEVICE not set (probe not done?)");
         return Err(AxError::BadState);
     }
-
     // 与 LicheeRV 对齐：先启动 bustx/busrx 线程，再发 IPC
-    ensure_bustx_thread_started();
-    ensure_busrx_thread_started();
+    ensure_bustx_thread_started().map_err(|_| AxError::ResourceBusy)?;
+    ensure_busrx_thread_started().map_err(|_| AxError::ResourceBusy)?;
     // 8801：与 LicheeRV aicwf_sdio_bus_start 顺序一致 — 先 claim_irq 再写 F1 INTR_CONFIG(0x04)=0x07，避免中断已使能但 handler 未注册
     if product_id == ProductId::Aic8801 {
         crate::sdio_irq::ensure_sdio_irq_registered();
@@ -1048,291 +1627,162 @@ pub fn aicbsp_driver_fw_init(info: &mut AicBspInfo) -> AxResult<()> {
     const POST_BUS_INIT_DELAY_MS: u64 = 200;
     axtask::sleep(core::time::Duration::from_millis(POST_BUS_INIT_DELAY_MS));
 
-    const CMD_TIMEOUT_MS: u32 = 2000;
-
-    // 1. 读 0x40500000 得 chip_rev（多线程：由 busrx 线程收 CFM，本线程仅 wait_done_until）。与 LicheeRV 一致：读失败则返回错误，不使用默认 chip_rev
-    let (chip_rev_raw, is_chip_id_h) = match (if product_id == ProductId::Aic8801 {
-        let mut after_delay = || {
-            with_sdio(|sdio| {
-                let bm = sdio.read_byte(0x102).unwrap_or(0xff);
-                let bc = sdio.read_byte(0x112).unwrap_or(0xff);
-                let fc = sdio.read_byte(0x10A).unwrap_or(0xff);
-                log::info!(target: "wireless::bsp::sdio", "dbg_mem_read: after 100ms F1 BLOCK_CNT(0x12)=0x{:02x} BYTEMODE_LEN(0x02)=0x{:02x} FLOW_CTRL(0x0A)=0x{:02x}", bc, bm, fc);
-            });
-        };
-        send_dbg_mem_read_busrx(CHIP_REV_MEM_ADDR, CMD_TIMEOUT_MS, Some(&mut after_delay))
-    } else {
-        send_dbg_mem_read_busrx(CHIP_REV_MEM_ADDR, CMD_TIMEOUT_MS, None)
-    }) {
-        Ok(memdata) => match product_id {
-            // 8801：与 LicheeRV aic_bsp_driver.c:2019 一致，无掩码 chip_rev = (u8)(memdata >> 16)
-            // 8800DC/D80：LicheeRV 用 (memdata>>16)&0x3F / is_chip_id_h=(memdata>>16)&0xC0
-            ProductId::Aic8801 => {
-                info.chip_rev = (memdata >> 16) as u8;
-                (info.chip_rev, false)
-            }
-            ProductId::Aic8800Dc | ProductId::Aic8800Dw => {
-                info.chip_rev = ((memdata >> 16) & 0x3F) as u8;
-                let is_h = ((memdata >> 16) & 0xC0) == 0xC0;
-                (info.chip_rev, is_h)
-            }
-            ProductId::Aic8800D80 => {
-                info.chip_rev = ((memdata >> 16) & 0x3F) as u8;
-                let is_h = ((memdata >> 16) & 0xC0) == 0xC0;
-                (info.chip_rev, is_h)
-            }
-            ProductId::Aic8800D80X2 => {
-                info.chip_rev = ((memdata >> 16) & 0x3F) as u8;
-                (info.chip_rev, false)
-            }
-        },
-        Err(e) => {
-            // 与 LicheeRV 一致：读 chip_rev 失败（超时或发送失败）则直接返回错误，不使用默认 chip_rev 继续
-            log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: dbg_mem_read 0x40500000 failed {}", e);
-            return Err(AxError::BadState);
-        }
-    };
+    driver_fw_init_on(&SdioDeviceRef, FwInitBus::Threads, product_id, info).map_err(to_ax)
+}
 
-    // 与 LicheeRV 一致：后续 DBG_* 发送经 bustx 线程；tx_fn/poll 先定义，step 2.5 用 send_one 内 with_cmd_mgr 短暂持锁，避免长期持 CMD_MGR 导致 busrx 无法收 CFM 而超时
-    // 主线程直接 with_sdio(send_msg)，不经过 bustx，避免与 busrx 轮询争用 SDIO 导致超时（与 LicheeRV 发送时 bus 独占语义一致）
-    // 发送缓冲：8801 与 LicheeRV 一致每块 1024 字节，消息 16+1032=1048，ipc_send_len_8801 向上取整到 1536
-    let mut block_write_first_log = false;
-    let mut mem_write_first_log = false;
-    let mut tx_fn = |msg: &LmacMsg| -> Result<(), i32> {
-        let mut buf = [0u8; 1536];
-        let len = if product_id == ProductId::Aic8801 {
-            msg.serialize_8801(&mut buf)
-        } else {
-            msg.serialize(&mut buf)
-        };
-        // 与 LicheeRV rwnx_set_cmd_tx 对齐：首包 DBG_MEM_WRITE（system_config 第一笔 0x40500014,0x00000101）打前 24B 便于逐字节对照
-        if product_id == ProductId::Aic8801 && msg.header.id == DBG_MEM_WRITE_REQ && !mem_write_first_log && len >= 24 {
-            mem_write_first_log = true;
-            log::warn!(target: "wireless::bsp::sdio",
-                "DBG_MEM_WRITE_REQ first 24B (LicheeRV rwnx_set_cmd_tx): {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x}",
-                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7], buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15], buf[16], buf[17], buf[18], buf[19], buf[20], buf[21], buf[22], buf[23]);
+/// aicbsp_driver_fw_init 的固件初始化部分（总线已就绪）：读 chip_rev → 选固件表 → 8801 **aicbsp_system_config**
+/// → 3.0 BT adid/patch（8801、8800D80/D80X2，对应 aicbt_init）→ 3a wl_fw 上传 → 3b patch 上传 → 3c aicwifi_patch_config
+/// → 4 aicwifi_sys_config → 5 START_APP → 6 fwlog。8800DC/DW 在选表后转入 `fw_8800dc::aicbsp_fw_init_8800dc`。
+/// 固件由 `get_firmware_by_name` 按固件来源链提供。`sdio` 在板上为 SDIO_DEVICE，主机侧测试为 EmuBootrom8801 / SdioReplay。
+/// 返回负 errno：-ENOENT RF 测试固件缺失，-EBADMSG 固件完整性检查失败，-EINVAL 芯片版本不受支持，其余为 IPC 失败
+fn driver_fw_init_on(sdio: &dyn SdioOps, bus: FwInitBus, product_id: ProductId, info: &mut AicBspInfo) -> Result<(), i32> {
+    let mut io = SdioDbgMemIo::new(sdio, bus, product_id);
+
+    // 1. 读 0x40500000 得 chip_rev。与 LicheeRV 一致：读失败则返回错误，不使用默认 chip_rev
+    let memdata = io.mem_read(CHIP_REV_MEM_ADDR).inspect_err(|e| {
+        log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: dbg_mem_read 0x40500000 failed {}", e);
+    })?;
+    let is_chip_id_h = match product_id {
+        // 8801：与 LicheeRV aic_bsp_driver.c:2019 一致，无掩码 chip_rev = (u8)(memdata >> 16)
+        ProductId::Aic8801 => {
+            info.chip_rev = (memdata >> 16) as u8;
+            false
         }
-        // 与 LicheeRV rwnx_set_cmd_tx 对齐：首包 DBG_MEM_BLOCK_WRITE 打前 24B 便于对照 [len+4 LE2][0x11 0x00][dummy4][id dest src param_len LE2][memaddr LE4]
-        if product_id == ProductId::Aic8801 && msg.header.id == DBG_MEM_BLOCK_WRITE_REQ && !block_write_first_log && len >= 24 {
-            block_write_first_log = true;
-            log::warn!(target: "wireless::bsp::sdio",
-                "DBG_MEM_BLOCK_WRITE_REQ first 24B (LicheeRV rwnx_set_cmd_tx): {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x}",
-                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7], buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15], buf[16], buf[17], buf[18], buf[19], buf[20], buf[21], buf[22], buf[23]);
+        // 8800DC/D80：LicheeRV 用 (memdata>>16)&0x3F / is_chip_id_h=(memdata>>16)&0xC0
+        ProductId::Aic8800Dc | ProductId::Aic8800Dw | ProductId::Aic8800D80 => {
+            info.chip_rev = ((memdata >> 16) & 0x3F) as u8;
+            ((memdata >> 16) & 0xC0) == 0xC0
         }
-        let send_len = if product_id == ProductId::Aic8801 {
-            ipc_send_len_8801(len)
-        } else {
-            len
-        };
-        if send_len > len {
-            buf[len..send_len].fill(0);
+        ProductId::Aic8800D80X2 => {
+            info.chip_rev = ((memdata >> 16) & 0x3F) as u8;
+            false
         }
-        // 8801 固件块写与 sysconfig 一致：经 bustx 发送（submit_cmd_tx_and_wait_tx_done），bustx 内 flow_ctrl+send_pkt，避免主线程直连 send_msg 时与 busrx 争用导致芯片未回 DBG_MEM_BLOCK_WRITE_CFM（BLOCK_CNT 恒 0）
-        if product_id == ProductId::Aic8801 {
-            submit_cmd_tx_and_wait_tx_done(&buf[..len], len).map_err(|e| {
-                log::warn!(target: "wireless::bsp::sdio", "tx_fn submit_cmd_tx (bustx) err={} (e.g. -110=FLOW_CTRL)", e);
-                e
-            })
-        } else {
-            match with_sdio(|sdio| sdio.send_msg(&buf[..send_len], send_len)) {
-                Some(Ok(_)) => Ok(()),
-                Some(Err(e)) => {
-                    log::warn!(target: "wireless::bsp::sdio", "tx_fn send_msg err={} (e.g. -110=FLOW_CTRL/-5=EIO)", e);
-                    Err(e)
-                }
-                None => {
-                    log::warn!(target: "wireless::bsp::sdio", "tx_fn with_sdio None (SDIO_DEVICE not set?)");
-                    Err(-5)
-                }
-            }
-        }
-    };
-    let mut poll = |c: &mut RwnxCmdMgr| {
-        with_sdio(|sdio| poll_rx_one(sdio, c));
     };
-
-    log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: product_id={:?} chip_rev={} (from (memdata>>16), LicheeRV accepts only 3/7)", product_id, info.chip_rev);
+    log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: product_id={:?} memdata=0x{:08x} chip_rev={}", product_id, memdata, info.chip_rev);
+    set_chip_ident(product_id, info.chip_rev, is_chip_id_h);
 
     // 2. 选固件表（与 LicheeRV aic_bsp_driver.c 2019-2030 一致：8801 仅接受 U02/U03/U04，否则返回错误）
-    let fw_list = get_firmware_list(product_id, chip_rev_raw, is_chip_id_h).ok_or_else(|| {
-        log::error!(target: "wireless::bsp::sdio", "aicbsp: aicbsp_driver_fw_init, unsupport chip rev: {} (LicheeRV same check; chip returned (memdata>>16)=0x{:02x}, expected 3 or 7)", info.chip_rev, info.chip_rev);
-        AxError::BadState
+    let fw_list = get_firmware_list(product_id, info.chip_rev, is_chip_id_h).ok_or_else(|| {
+        log::error!(target: "wireless::bsp::sdio", "aicbsp: aicbsp_driver_fw_init, unsupport chip rev: {}", info.chip_rev);
+        -22
     })?;
-
     let cpmode = (info.cpmode as usize).min(1);
     let fw = &fw_list[cpmode];
+    let testmode = cpmode == AicBspCpMode::Test as usize;
+    let bt_mode = BtModeCfg::new(info.hwinfo, info.cpmode);
+    set_bt_fw_ready(false);
     log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: fw_list[{}] wl_fw={}", cpmode, fw.wl_fw);
+    // RF 测试模式：8801 的 fmacfw_rf.bin 仍在 RAM_FMAC_FW_ADDR；DC/DW/D80 的 lmacfw_rf_*.bin 上传并启动于 RAM_LMAC_FW_ADDR
+    let wl_fw_addr = if testmode && product_id != ProductId::Aic8801 {
+        RAM_LMAC_FW_ADDR
+    } else {
+        RAM_FMAC_FW_ADDR
+    };
+    fw_upload_stats_reset();
 
-    // 2.5. 8801：aicbsp_system_config。与 LicheeRV 完全对齐：
-    // - 时序：LicheeRV 在 driver_fw_init 中 mem_read 返回后立即调用 aicbsp_system_config，无中间 delay。
-    // - 发送：经 bustx 线程写 WR_FIFO；收 CFM：仅 busrx+PLIC，主线程不传 poll_fn。
-    if matches!(product_id, ProductId::Aic8801) {
+    // 8800DC/DW：wifi 固件在 ROM，流程与 8801 不同（system_config、BT patch 表、wifi patch、从 ROM 启动），完成后直接返回
+    if matches!(product_id, ProductId::Aic8800Dc | ProductId::Aic8800Dw) {
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: 8800DC/DW bring-up ({})", fw.desc);
+        aicbsp_fw_init_8800dc(&mut io, fw, info.chip_rev, memdata, testmode, &bt_mode).inspect_err(|e| {
+            log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: 8800DC/DW bring-up failed, err={}", e);
+        })?;
+        fwlog_enable_if_configured(&mut io, info);
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: done (all steps ok)");
+        return Ok(());
+    }
+
+    // 2.5. 8801：aicbsp_system_config，与 LicheeRV 一致在 mem_read 返回后立即执行
+    if product_id == ProductId::Aic8801 {
         log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 2.5 aicbsp_system_config_8801");
-        const SYS_CONFIG_CFM_WAIT_MS: u32 = 3000;
-        const SYS_CONFIG_LOG_EVERY_MS: u32 = 500;
-        let mut tick_sys_config = |waited_ms: u32| {
-            log_f1_block_cnt_flow_ctrl(waited_ms);
-        };
-        let mut syscfg_first_24b_log = false;
-        let mut send_one = |addr: u32, data: u32| -> Result<(), i32> {
-            let msg = build_dbg_mem_write_req(addr, data);
-            let mut buf = [0u8; PENDING_CMD_TX_CAP];
-            let len = msg.serialize_8801(&mut buf);
-            let send_len = ipc_send_len_8801(len);
-            if send_len > buf.len() {
-                return Err(-22);
-            }
-            const TAIL_LEN_SYS: usize = 4; // 与 LicheeRV aicwf_sdio_tx_msg TAIL_LEN 一致
-            if send_len > len {
-                buf[len..len + TAIL_LEN_SYS].fill(0);
-                if send_len > len + TAIL_LEN_SYS {
-                    buf[len + TAIL_LEN_SYS..send_len].fill(0);
-                }
-            }
-            log::warn!(
-                target: "wireless::bsp",
-                "send_dbg_mem_write: id={} dest={} src={} param_len={} addr=0x{:08x} data=0x{:08x}",
-                msg.header.id, msg.header.dest_id, msg.header.src_id, msg.header.param_len,
-                addr, data
-            );
-            if !syscfg_first_24b_log && send_len >= 24 {
-                syscfg_first_24b_log = true;
-                log::warn!(target: "wireless::bsp::sdio",
-                    "DBG_MEM_WRITE_REQ first 24B (LicheeRV rwnx_set_cmd_tx): {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x} {:02x}",
-                    buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7], buf[8], buf[9], buf[10], buf[11], buf[12], buf[13], buf[14], buf[15], buf[16], buf[17], buf[18], buf[19], buf[20], buf[21], buf[22], buf[23]);
-            }
-            let token = match with_cmd_mgr(|mgr| mgr.push(DBG_MEM_WRITE_CFM).ok_or(-12)) {
-                Some(Ok(t)) => t,
-                Some(Err(e)) => return Err(e),
-                None => return Err(-5),
-            };
-            submit_cmd_tx_and_wait_tx_done(&buf[..send_len], send_len)?;
-            RwnxCmdMgr::wait_done_until(
-                SYS_CONFIG_CFM_WAIT_MS,
-                || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
-                Some(&mut tick_sys_config),
-                None,
-                Some(SYS_CONFIG_LOG_EVERY_MS),
-            )
-            .map_err(|_| -62)?;
-            // 与 LicheeRV 一致：wait 返回后释放 cmd 槽位（LicheeRV 在 rwnx_send_msg 返回前 kfree(cmd)），否则 10 条 syscfg 会占满 8 个 slot 导致第 9 次 push 返回 -ENOMEM(-12)
-            let _ = with_cmd_mgr(|c| c.take_cfm(token, &mut [0u8; 16]));
-            axtask::sleep(core::time::Duration::from_millis(10));
-            Ok(())
-        };
-        aicbsp_system_config_8801(&mut send_one).map_err(|e| {
+        aicbsp_system_config_8801(&mut |addr, data| io.mem_write(addr, data)).inspect_err(|e| {
             log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: aicbsp_system_config_8801 failed {}", e);
-            AxError::BadState
         })?;
     }
 
-    // 3a / 3b：固件上传。
-    // 根因（等不到 CFM）：原实现整段 3a/3b 持 CMD_MGR，主线程 wait_done 时只由主线程 poll_rx_one 收包；busrx 线程需持 CMD_MGR 才能
-    // 调 on_cfm，故被阻塞无法运行，CFM 仅能靠主线程轮询。与 LicheeRV 不同：LicheeRV 为中断+独立 busrx 线程收包并 complete()，主线程
-    // 只 wait_for_completion。修复：用 push_fn/wait_fn 仅在 push 与每轮 condition/poll 时短暂持锁，wait_done_until 内 1ms sleep 时不持锁，
-    // busrx 可运行并 poll_rx_one → on_cfm，主线程被唤醒后 condition() 为 true。
-    let mut poll_impl = || {
-        with_cmd_mgr(|m| {
-            with_sdio(|s| poll_rx_one(s, m));
-        });
-    };
-    const BLOCK_WRITE_CFM_TIMEOUT_MS: u32 = 500; // 每块等 CFM 超时，到时间未返回即报错
-    const BLOCK_WRITE_LOG_EVERY_MS: u32 = 100; // 等待 CFM 时每 100ms 打 F1 BLOCK_CNT/FLOW_CTRL 便于确认设备是否回包
-    let mut block_wait_tick = |waited_ms: u32| log_f1_block_cnt_flow_ctrl(waited_ms);
-    let mut push_fn = || with_cmd_mgr(|m| m.push(DBG_MEM_BLOCK_WRITE_CFM)).flatten();
-    let mut block_cfm_dummy = [0u8; 16];
-    let mut wait_fn = |token: usize| {
-        RwnxCmdMgr::wait_done_until(
-            BLOCK_WRITE_CFM_TIMEOUT_MS,
-            || with_cmd_mgr(|m| m.is_done(token)).unwrap_or(false),
-            Some(&mut block_wait_tick),
-            Some(&mut poll_impl),
-            Some(BLOCK_WRITE_LOG_EVERY_MS),
-        )
-        .map_err(|_| -62)?;
-        // 与 sysconfig 一致：wait 后 take_cfm 释放 slot，否则多块上传会占满 8 个 slot
-        let _ = with_cmd_mgr(|c| c.take_cfm(token, &mut block_cfm_dummy));
-        Ok(())
-    };
-    if let Some(data) = get_firmware_by_name(fw.wl_fw) {
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a wl_fw upload ({} bytes, {})", data.len(), fw.wl_fw);
-        fw_upload_blocks(
-            &mut tx_fn,
-            RAM_FMAC_FW_ADDR,
-            data,
-            &mut push_fn,
-            &mut wait_fn,
-        )
-        .map_err(|e| {
-            log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: fw_upload_blocks wl_fw failed, err={}", e);
-            AxError::BadState
-        })?;
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a wl_fw done");
-    } else {
-        log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a no firmware for {}, skip", fw.wl_fw);
-    }
-    if matches!(product_id, ProductId::Aic8801) {
-        const RAM_FMAC_FW_PATCH_NAME: &str = "fmacfw_patch.bin";
-        if let Some(data) = get_firmware_by_name(RAM_FMAC_FW_PATCH_NAME) {
-            log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b patch upload ({} bytes, {})", data.len(), RAM_FMAC_FW_PATCH_NAME);
-            fw_upload_blocks(
-                &mut tx_fn,
-                RAM_FMAC_FW_PATCH_ADDR,
-                data,
-                &mut push_fn,
-                &mut wait_fn,
-            )
-            .map_err(|e| {
-                log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: patch upload failed {}", e);
-                AxError::BadState
-            })?;
-            log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b patch done");
+    // 3.0 8801（aicbt_init）与 8800D80/D80X2：与 LicheeRV 一致先加载 BT adid/patch 与 fw_patch_table，再走 3a 上传 wl_fw 并 START_APP；
+    // RF 测试模式不加载 BT；BT 固件缺失时仅告警，WiFi 照常启动（aicbsp_bt_ready() 为 false）
+    if !testmode {
+        let (default_adid, default_patch) = if product_id == ProductId::Aic8801 {
+            (FW_RAM_ADID_BASE_ADDR, FW_RAM_PATCH_BASE_ADDR)
         } else {
-            log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b no {} skip", RAM_FMAC_FW_PATCH_NAME);
+            (FW_RAM_ADID_BASE_ADDR_8800D80, FW_RAM_PATCH_BASE_ADDR_8800D80)
+        };
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3.0 bt patch ({})", fw.desc);
+        match aicbt_patch_load(&mut io, fw, default_adid, default_patch, &bt_mode) {
+            Ok(()) => log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3.0 bt patch done"),
+            Err(-2) => log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3.0 bt firmware missing, bt unavailable"),
+            Err(e) => {
+                log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3.0 bt patch failed, err={}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    // 3a. wl_fw 上传
+    match io.upload(fw.wl_fw, wl_fw_addr) {
+        Ok(()) => log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a wl_fw done"),
+        // RF 测试模式只运行 fmacfw_rf.bin，缺失时 START_APP 会启动不存在的固件
+        Err(-2) if testmode => {
+            log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a rf test firmware {} missing", fw.wl_fw);
+            return Err(-2);
+        }
+        Err(-2) => log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a no firmware for {}, skip", fw.wl_fw),
+        Err(e) => {
+            log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3a {} upload failed, err={}", fw.wl_fw, e);
+            return Err(e);
         }
     }
 
-    // 3c. 8801：aicwifi_patch_config，不持 CMD_MGR（主线程直接发 + wait_done_until），避免 busrx 无法收 CFM
-    if matches!(product_id, ProductId::Aic8801) {
+    if product_id == ProductId::Aic8801 {
+        // 3b. 工作模式上传 fmacfw_patch.bin；RF 测试固件自带 patch，与 LicheeRV testmode 分支一致跳过
+        if !testmode {
+            const RAM_FMAC_FW_PATCH_NAME: &str = "fmacfw_patch.bin";
+            match io.upload(RAM_FMAC_FW_PATCH_NAME, RAM_FMAC_FW_PATCH_ADDR) {
+                Ok(()) => log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b patch done"),
+                Err(-2) => log::warn!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b no {} skip", RAM_FMAC_FW_PATCH_NAME),
+                Err(e) => {
+                    log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3b patch upload failed, err={}", e);
+                    return Err(e);
+                }
+            }
+        }
+        // 3c / 4
         log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3c aicwifi_patch_config_8801");
-        aicwifi_patch_config_8801_init(product_id, CMD_TIMEOUT_MS).map_err(|e| {
+        aicwifi_patch_config_8801(&mut io).inspect_err(|e| {
             log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: aicwifi_patch_config_8801 failed {}", e);
-            AxError::BadState
         })?;
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 3c aicwifi_patch_config_8801 done");
-    }
-
-    // 4. 8801：sys_config（持 cmd_guard 用于 4、5）
-    let mut cmd_guard = CMD_MGR.lock();
-    let cmd_mgr = cmd_guard.as_mut().ok_or(AxError::BadState)?;
-    if matches!(product_id, ProductId::Aic8801) {
         log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 4 aicwifi_sys_config_8801");
-        aicwifi_sys_config_8801(cmd_mgr, &mut tx_fn, CMD_TIMEOUT_MS, &mut poll).map_err(|e| {
+        aicwifi_sys_config_8801(&mut io).inspect_err(|e| {
             log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: aicwifi_sys_config_8801 failed {}", e);
-            AxError::BadState
         })?;
-        log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 4 aicwifi_sys_config_8801 done");
     }
 
     // 5. START_APP（从 bootrom 启动）
     log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 5 fw_start_app");
-    fw_start_app(
-        cmd_mgr,
-        &mut tx_fn,
-        RAM_FMAC_FW_ADDR,
-        HOST_START_APP_AUTO,
-        CMD_TIMEOUT_MS,
-        &mut poll,
-    ).map_err(|e| {
+    io.start_app(wl_fw_addr, HOST_START_APP_AUTO).inspect_err(|e| {
         log::error!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: fw_start_app failed at step 5, err={}", e);
-        AxError::BadState
     })?;
-    log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: step 5 fw_start_app done");
+
+    // 6. fwlog_en：START_APP 后下发日志过滤器
+    fwlog_enable_if_configured(&mut io, info);
 
     log::info!(target: "wireless::bsp::sdio", "aicbsp_driver_fw_init: done (all steps ok)");
     Ok(())
 }
 
+/// 单元测试用：把 dev 挂为软件设备后调用 aicbsp_driver_fw_init（FwInitBus::Poll），返回前卸下；调用方须持 GLOBAL_TEST_LOCK
+#[cfg(test)]
+pub(crate) fn driver_fw_init_on_sim_for_test(
+    dev: Arc<dyn SdioOps + Send + Sync>,
+    product_id: ProductId,
+    info: &mut AicBspInfo,
+) -> AxResult<()> {
+    aicbsp_attach_sim_device(dev, product_id);
+    let r = aicbsp_driver_fw_init(info);
+    aicbsp_detach_sim_device();
+    r
+}
+
 /// **aicbsp_sdio_release** — 释放 SDIO host 占用（保持设备已 probe 状态）
 ///
 /// **作用**：在 `aicbsp_driver_fw_init` 成功后、FDRV 使用 SDIO 前，释放 BSP 对 SDIO host 的占用
@@ -1388,8 +1838,17 @@ pub fn aicbsp_sdio_exit() {
     }
 
     CURRENT_PRODUCT_ID.store(PRODUCT_ID_NONE, Ordering::SeqCst);
+    set_bt_fw_ready(false);
+    clear_chip_info();
     SDIO_DEVICE.lock().take();
-    CMD_MGR.lock().take();
+    TX_DATA.lock().take();
+    TX_SENDER.lock().take();
+    // 对齐 rwnx_cmd_mgr_deinit → cmd_mgr_flush：出锁后再唤醒/回调挂起命令，避免回调内 with_cmd_mgr 死锁
+    let cmd_mgr = CMD_MGR.lock().take();
+    if let Some(mut cmd_mgr) = cmd_mgr {
+        cmd_mgr.flush();
+        cmd_mgr.take_completions().run();
+    }
     log::debug!(target: "wireless::bsp::sdio", "aicbsp_sdio_exit");
 }
 
@@ -0,0 +1,538 @@
+//! 8800DC/DW 固件初始化（对应 LicheeRV aic_bsp_driver.c aicbsp_driver_fw_init 的 8800DC 分支、
+//! aicbsp_system_config_8800dc、aicbt_patch_table_load，以及 aic8800_fdrv rwnx_platform.c 的
+//! aicwf_plat_patch_load_8800dc / aicwf_patch_config_8800dc / start_from_bootrom / aicwf_misc_ram_init_8800dc，
+//! 以及 aic8800dc_compat.c aicwf_set_rf_config_8800dc 的 txgain/rxgain 表下发）
+//!
+//! 与 8801 不同：DC/DW 的 WiFi 固件在 ROM 中，主机只上传 fmacfw_patch_8800dc_u02.bin 到 ROM_FMAC_PATCH_ADDR
+//! 并写 patch 表，再从 ROM_FMAC_FW_ADDR 启动；BT 使能时先上传 adid/patch 并应用 fw_patch_table_8800dc_u02.bin。
+//! BT patch 表加载（aicbt_patch_load）与 8800D80 共用，后者仅默认地址不同。
+
+use alloc::format;
+
+use spin::Mutex;
+
+use crate::bt::{set_bt_fw_ready, BtModeCfg};
+use crate::cmd::{LmacMsg, TaskId, DRV_TASK_ID, MM_SET_RF_CONFIG_CFM, MM_SET_RF_CONFIG_REQ};
+use crate::firmware::{AicBspFirmware, ChipRev};
+use crate::fw_load::RAM_LMAC_FW_ADDR;
+use crate::fw_source::FirmwareBlob;
+use crate::patch_table::{apply_patch_table, PatchSectionType, PatchTable, PatchTableWriter};
+
+/// DC/DW 初始化所需的 DBG_* 操作，由 flow.rs 基于 tx_fn + cmd_mgr 实现（DBG_MEM_WRITE / MASK_WRITE 见 PatchTableWriter）
+pub(super) trait DbgMemIo: PatchTableWriter {
+    /// DBG_MEM_READ_REQ → memdata
+    fn mem_read(&mut self, addr: u32) -> Result<u32, i32>;
+    /// 按名取固件（含清单校验）并 DBG_MEM_BLOCK_WRITE 到 addr；固件不存在返回 -ENOENT
+    fn upload(&mut self, name: &str, addr: u32) -> Result<(), i32>;
+    /// 按名取固件原始内容（不上传），用于 patch 表；不存在返回 None
+    fn firmware(&mut self, name: &str) -> Option<FirmwareBlob>;
+    /// DBG_START_APP_REQ
+    fn start_app(&mut self, boot_addr: u32, boot_type: u32) -> Result<(), i32>;
+    /// 发送一条 LMAC 请求并等待 cfm_id（START_APP 后由运行固件处理，如 MM_SET_RF_CONFIG_REQ）
+    fn lmac_req(&mut self, msg: &LmacMsg, cfm_id: u16) -> Result<(), i32>;
+}
+
+/// ROM 中 FMAC 固件入口（LicheeRV ROM_FMAC_FW_ADDR）
+const ROM_FMAC_FW_ADDR: u32 = 0x0001_0000;
+/// FMAC patch 上传地址（LicheeRV ROM_FMAC_PATCH_ADDR）
+const ROM_FMAC_PATCH_ADDR: u32 = 0x0018_0000;
+/// DBG_START_APP boottype（与 fw_load::HOST_START_APP_AUTO 相同）
+const HOST_START_APP_AUTO: u32 = 1;
+
+/// chip_sub_id 所在地址（aicbsp_system_config_8800dc 读 0x00000004 低字节）
+const CHIP_SUB_ID_ADDR_8800DC: u32 = 0x0000_0004;
+
+/// syscfg_tbl_8800dc_sdio_u01（assert 后死循环，便于 dump）
+const SYSCFG_TBL_8800DC_U01: &[(u32, u32)] = &[
+    (0x4003_0000, 0x0003_6724), // loop forever after assert_err
+    (0x0011_E800, 0xE7FE_4070),
+    (0x4003_0084, 0x0011_E800),
+    (0x4003_0080, 0x0000_0001),
+    (0x4010_001C, 0x0000_0000),
+];
+
+/// syscfg_tbl_8800dc_sdio_u02
+const SYSCFG_TBL_8800DC_U02: &[(u32, u32)] = &[
+    (0x4003_0000, 0x0003_6DA4), // loop forever after assert_err
+    (0x0011_E800, 0xE7FE_4070),
+    (0x4003_0084, 0x0011_E800),
+    (0x4003_0080, 0x0000_0001),
+    (0x4010_001C, 0x0000_0000),
+];
+
+/// syscfg_tbl_masked_8800dc（pmic_pmu_init 等，!CONFIG_VRF_DCDC_MODE）
+const SYSCFG_TBL_MASKED_8800DC: &[(u32, u32, u32)] = &[
+    (0x7000_216C, 0x3 << 2, 0x1 << 2), // pmic_pmu_init
+    (0x7000_21BC, 0x3 << 2, 0x1 << 2),
+    (0x7000_2118, (0x7 << 4) | (0x1 << 7), (0x2 << 4) | (0x1 << 7)),
+    (0x7000_2104, 0x3F | (0x1 << 6), 0x2 | (0x1 << 6)),
+    (0x7000_210C, 0x3F | (0x1 << 6), 0x2 | (0x1 << 6)),
+    (0x7000_2170, 0xF, 0x1),
+    (0x7000_2190, 0x3F, 24),
+    (0x7000_21CC, (0x7 << 4) | (0x1 << 7), 0),
+    (0x7000_10A0, 0x1 << 11, 0x1 << 11),
+    (0x7000_1034, (0x1 << 20) | (0x7 << 26), 0x2 << 26),
+    (0x7000_1038, 0x1 << 8, 0x1 << 8),
+    (0x7000_1094, 0x3 << 2, 0),
+    (0x7000_21D0, (0x1 << 5) | (0x1 << 6), (0x1 << 5) | (0x1 << 6)),
+    (0x7000_1000, 0x1 | (0x1 << 20) | (0x1 << 22), 0x1 << 20),
+    (0x7000_1028, 0xF << 2, 0x1 << 2),
+];
+
+/// aicwf_patch_config_8800dc：ROM 中 patch 描述指针位置（CONFIG_ROM_PATCH_EN）
+const RD_PATCH_ADDR_8800DC: u32 = ROM_FMAC_FW_ADDR + 0x0198;
+/// patch (addr, val) 对写入的起始地址
+const PATCH_START_ADDR_8800DC: u32 = 0x0016_F800;
+/// aic_patch_t.magic_num / magic_num_2
+const AIC_PATCH_MAGIC_NUM: u32 = 0x4843_5450; // "PTCH"
+const AIC_PATCH_MAGIC_NUM_2: u32 = 0x5054_4348; // "HCTP"
+const AIC_PATCH_BLOCK_MAX: u32 = 4;
+/// aic_patch_t 字段偏移：magic_num, pair_start, magic_num_2, pair_count, block_dst[4], block_src[4], block_size[4]
+const AIC_PATCH_OFST_MAGIC_NUM: u32 = 0x00;
+const AIC_PATCH_OFST_PAIR_START: u32 = 0x04;
+const AIC_PATCH_OFST_MAGIC_NUM_2: u32 = 0x08;
+const AIC_PATCH_OFST_PAIR_COUNT: u32 = 0x0C;
+const AIC_PATCH_OFST_BLOCK_SIZE: u32 = 0x10 + 4 * AIC_PATCH_BLOCK_MAX * 2;
+
+/// patch_tbl_8800dc（!USE_5G，DC/DW 仅 2.4G）
+const PATCH_TBL_8800DC: &[(u32, u32)] = &[
+    (0x00b4, 0xf301_0000),
+];
+
+/// aicwf_misc_ram_init_8800dc：cfg_base + 0x14 处为 misc ram 地址，启动后清零 12 字节
+const MISC_RAM_CFG_BASE_8800DC: u32 = 0x0001_0164;
+const MISC_RAM_SIZE_8800DC: u32 = 12;
+
+/// BT adid / patch 默认地址（fw_patch_table 无 AICBT_PINF_T 段时使用）
+const RAM_8800DC_U01_ADID_ADDR: u32 = 0x0010_1788;
+const RAM_8800DC_U02_ADID_ADDR: u32 = 0x0010_17d8;
+const RAM_8800DC_FW_PATCH_ADDR: u32 = 0x0018_4000;
+
+/// mm_set_rf_config_req.table_sel
+const RF_TABLE_SEL_RXGAIN: u8 = 0;
+const RF_TABLE_SEL_TXGAIN: u8 = 1;
+/// rwnx_send_rf_config_req 每条请求下发 16 项（每项 2 个字，共 128 字节），table_ofst 按项递增
+const RF_CONFIG_ENTRIES_PER_REQ: usize = 16;
+const RF_CONFIG_WORDS_PER_REQ: usize = RF_CONFIG_ENTRIES_PER_REQ * 2;
+/// mm_set_rf_config_req：table_sel、table_ofst、table_num、deft_page 各 1 字节 + u32 data[64]
+const RF_CONFIG_REQ_DATA_WORDS: usize = 64;
+
+/// 8800DC/DW 2.4G 增益表，对应 aic8800dc_compat.c 的 txgain_table_24g_8800dcdw / txgain_table_24g_1_8800dcdw
+/// 与 rxgain_table_24g_20m_8800dcdw / rxgain_table_24g_40m_8800dcdw（H 芯片用 *_8800dcdw_h 的 txgain 表）。
+/// 表值随模组校准，由平台按模组提供
+#[derive(Debug, Clone, Copy)]
+pub struct RfGainTables8800dc {
+    pub txgain: [u32; 32],
+    pub txgain_1: [u32; 32],
+    pub rxgain_20m: [u32; 64],
+    pub rxgain_40m: [u32; 64],
+}
+
+static RF_GAIN_TABLES_8800DC: Mutex<Option<&'static RfGainTables8800dc>> = Mutex::new(None);
+
+/// 设置 8800DC/DW 的 txgain/rxgain 表，下次 driver_fw_init 在 START_APP 后下发；None 时不下发，固件使用 ROM 默认表
+pub fn aicbsp_set_rf_gain_tables_8800dc(tables: Option<&'static RfGainTables8800dc>) {
+    *RF_GAIN_TABLES_8800DC.lock() = tables;
+}
+
+/// aicbt_patch_info_t 中本流程用到的字段（来自 AICBT_PINF_T 段）
+#[derive(Debug, Default)]
+struct AicBtPatchInfo {
+    addr_adid: Option<u32>,
+    addr_patch: Option<u32>,
+    /// (ext patch 序号, 上传地址)
+    ext_patch: Option<(u32, u32)>,
+}
+
+/// aicbsp_system_config_8800dc：读 chip_sub_id，写 syscfg_tbl 与 syscfg_tbl_masked
+fn aicbsp_system_config_8800dc(io: &mut dyn DbgMemIo, chip_rev: u8) -> Result<(), i32> {
+    let chip_sub_id = io.mem_read(CHIP_SUB_ID_ADDR_8800DC)? as u8;
+    log::info!(target: "wireless::bsp::sdio", "aicbsp_system_config_8800dc: chip_rev={} chip_sub_id={}", chip_rev, chip_sub_id);
+    let syscfg = if chip_rev == ChipRev::U01 as u8 {
+        SYSCFG_TBL_8800DC_U01
+    } else {
+        SYSCFG_TBL_8800DC_U02
+    };
+    for &(addr, data) in syscfg {
+        io.mem_write(addr, data)?;
+    }
+    for &(addr, mask, data) in SYSCFG_TBL_MASKED_8800DC {
+        io.mem_mask_write(addr, mask, data)?;
+    }
+    log::info!(target: "wireless::bsp::sdio", "aicbsp_system_config_8800dc: done");
+    Ok(())
+}
+
+/// BT 部分（LicheeRV 在 btenable 时于 driver_fw_init 内完成，DC/DW 与 D80 共用）：
+/// 用 PatchTable 解析 bt_table，由 AICBT_PINF_T 得 adid/patch/ext patch 地址（无该段时用 default_adid/default_patch）
+/// → 上传 bt_adid、bt_patch、ext patch → 按 mode 覆盖 AICBT_MODE_T → apply_patch_table 逐段下发（跳过 VER）。
+/// 8801 的 fw_patch_table.bin 无 PINF 段，同样走此函数。成功后 aicbsp_bt_ready() 为 true；任一文件缺失返回 -ENOENT
+pub(super) fn aicbt_patch_load(
+    io: &mut dyn DbgMemIo,
+    fw: &AicBspFirmware,
+    default_adid: u32,
+    default_patch: u32,
+    mode: &BtModeCfg,
+) -> Result<(), i32> {
+    let table = match io.firmware(fw.bt_table) {
+        Some(t) => t,
+        None => {
+            log::error!(target: "wireless::bsp::sdio", "aicbt_patch_load: no {}", fw.bt_table);
+            return Err(-2);
+        }
+    };
+    let mut table = PatchTable::parse(&table).map_err(|e| {
+        log::error!(target: "wireless::bsp::sdio", "aicbt_patch_load: {}: {}", fw.bt_table, e);
+        -22
+    })?;
+    let mut info = AicBtPatchInfo::default();
+    if let Some(inf) = table.section(PatchSectionType::Inf) {
+        info.addr_adid = inf.pair(0).map(|p| p.1);
+        info.addr_patch = inf.pair(1).map(|p| p.1);
+        // 第 5 对为 (ext_patch_nb_addr, ext_patch_nb)，第 6 对为 (ext patch 序号, 上传地址)
+        if inf.pair(4).is_some_and(|p| p.1 > 0) {
+            info.ext_patch = inf.pair(5);
+        }
+    }
+    let addr_adid = info.addr_adid.unwrap_or(default_adid);
+    let addr_patch = info.addr_patch.unwrap_or(default_patch);
+    log::info!(target: "wireless::bsp::sdio", "aicbt_patch_load: adid 0x{:08x} patch 0x{:08x} ext {:?}", addr_adid, addr_patch, info.ext_patch);
+    io.upload(fw.bt_adid, addr_adid)?;
+    io.upload(fw.bt_patch, addr_patch)?;
+    if let (Some((id, addr)), Some(ext)) = (info.ext_patch, fw.bt_ext_patch) {
+        let name = format!("{}{}.bin", ext, id);
+        match io.upload(&name, addr) {
+            Err(-2) => log::warn!(target: "wireless::bsp::sdio", "aicbt_patch_load: no {}, skip ext patch", name),
+            r => r?,
+        }
+    }
+    mode.apply(&mut table);
+    log::info!(target: "wireless::bsp::sdio", "aicbt_patch_load: {} ({} sections, {} writes)", fw.bt_table, table.sections.len(), table.op_count());
+    apply_patch_table(io, &table)?;
+    set_bt_fw_ready(true);
+    Ok(())
+}
+
+/// aicwf_patch_config_8800dc：按 ROM 中 aic_patch_t 描述写 magic、patch 对起始地址与数量、各 (addr, val)，并关闭 block patch
+fn aicwifi_patch_config_8800dc(io: &mut dyn DbgMemIo) -> Result<(), i32> {
+    let config_base = io.mem_read(RD_PATCH_ADDR_8800DC)?;
+    let aic_patch_str_base = io.mem_read(RD_PATCH_ADDR_8800DC + 8)?;
+    log::info!(target: "wireless::bsp::sdio", "aicwifi_patch_config_8800dc: config_base=0x{:08x} patch_str_base=0x{:08x}", config_base, aic_patch_str_base);
+    io.mem_write(aic_patch_str_base + AIC_PATCH_OFST_MAGIC_NUM, AIC_PATCH_MAGIC_NUM)?;
+    io.mem_write(aic_patch_str_base + AIC_PATCH_OFST_MAGIC_NUM_2, AIC_PATCH_MAGIC_NUM_2)?;
+    io.mem_write(aic_patch_str_base + AIC_PATCH_OFST_PAIR_START, PATCH_START_ADDR_8800DC)?;
+    io.mem_write(aic_patch_str_base + AIC_PATCH_OFST_PAIR_COUNT, PATCH_TBL_8800DC.len() as u32)?;
+    for (cnt, &(off, val)) in PATCH_TBL_8800DC.iter().enumerate() {
+        let addr = PATCH_START_ADDR_8800DC + (cnt as u32) * 8;
+        io.mem_write(addr, off + config_base)?;
+        io.mem_write(addr + 4, val)?;
+    }
+    for i in 0..AIC_PATCH_BLOCK_MAX {
+        io.mem_write(aic_patch_str_base + AIC_PATCH_OFST_BLOCK_SIZE + i * 4, 0)?;
+    }
+    Ok(())
+}
+
+/// aicwf_misc_ram_init_8800dc：启动后清 misc ram
+fn aicwifi_misc_ram_init_8800dc(io: &mut dyn DbgMemIo) -> Result<(), i32> {
+    let misc_ram_addr = io.mem_read(MISC_RAM_CFG_BASE_8800DC + 0x14)?;
+    for i in 0..MISC_RAM_SIZE_8800DC / 4 {
+        io.mem_write(misc_ram_addr + i * 4, 0)?;
+    }
+    Ok(())
+}
+
+/// 构建 MM_SET_RF_CONFIG_REQ：table_num 取 data 的项数（每项 2 个字），deft_page 为 0
+fn build_mm_set_rf_config_req(table_sel: u8, table_ofst: u8, data: &[u32]) -> LmacMsg {
+    let mut msg = LmacMsg::new(MM_SET_RF_CONFIG_REQ, TaskId::Mm as u16, DRV_TASK_ID, (4 + RF_CONFIG_REQ_DATA_WORDS * 4) as u16);
+    msg.param[0] = table_sel;
+    msg.param[1] = table_ofst;
+    msg.param[2] = (data.len() / 2) as u8;
+    msg.param[3] = 0;
+    for (i, w) in data.iter().enumerate() {
+        msg.param[4 + i * 4..8 + i * 4].copy_from_slice(&w.to_le_bytes());
+    }
+    msg
+}
+
+/// aicwf_set_rf_config_8800dc 的增益表部分：txgain 表从 table_ofst 0 / 32 下发，rxgain 20M / 40M 表从 0 / 32 下发，
+/// 每条 MM_SET_RF_CONFIG_REQ 16 项并等 CFM。未设置 RfGainTables8800dc 时跳过
+fn aicwifi_rf_config_8800dc(io: &mut dyn DbgMemIo) -> Result<(), i32> {
+    let Some(tables) = *RF_GAIN_TABLES_8800DC.lock() else {
+        log::warn!(target: "wireless::bsp::sdio", "aicwifi_rf_config_8800dc: no gain tables set, firmware keeps ROM defaults");
+        return Ok(());
+    };
+    let sets: [(u8, u8, &[u32]); 4] = [
+        (RF_TABLE_SEL_TXGAIN, 0, &tables.txgain),
+        (RF_TABLE_SEL_TXGAIN, 32, &tables.txgain_1),
+        (RF_TABLE_SEL_RXGAIN, 0, &tables.rxgain_20m),
+        (RF_TABLE_SEL_RXGAIN, 32, &tables.rxgain_40m),
+    ];
+    for (sel, ofst, table) in sets {
+        for (i, chunk) in table.chunks(RF_CONFIG_WORDS_PER_REQ).enumerate() {
+            let table_ofst = ofst + (i * RF_CONFIG_ENTRIES_PER_REQ) as u8;
+            io.lmac_req(&build_mm_set_rf_config_req(sel, table_ofst, chunk), MM_SET_RF_CONFIG_CFM)?;
+        }
+    }
+    log::info!(target: "wireless::bsp::sdio", "aicwifi_rf_config_8800dc: gain tables done");
+    Ok(())
+}
+
+/// 8800DC/DW 完整初始化（替代 8801 的 3a~5 步）：
+/// system_config → [btenable] BT adid/patch/ext patch + patch 表 → wl_fw 上传到 ROM_FMAC_PATCH_ADDR
+/// → aicwifi_patch_config → START_APP(ROM_FMAC_FW_ADDR) → misc_ram_init → txgain/rxgain 表（MM_SET_RF_CONFIG_REQ）。
+///
+/// testmode 时改为 system_config → 上传 RF 测试固件到 RAM_LMAC_FW_ADDR → START_APP，与 LicheeRV 一致不下发增益表。
+/// chip_memdata 为读 0x40500000 的原始值，bit26 为 0 表示芯片启用 BT（LicheeRV btenable = !((memdata >> 26) & 1)）。
+pub(super) fn aicbsp_fw_init_8800dc(
+    io: &mut dyn DbgMemIo,
+    fw: &AicBspFirmware,
+    chip_rev: u8,
+    chip_memdata: u32,
+    testmode: bool,
+    bt_mode: &BtModeCfg,
+) -> Result<(), i32> {
+    aicbsp_system_config_8800dc(io, chip_rev)?;
+    if testmode {
+        // RF 测试：lmacfw_rf_8800dc.bin 为完整 LMAC 固件，上传到 RAM_LMAC_FW_ADDR 直接启动，不加载 BT 与 ROM patch
+        io.upload(fw.wl_fw, RAM_LMAC_FW_ADDR)?;
+        io.start_app(RAM_LMAC_FW_ADDR, HOST_START_APP_AUTO)?;
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_fw_init_8800dc: rf test firmware started ({})", fw.desc);
+        return Ok(());
+    }
+    let btenable = (chip_memdata >> 26) & 1 == 0;
+    if btenable {
+        let default_adid = if chip_rev == ChipRev::U01 as u8 {
+            RAM_8800DC_U01_ADID_ADDR
+        } else {
+            RAM_8800DC_U02_ADID_ADDR
+        };
+        match aicbt_patch_load(io, fw, default_adid, RAM_8800DC_FW_PATCH_ADDR, bt_mode) {
+            Err(-2) => log::warn!(target: "wireless::bsp::sdio", "aicbsp_fw_init_8800dc: bt firmware missing, bt unavailable"),
+            r => r?,
+        }
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_fw_init_8800dc: bt patch done");
+    } else {
+        log::info!(target: "wireless::bsp::sdio", "aicbsp_fw_init_8800dc: bt disabled by chip (memdata=0x{:08x}), skip bt patch", chip_memdata);
+    }
+    io.upload(fw.wl_fw, ROM_FMAC_PATCH_ADDR)?;
+    aicwifi_patch_config_8800dc(io)?;
+    io.start_app(ROM_FMAC_FW_ADDR, HOST_START_APP_AUTO)?;
+    aicwifi_misc_ram_init_8800dc(io)?;
+    aicwifi_rf_config_8800dc(io)?;
+    log::info!(target: "wireless::bsp::sdio", "aicbsp_fw_init_8800dc: done ({})", fw.desc);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::collections::BTreeMap;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::bt::BtModeCfg;
+    use crate::cmd::{cmd_flags, RwnxCmdMgr};
+    use crate::fw_load::{build_dbg_mem_block_write_req, build_dbg_mem_read_req, build_dbg_mem_mask_write_req,
+        build_dbg_mem_write_req, build_dbg_start_app_req, parse_dbg_mem_read_cfm_with_addr, DBG_MEM_BLOCK_WRITE_CFM,
+        DBG_MEM_MASK_WRITE_CFM, DBG_MEM_READ_CFM, DBG_MEM_WRITE_CFM, DBG_START_APP_CFM, FW_UPLOAD_BLOCK_SIZE};
+    use crate::sdio::emu::{EmuBootrom8801, EmuFirmware, EmuReply};
+    use crate::sdio::ops::SdioOps;
+    use crate::sdio::flow::{ipc_serialize_lmac_msg, poll_rx_one};
+    use crate::sdio::types::ProductId;
+    use crate::sdio::GLOBAL_TEST_LOCK;
+
+    const TIMEOUT_MS: u32 = 100;
+    const CONFIG_BASE: u32 = 0x0010_0000;
+    const PATCH_STR_BASE: u32 = 0x0016_0000;
+    const MISC_RAM_ADDR: u32 = 0x0010_2000;
+
+    static TABLES: RfGainTables8800dc = RfGainTables8800dc {
+        txgain: gen_table::<32>(0x1000_0000),
+        txgain_1: gen_table::<32>(0x2000_0000),
+        rxgain_20m: gen_table::<64>(0x3000_0000),
+        rxgain_40m: gen_table::<64>(0x4000_0000),
+    };
+
+    const fn gen_table<const N: usize>(base: u32) -> [u32; N] {
+        let mut t = [0u32; N];
+        let mut i = 0;
+        while i < N {
+            t[i] = base + i as u32;
+            i += 1;
+        }
+        t
+    }
+
+    /// 运行固件模型：记录 MM_SET_RF_CONFIG_REQ 的 param 并回 CFM
+    struct RfConfigFw(Arc<Mutex<Vec<Vec<u8>>>>);
+
+    impl EmuFirmware for RfConfigFw {
+        fn on_msg(&mut self, id: u16, param: &[u8], reply: &mut EmuReply<'_>) -> bool {
+            if id != MM_SET_RF_CONFIG_REQ {
+                return false;
+            }
+            self.0.lock().push(param.to_vec());
+            reply.push(MM_SET_RF_CONFIG_CFM, &[]);
+            true
+        }
+    }
+
+    /// 经 8801 格式 A2E 驱动 bootrom 模型的 DbgMemIo，命令走本地 cmd_mgr
+    struct EmuDbgIo<'a> {
+        emu: &'a EmuBootrom8801,
+        cmd_mgr: RwnxCmdMgr,
+        firmware: BTreeMap<&'static str, Vec<u8>>,
+    }
+
+    impl EmuDbgIo<'_> {
+        fn req(&mut self, msg: &LmacMsg, cfm_id: u16) -> Result<Vec<u8>, i32> {
+            let token = self.cmd_mgr.push(cfm_id, cmd_flags::REQ_CFM).ok_or(-12)?;
+            let mut buf = [0u8; 1536];
+            let (_, send_len) = ipc_serialize_lmac_msg(ProductId::Aic8801, msg, &mut buf)?;
+            self.emu.send_msg(&buf[..send_len], send_len)?;
+            let emu = self.emu;
+            self.cmd_mgr.wait_done(token, TIMEOUT_MS, &mut |c| {
+                let _ = poll_rx_one(emu, c);
+            }, None)?;
+            self.cmd_mgr.take_cfm_vec(token).ok_or(-5)
+        }
+    }
+
+    impl PatchTableWriter for EmuDbgIo<'_> {
+        fn mem_write(&mut self, addr: u32, data: u32) -> Result<(), i32> {
+            self.req(&build_dbg_mem_write_req(addr, data), DBG_MEM_WRITE_CFM).map(|_| ())
+        }
+
+        fn mem_mask_write(&mut self, addr: u32, mask: u32, data: u32) -> Result<(), i32> {
+            self.req(&build_dbg_mem_mask_write_req(addr, mask, data), DBG_MEM_MASK_WRITE_CFM).map(|_| ())
+        }
+    }
+
+    impl DbgMemIo for EmuDbgIo<'_> {
+        fn mem_read(&mut self, addr: u32) -> Result<u32, i32> {
+            let cfm = self.req(&build_dbg_mem_read_req(addr), DBG_MEM_READ_CFM)?;
+            parse_dbg_mem_read_cfm_with_addr(&cfm, addr).ok_or(-5)
+        }
+
+        fn upload(&mut self, name: &str, addr: u32) -> Result<(), i32> {
+            let data = self.firmware.get(name).cloned().ok_or(-2)?;
+            for (i, chunk) in data.chunks(FW_UPLOAD_BLOCK_SIZE).enumerate() {
+                let block_addr = addr + (i * FW_UPLOAD_BLOCK_SIZE) as u32;
+                let msg = build_dbg_mem_block_write_req(block_addr, chunk.len() as u32, chunk).ok_or(-22)?;
+                self.req(&msg, DBG_MEM_BLOCK_WRITE_CFM)?;
+            }
+            Ok(())
+        }
+
+        fn firmware(&mut self, name: &str) -> Option<FirmwareBlob> {
+            self.firmware.get(name).cloned().map(FirmwareBlob::Owned)
+        }
+
+        fn start_app(&mut self, boot_addr: u32, boot_type: u32) -> Result<(), i32> {
+            self.req(&build_dbg_start_app_req(boot_addr, boot_type), DBG_START_APP_CFM).map(|_| ())
+        }
+
+        fn lmac_req(&mut self, msg: &LmacMsg, cfm_id: u16) -> Result<(), i32> {
+            self.req(msg, cfm_id).map(|_| ())
+        }
+    }
+
+    const FW: AicBspFirmware = AicBspFirmware {
+        desc: "emu 8800dc",
+        bt_adid: "emu_adid.bin",
+        bt_patch: "emu_bt_patch.bin",
+        bt_table: "emu_bt_table.bin",
+        wl_fw: "emu_fmacfw_patch_8800dc.bin",
+        bt_ext_patch: None,
+    };
+    /// 0x40500000 bit26 置位：芯片禁用 BT，跳过 BT patch
+    const MEMDATA_BT_DISABLED: u32 = 1 << 26;
+
+    fn boot_emu(rf_log: &Arc<Mutex<Vec<Vec<u8>>>>) -> EmuBootrom8801 {
+        let emu = EmuBootrom8801::with_firmware(ChipRev::U02 as u8, Box::new(RfConfigFw(rf_log.clone())));
+        emu.mem_write_word(RD_PATCH_ADDR_8800DC, CONFIG_BASE);
+        emu.mem_write_word(RD_PATCH_ADDR_8800DC + 8, PATCH_STR_BASE);
+        emu.mem_write_word(MISC_RAM_CFG_BASE_8800DC + 0x14, MISC_RAM_ADDR);
+        for i in 0..MISC_RAM_SIZE_8800DC / 4 {
+            emu.mem_write_word(MISC_RAM_ADDR + i * 4, 0xdead_beef);
+        }
+        emu
+    }
+
+    fn io<'a>(emu: &'a EmuBootrom8801, wl_fw: &[u8]) -> EmuDbgIo<'a> {
+        let mut firmware = BTreeMap::new();
+        firmware.insert(FW.wl_fw, wl_fw.to_vec());
+        EmuDbgIo { emu, cmd_mgr: RwnxCmdMgr::new(), firmware }
+    }
+
+    #[test]
+    fn dc_init_patches_rom_and_sends_gain_tables_after_start_app() {
+        let _g = GLOBAL_TEST_LOCK.lock();
+        aicbsp_set_rf_gain_tables_8800dc(Some(&TABLES));
+        let rf_log = Arc::new(Mutex::new(Vec::new()));
+        let emu = boot_emu(&rf_log);
+        let wl_fw: Vec<u8> = (0..2500u32).map(|i| (i * 13 + 1) as u8).collect();
+        let mut io = io(&emu, &wl_fw);
+        let r = aicbsp_fw_init_8800dc(&mut io, &FW, ChipRev::U02 as u8, MEMDATA_BT_DISABLED, false, &BtModeCfg::new(0, 0));
+        aicbsp_set_rf_gain_tables_8800dc(None);
+        r.unwrap();
+
+        let (addr, data) = SYSCFG_TBL_8800DC_U02[0];
+        assert_eq!(emu.mem_read_word(addr), data);
+        assert_eq!(emu.mem_read(ROM_FMAC_PATCH_ADDR, wl_fw.len()), wl_fw);
+        assert_eq!(emu.mem_read_word(PATCH_STR_BASE + AIC_PATCH_OFST_MAGIC_NUM), AIC_PATCH_MAGIC_NUM);
+        assert_eq!(emu.mem_read_word(PATCH_STR_BASE + AIC_PATCH_OFST_PAIR_COUNT), PATCH_TBL_8800DC.len() as u32);
+        assert_eq!(emu.mem_read_word(PATCH_START_ADDR_8800DC), PATCH_TBL_8800DC[0].0 + CONFIG_BASE);
+        assert_eq!(emu.started(), Some((ROM_FMAC_FW_ADDR, HOST_START_APP_AUTO)));
+        for i in 0..MISC_RAM_SIZE_8800DC / 4 {
+            assert_eq!(emu.mem_read_word(MISC_RAM_ADDR + i * 4), 0);
+        }
+
+        // 增益表在 START_APP 之后由运行固件接收，每条 16 项
+        let log = emu.msg_log();
+        let start = log.iter().position(|&id| id == crate::fw_load::DBG_START_APP_REQ).unwrap();
+        let first_rf = log.iter().position(|&id| id == MM_SET_RF_CONFIG_REQ).unwrap();
+        assert!(first_rf > start);
+        let reqs = rf_log.lock().clone();
+        let heads: Vec<[u8; 4]> = reqs.iter().map(|p| [p[0], p[1], p[2], p[3]]).collect();
+        assert_eq!(
+            heads,
+            [[1, 0, 16, 0], [1, 32, 16, 0], [0, 0, 16, 0], [0, 16, 16, 0], [0, 32, 16, 0], [0, 48, 16, 0]]
+        );
+        assert!(reqs.iter().all(|p| p.len() == 4 + RF_CONFIG_REQ_DATA_WORDS * 4));
+        let words = |p: &Vec<u8>| -> Vec<u32> {
+            p[4..4 + RF_CONFIG_WORDS_PER_REQ * 4].chunks(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+        };
+        assert_eq!(words(&reqs[1]), TABLES.txgain_1);
+        assert_eq!(words(&reqs[3]), TABLES.rxgain_20m[32..]);
+        assert_eq!(words(&reqs[4]), TABLES.rxgain_40m[..32]);
+        assert_eq!(emu.dropped(), 0);
+        assert_eq!(emu.pending_rx(), 0);
+    }
+
+    #[test]
+    fn dc_init_without_tables_and_testmode_send_no_rf_config() {
+        let _g = GLOBAL_TEST_LOCK.lock();
+        aicbsp_set_rf_gain_tables_8800dc(None);
+        let rf_log = Arc::new(Mutex::new(Vec::new()));
+        let emu = boot_emu(&rf_log);
+        let wl_fw = [0x5au8; 600];
+        aicbsp_fw_init_8800dc(&mut io(&emu, &wl_fw), &FW, ChipRev::U02 as u8, MEMDATA_BT_DISABLED, false, &BtModeCfg::new(0, 0))
+            .unwrap();
+        assert_eq!(emu.started(), Some((ROM_FMAC_FW_ADDR, HOST_START_APP_AUTO)));
+        assert!(rf_log.lock().is_empty());
+
+        // RF 测试模式：整份固件上传到 RAM_LMAC_FW_ADDR 启动，即使设置了增益表也不下发
+        aicbsp_set_rf_gain_tables_8800dc(Some(&TABLES));
+        let emu = boot_emu(&rf_log);
+        let r = aicbsp_fw_init_8800dc(&mut io(&emu, &wl_fw), &FW, ChipRev::U02 as u8, MEMDATA_BT_DISABLED, true, &BtModeCfg::new(0, 0));
+        aicbsp_set_rf_gain_tables_8800dc(None);
+        r.unwrap();
+        assert_eq!(emu.started(), Some((RAM_LMAC_FW_ADDR, HOST_START_APP_AUTO)));
+        assert_eq!(emu.mem_read(RAM_LMAC_FW_ADDR, wl_fw.len()), wl_fw);
+        assert!(rf_log.lock().is_empty());
+        assert_eq!(emu.dropped(), 0);
+    }
+}
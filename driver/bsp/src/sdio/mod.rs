@@ -7,14 +7,23 @@
 //! - `cis` — FBR/CIS 读与解析、probe_from_sdio_cis
 //! - `backend` — Aic8800SdioHost（基于 SG2002 SD1 的 CMD52/CMD53）
 //! - `flow` — SDIO 流程六函数
+//! - `fw_8800dc` — 8800DC/DW 固件初始化（system_config、BT patch 表、wifi patch、START_APP）
+//! - `emu` — 8801 bootrom 软件模型（主机侧测试）
+//! - `record` — SdioOps 访问录制与回放（板上 trace 转回归测试）
+//! - `tx_aggr` — 数据帧 TX 聚合（aicwf_sdio_aggr / aicwf_sdio_send）
 
 mod backend;
 mod chip_ident;
 mod cis;
+#[cfg(any(test, feature = "sim"))]
+mod emu;
 mod flow;
+mod fw_8800dc;
 pub mod irq;
 mod mmc_impl;
 mod ops;
+mod record;
+mod tx_aggr;
 mod types;
 
 // 类型与常量
@@ -38,13 +47,39 @@ pub use cis::{
 // AIC8800 SDIO 主机：基于 SG2002 SD1 的 CMD52/CMD53 实现
 pub use backend::Aic8800SdioHost;
 
+// 8800DC/DW 增益表
+pub use fw_8800dc::{aicbsp_set_rf_gain_tables_8800dc, RfGainTables8800dc};
+
+// 8801 bootrom 软件模型
+#[cfg(any(test, feature = "sim"))]
+pub use emu::{EmuBootrom8801, EmuFirmware, EmuReply};
+
+// SdioOps 录制与回放
+pub use record::{SdioOpKind, SdioRecorder, SdioReplay, SdioReplayError, SdioTrace, SdioTraceOp, SdioTraceParseError};
+
+// 单元测试访问全局 cmd_mgr
+#[cfg(test)]
+pub(crate) use flow::{driver_fw_init_on_sim_for_test, replace_cmd_mgr_for_test, GLOBAL_TEST_LOCK};
+
+// 数据帧 TX 聚合
+pub use tx_aggr::{TxAggrBuf, TxDataPriv, TxSender, MAX_AGGR_TXPKT_LEN, TXPKT_BLOCKSIZE, TXQLEN, TX_FRAME_MAX_LEN};
+
 // 流程六函数与 IPC 导出（供 FDRV 发送 LMAC 命令与注册 E2A 回调）
+#[cfg(any(test, feature = "sim"))]
+pub use flow::{aicbsp_attach_sim_device, aicbsp_detach_sim_device, aicbsp_set_sim_power_cycle, sdio_tx_process_once};
+#[cfg(any(test, feature = "sim"))]
+pub(crate) use flow::sim_power_cycle_hook;
+#[cfg(feature = "record")]
+pub use flow::{aicbsp_sdio_record_start, aicbsp_sdio_record_stop};
 pub use flow::{
     aicbsp_current_product_id, aicbsp_driver_fw_init, aicbsp_minimal_ipc_verify, aicbsp_power_on,
+    aicbsp_set_cmd_queue_depth,
     aicbsp_sdio_exit, aicbsp_sdio_init, aicbsp_sdio_probe, aicbsp_sdio_release,
-    submit_cmd_tx_and_wait_tx_done, with_cmd_mgr, set_e2a_indication_cb, set_rx_data_indication_cb,
-    sdio_poll_rx_once, E2aIndicationCb, RxDataIndicationCb,
+    aicbsp_txdata_enqueue, aicbsp_txdata_stats,
+    submit_cmd_tx_and_wait_tx_done, with_cmd_mgr, with_sdio, set_e2a_indication_cb, set_rx_data_indication_cb,
+    sdio_poll_rx_once, ipc_serialize_lmac_msg, E2aIndicationCb, RxDataIndicationCb, SdioDeviceRef, PENDING_CMD_TX_CAP,
 };
+pub(crate) use flow::{send_dbg_req, try_with_cmd_mgr};
 
 // mmc crate 实现（MmcHost / SdioFunc）及 SDIO 驱动注册
 pub use mmc_impl::{register_aicbsp_sdio_driver, BspSdioFuncRef, BspSdioHost};
@@ -63,6 +63,15 @@ pub trait SdioOps {
     }
     /// 从读 FIFO 接收一包数据。`size` 为字节数，`msg` 非 0 表示 Function 2 读 FIFO。
     fn recv_pkt(&self, buf: &mut [u8], size: u32, msg: u8) -> Result<usize, i32>;
+    /// E2A 所在 RD_FIFO 的待收字节数：V1/V2 读 BLOCK_CNT（>=64 时为 BYTEMODE_LEN*4；8801 为 F1，8800DC/DW 为 F2），
+    /// V3 读 MISC_INT_STATUS；0 表示无数据。不能按寄存器定长的设备返回 -ENODEV，调用方退回定长 recv_pkt。
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        Err(-19)
+    }
+    /// 一次 CMD53 多块读 rx_pending_len 所指 function 的 RD_FIFO，读满 `buf`（长度取 rx_pending_len 的结果，排空整个突发）。
+    fn read_rx_fifo(&self, _buf: &mut [u8]) -> Result<usize, i32> {
+        Err(-19)
+    }
     /// 向写 FIFO 发送一包数据，`count` 为字节数。
     fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32>;
     /// 向 Function 2 消息地址发送消息（aicsdio.c send_msg）。
@@ -211,6 +220,97 @@ impl Aic8800Sdio {
     pub fn product_id(&self) -> ProductId {
         self.product_id
     }
+
+    /// 是否为 V3 寄存器布局（8800D80/8800D80X2，仅 F1，无 func_msg）
+    pub fn is_v3(&self) -> bool {
+        matches!(self.product_id, ProductId::Aic8800D80 | ProductId::Aic8800D80X2)
+    }
+
+    /// F1 状态寄存器 (块计数, bytemode_len, flow_ctrl)：V1/V2 为 BLOCK_CNT(0x12)/BYTEMODE_LEN(0x02)/FLOW_CTRL(0x0A)，
+    /// V3 为 MISC_INT_STATUS(0x04)/BYTEMODE_LEN(0x05)/FLOW_CTRL_Q1(0x03)（aicwf_sdio_reg_init）
+    pub fn f1_status_regs(&self) -> (u8, u8, u8) {
+        if self.is_v3() {
+            (reg_v3::MISC_INT_STATUS, reg_v3::BYTEMODE_LEN, reg_v3::FLOW_CTRL_Q1)
+        } else {
+            (reg::BLOCK_CNT, reg::BYTEMODE_LEN, reg::FLOW_CTRL)
+        }
+    }
+
+    /// 与 LicheeRV aicwf_sdio_tx_msg(aicsdio.c 979-1001) 完全一致：buffer_cnt = flow_ctrl(); while ((buffer_cnt<=0 || (buffer_cnt>0 && len>buffer_cnt*BUFFER_SIZE)) && retry<10) { retry++; buffer_cnt = flow_ctrl(); }
+    /// 每轮 aicwf_sdio_flow_ctrl 为 50 次重试、递增延时；未就绪则返回 -110 不写 WR_FIFO，避免 CMD53 超时。
+    fn wait_flow_ctrl(&self, flow_ctrl_reg: u8, count: usize) -> Result<(), i32> {
+        for _retry in 0..10u8 {
+            let mut last_fc: u8 = 0;
+            for i in 0..FLOW_CTRL_RETRY_COUNT {
+                let fc = self.host.read_byte_at_func(1, flow_ctrl_reg as u32)?;
+                last_fc = fc & reg::FLOWCTRL_MASK;
+                if last_fc > FLOW_CTRL_THRESH {
+                    break;
+                }
+                if i < 30 {
+                    crate::delay_spin_us(200);
+                } else if i < 40 {
+                    axtask::sleep(core::time::Duration::from_millis(1));
+                } else {
+                    axtask::sleep(core::time::Duration::from_millis(10));
+                }
+            }
+            if last_fc > 0 && count < last_fc as usize * BUFFER_SIZE {
+                return Ok(());
+            }
+        }
+        Err(-110)
+    }
+
+    /// V3 收包长度（aicwf_sdio_hal_irqhandler V3 分支）：读 MISC_INT_STATUS，bit7 为 SDIO_OTHER_INTERRUPT；
+    /// 其余为 120 时为 byte mode，长度 = BYTEMODE_LEN*4，否则 (intstatus & 0x7F) 块 * 512。返回 0 表示无数据
+    fn v3_rx_data_len(&self) -> Result<usize, i32> {
+        const SDIO_OTHER_INTERRUPT: u8 = 0x80;
+        const V3_BYTEMODE_FLAG: u8 = 120;
+        const BLOCKSIZE: usize = 512;
+        let intstatus = self.host.read_byte_at_func(1, reg_v3::MISC_INT_STATUS as u32)? & !SDIO_OTHER_INTERRUPT;
+        if intstatus == 0 {
+            return Ok(0);
+        }
+        if intstatus == V3_BYTEMODE_FLAG {
+            let byte_len = self.host.read_byte_at_func(1, reg_v3::BYTEMODE_LEN as u32)?;
+            Ok(byte_len as usize * 4)
+        } else {
+            Ok((intstatus & 0x7F) as usize * BLOCKSIZE)
+        }
+    }
+
+    /// V1/V2 收包长度（aicsdio.c 1449-1472）：读 func 的 BLOCK_CNT(0x12)，intstatus < 64 为 block_cnt*512，>=64 为 BYTEMODE_LEN*4。
+    /// 8801 读 F1；8800DC/DW 的 E2A 消息在 F2，与 aicwf_sdio_hal_irqhandler_func2 一样读 F2 的同名寄存器。
+    /// 必须用 read_byte_at_func(func, reg)：backend.read_byte(addr) 始终 fn=0，若用 read_byte(0x112) 会误读 F0 导致恒得 0
+    fn block_cnt_rx_len(&self, func: u8) -> Result<usize, i32> {
+        const BLOCKSIZE: usize = 512;
+        const BYTEMODE_THRESH: u8 = 64;
+        let block_cnt = self.host.read_byte_at_func(func, reg::BLOCK_CNT as u32)?;
+        if block_cnt == 0 {
+            return Ok(0);
+        }
+        if block_cnt >= BYTEMODE_THRESH {
+            let byte_len = self.host.read_byte_at_func(func, reg::BYTEMODE_LEN as u32)?;
+            Ok(byte_len as usize * 4)
+        } else {
+            Ok(block_cnt as usize * BLOCKSIZE)
+        }
+    }
+
+    /// E2A 消息所在的 function：8800DC/DW 为 F2（func_msg），其余为 F1
+    fn msg_func(&self) -> u8 {
+        if matches!(self.product_id, ProductId::Aic8800Dc | ProductId::Aic8800Dw) {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// IPC 走 F1 rd_fifo 且可按寄存器定长的芯片（8801、D80/D80X2）
+    fn rx_len_from_regs(&self) -> bool {
+        self.product_id == ProductId::Aic8801 || self.is_v3()
+    }
 }
 
 impl SdioOps for Aic8800Sdio {
@@ -257,78 +357,50 @@ impl SdioOps for Aic8800Sdio {
         if n > buf.len() {
             return Err(-22);
         }
-        // Aic8801：IPC 走 F1 rd_fifo；LicheeRV 在中断里先读 F1 BLOCK_CNT(0x12)，有数据再读 rd_fifo
-        let base = if self.product_id == ProductId::Aic8801 {
-            FUNC1_BASE + u32::from(self.rd_fifo_offset)
-        } else if msg == 0 {
+        // 8801 / V3 以及 DC/DW 的 F2 消息：无 SDIO 中断时轮询，与 LicheeRV 中断处理一致先读 BLOCK_CNT / MISC_INT_STATUS 定长，
+        // 有数据再读对应 function 的 rd_fifo
+        if self.rx_len_from_regs() || (msg != 0 && self.msg_func() == 2) {
+            let data_len = self.rx_pending_len()?;
+            if data_len == 0 {
+                return Ok(0);
+            }
+            // 应读满 data_len 以排空 RD_FIFO；size 不足时截断，整包接收用 rx_pending_len + read_rx_fifo
+            let read_len = min(n, data_len);
+            log::info!(target: "wireless::bsp::sdio", "recv_pkt: data_len={} read_len={}", data_len, read_len);
+            return self.read_rx_fifo(&mut buf[..read_len]);
+        }
+        let base = if msg == 0 {
             FUNC1_BASE + u32::from(self.rd_fifo_offset)
         } else {
             FUNC2_BASE + u32::from(self.rd_fifo_offset)
         };
-        // 无 SDIO 中断时轮询：8801 与 LicheeRV aicsdio.c 1449-1472 一致，先读 F1 BLOCK_CNT(0x12)，非 0 再定长并读 rd_fifo
-        if self.product_id == ProductId::Aic8801 {
-            // LicheeRV 8801/DC/DW：先读 block_cnt_reg(0x12)，while(intstatus){ data_len=intstatus*512; 若 intstatus>=64 再读 bytemode_len(0x02) 用 byte_len*4 }
-            // 必须用 read_byte_at_func(1, reg)：backend.read_byte(addr) 始终 fn=0，若用 read_byte(0x112) 会误读 F0 导致恒得 0
-            const BLOCKSIZE: usize = 512;
-            const BYTEMODE_THRESH: u8 = 64; // LicheeRV: intstatus < 64 用 block_cnt*512，>=64 用 bytemode_len*4
-            let block_cnt = self.host.read_byte_at_func(1, reg::BLOCK_CNT as u32)?;
-            if block_cnt == 0 {
-                return Ok(0);
-            }
-            let data_len = if block_cnt >= BYTEMODE_THRESH {
-                let byte_len = self.host.read_byte_at_func(1, reg::BYTEMODE_LEN as u32)?;
-                (byte_len as usize) * 4
-            } else {
-                (block_cnt as usize) * BLOCKSIZE
-            };
-            // 必须读满 data_len 以排空 RD_FIFO，否则芯片可能不响应下一包（LicheeRV 用 data_len 分配 skb 并读满）
-            let read_len = min(n, data_len);
-            log::info!(target: "wireless::bsp::sdio", "recv_pkt: F1 block_cnt(0x12)={} data_len={} read_len={}", block_cnt, data_len, read_len);
-            return self.host.read_block(base, &mut buf[..read_len]);
+        self.host.read_block(base, &mut buf[..n])
+    }
+
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        if self.is_v3() {
+            self.v3_rx_data_len()
         } else {
-            self.host.read_block(base, &mut buf[..n])
+            self.block_cnt_rx_len(self.msg_func())
         }
     }
 
+    fn read_rx_fifo(&self, buf: &mut [u8]) -> Result<usize, i32> {
+        let base = if self.msg_func() == 2 { FUNC2_BASE } else { FUNC1_BASE };
+        self.host.read_block(base + u32::from(self.rd_fifo_offset), buf)
+    }
+
     fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
         let addr = FUNC1_BASE + u32::from(self.wr_fifo_offset);
         self.host.write_block(addr, &buf[..count])
     }
 
-    /// IPC 消息：Aic8801 走 F1 wr_fifo（aicsdio.c 8801 用 aicwf_sdio_send_pkt），8800DC/DW 走 F2 reg 7（send_msg）。
-    /// Aic8801 发送前须等 F1 FLOW_CTRL(0x0A) 表示有缓冲空间（与 LicheeRV aicwf_sdio_flow_ctrl + aicwf_sdio_tx_msg 完全一致）：
-    /// 50 次重试、递增延时；未就绪则返回 -110 不写 WR_FIFO，避免 CMD53 超时。
+    /// IPC 消息：Aic8801 与 V3（D80/D80X2）走 F1 wr_fifo（aicsdio.c aicwf_sdio_send_pkt），8800DC/DW 走 F2 reg 7（send_msg）。
+    /// F1 发送前须等 FLOW_CTRL（V3 为 FLOW_CTRL_Q1）表示有缓冲空间（与 LicheeRV aicwf_sdio_flow_ctrl + aicwf_sdio_tx_msg 完全一致）。
     fn send_msg(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
-        let addr = if self.product_id == ProductId::Aic8801 {
-            // 与 LicheeRV aicwf_sdio_tx_msg(aicsdio.c 979-1001) 完全一致：buffer_cnt = flow_ctrl(); while ((buffer_cnt<=0 || (buffer_cnt>0 && len>buffer_cnt*BUFFER_SIZE)) && retry<10) { retry++; buffer_cnt = flow_ctrl(); }; 再判断 buffer_cnt>0 && len<(buffer_cnt*BUFFER_SIZE) 才 send_pkt
-            let mut buffer_cnt: i32 = 0;
-            for retry in 0..10u8 {
-                let mut last_fc: u8 = 0;
-                for i in 0..FLOW_CTRL_RETRY_COUNT {
-                    let fc = self.host.read_byte_at_func(1, reg::FLOW_CTRL as u32)?;
-                    last_fc = fc & reg::FLOWCTRL_MASK;
-                    if last_fc != 0 {
-                        break;
-                    }
-                    if i < 30 {
-                        crate::delay_spin_us(200);
-                    } else if i < 40 {
-                        axtask::sleep(core::time::Duration::from_millis(1));
-                    } else {
-                        axtask::sleep(core::time::Duration::from_millis(10));
-                    }
-                }
-                buffer_cnt = last_fc as i32;
-                if buffer_cnt > 0 && count < buffer_cnt as usize * BUFFER_SIZE {
-                    break;
-                }
-                if retry == 9 {
-                    return Err(-110);
-                }
-            }
-            if buffer_cnt <= 0 || count >= buffer_cnt as usize * BUFFER_SIZE {
-                return Err(-110);
-            }
+        let addr = if self.product_id == ProductId::Aic8801 || self.is_v3() {
+            let (_, _, flow_ctrl_reg) = self.f1_status_regs();
+            self.wait_flow_ctrl(flow_ctrl_reg, count)?;
             FUNC1_BASE + u32::from(self.wr_fifo_offset)
         } else {
             FUNC2_BASE + FUNC2_MSG_ADDR_OFFSET
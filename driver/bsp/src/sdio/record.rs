@@ -0,0 +1,689 @@
+//! SDIO 访问录制与回放（主机侧回归测试用）
+//!
+//! `SdioRecorder` 包装任意 SdioOps，把每次访问按调用顺序记为一条 `SdioTraceOp`：
+//! 单字节读写（readb/writeb、func2、read_byte/write_byte、read_byte_f0、read_byte_at_func）与 rx_pending_len 对应 CMD52，
+//! recv_pkt/read_rx_fifo/send_pkt/send_msg/read_block/write_block 对应 CMD53（Aic8800Sdio 内部的 BLOCK_CNT、FLOW_CTRL 查询不单独记录）。
+//! `SdioTrace::to_text` 每条访问一行，可直接存成文件；`SdioReplay` 按顺序回放读数据并比对写数据，
+//! 板上一次成功启动的 trace 即可作为 serialize_8801、ipc_send_len_8801 与初始化寄存器序列的回归用例。
+//! 板上录制见 feature "record" 的 aicbsp_sdio_record_start；checked-in 的 trace 放在 driver/bsp/fixtures/。
+//!
+//! 文本格式（`#` 开头为注释）：`<op> <addr> <arg> <status> <len>:<hex>`
+//! - addr：寄存器偏移或完整 SDIO 地址；recv_pkt 为 msg 参数
+//! - arg：read_byte_at_func 的 func；recv_pkt 的 size；read_rx_fifo 的读长度；send_pkt/send_msg 的 count；其余为 0
+//! - rx_pending_len 的 data 为 4B 小端长度
+//! - status：`ok` 或负 errno
+//! - hex 省略末尾的 0（send_msg 按 512 对齐填充，trace 因此很短），len 为完整长度
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+use spin::Mutex;
+
+use super::ops::SdioOps;
+
+/// 回放不一致时返回给驱动的错误码（-EIO）
+const REPLAY_MISMATCH_ERR: i32 = -5;
+
+/// 一次 SdioOps 访问的种类（与 SdioOps 方法一一对应）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SdioOpKind {
+    Readb,
+    Writeb,
+    ReadbFunc2,
+    WritebFunc2,
+    ReadByte,
+    WriteByte,
+    ReadByteF0,
+    ReadByteAtFunc,
+    RecvPkt,
+    RxPendingLen,
+    ReadRxFifo,
+    SendPkt,
+    SendMsg,
+    ReadBlock,
+    WriteBlock,
+}
+
+impl SdioOpKind {
+    const ALL: [SdioOpKind; 15] = [
+        SdioOpKind::Readb,
+        SdioOpKind::Writeb,
+        SdioOpKind::ReadbFunc2,
+        SdioOpKind::WritebFunc2,
+        SdioOpKind::ReadByte,
+        SdioOpKind::WriteByte,
+        SdioOpKind::ReadByteF0,
+        SdioOpKind::ReadByteAtFunc,
+        SdioOpKind::RecvPkt,
+        SdioOpKind::RxPendingLen,
+        SdioOpKind::ReadRxFifo,
+        SdioOpKind::SendPkt,
+        SdioOpKind::SendMsg,
+        SdioOpKind::ReadBlock,
+        SdioOpKind::WriteBlock,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SdioOpKind::Readb => "readb",
+            SdioOpKind::Writeb => "writeb",
+            SdioOpKind::ReadbFunc2 => "readb_func2",
+            SdioOpKind::WritebFunc2 => "writeb_func2",
+            SdioOpKind::ReadByte => "read_byte",
+            SdioOpKind::WriteByte => "write_byte",
+            SdioOpKind::ReadByteF0 => "read_byte_f0",
+            SdioOpKind::ReadByteAtFunc => "read_byte_at_func",
+            SdioOpKind::RecvPkt => "recv_pkt",
+            SdioOpKind::RxPendingLen => "rx_pending_len",
+            SdioOpKind::ReadRxFifo => "read_rx_fifo",
+            SdioOpKind::SendPkt => "send_pkt",
+            SdioOpKind::SendMsg => "send_msg",
+            SdioOpKind::ReadBlock => "read_block",
+            SdioOpKind::WriteBlock => "write_block",
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|k| k.name() == s)
+    }
+
+    /// 主机写设备（data 为主机写出的数据）；否则 data 为设备返回的数据
+    pub fn is_write(self) -> bool {
+        matches!(
+            self,
+            SdioOpKind::Writeb
+                | SdioOpKind::WritebFunc2
+                | SdioOpKind::WriteByte
+                | SdioOpKind::SendPkt
+                | SdioOpKind::SendMsg
+                | SdioOpKind::WriteBlock
+        )
+    }
+
+    /// CMD53 访问（其余为 CMD52）
+    pub fn is_cmd53(self) -> bool {
+        matches!(
+            self,
+            SdioOpKind::RecvPkt
+                | SdioOpKind::ReadRxFifo
+                | SdioOpKind::SendPkt
+                | SdioOpKind::SendMsg
+                | SdioOpKind::ReadBlock
+                | SdioOpKind::WriteBlock
+        )
+    }
+
+    /// RX 轮询（recv_pkt、rx_pending_len）：落空次数取决于时序
+    fn is_rx_poll(self) -> bool {
+        matches!(self, SdioOpKind::RecvPkt | SdioOpKind::RxPendingLen)
+    }
+}
+
+/// 一条录制的访问
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdioTraceOp {
+    pub kind: SdioOpKind,
+    pub addr: u32,
+    pub arg: u32,
+    /// 写：主机写出的数据；读：设备返回的数据（出错时为空）
+    pub data: Vec<u8>,
+    /// Ok 或访问返回的负 errno
+    pub status: Result<(), i32>,
+}
+
+impl SdioTraceOp {
+    /// RX 轮询落空（recv_pkt 无数据、rx_pending_len 为 0）；回放时允许多出或缺少这类访问
+    fn is_empty_poll(&self) -> bool {
+        self.kind.is_rx_poll() && self.status.is_ok() && self.data.iter().all(|&b| b == 0)
+    }
+
+    /// 写 kind/addr/arg/data 与录制一致；读只比较 kind/addr/arg
+    fn matches(&self, kind: SdioOpKind, addr: u32, arg: u32, wdata: &[u8]) -> bool {
+        self.kind == kind && self.addr == addr && self.arg == arg && (!kind.is_write() || self.data == wdata)
+    }
+}
+
+impl fmt::Display for SdioTraceOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:#x} {} ", self.kind.name(), self.addr, self.arg)?;
+        match self.status {
+            Ok(()) => f.write_str("ok")?,
+            Err(e) => write!(f, "{}", e)?,
+        }
+        write!(f, " {}:", self.data.len())?;
+        let used = self.data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        for b in &self.data[..used] {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// trace 文本解析失败（line 从 1 起）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SdioTraceParseError {
+    pub line: usize,
+}
+
+impl fmt::Display for SdioTraceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sdio trace: malformed line {}", self.line)
+    }
+}
+
+/// 一次录制的全部访问
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdioTrace {
+    pub ops: Vec<SdioTraceOp>,
+}
+
+impl SdioTrace {
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// 每条访问一行
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        for op in &self.ops {
+            let _ = writeln!(s, "{}", op);
+        }
+        s
+    }
+
+    /// 解析 to_text 的输出（忽略空行与 `#` 注释）
+    pub fn parse(text: &str) -> Result<Self, SdioTraceParseError> {
+        let mut ops = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            ops.push(parse_op(line).ok_or(SdioTraceParseError { line: i + 1 })?);
+        }
+        Ok(Self { ops })
+    }
+}
+
+fn parse_u32(s: &str) -> Option<u32> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn parse_op(line: &str) -> Option<SdioTraceOp> {
+    let mut it = line.split_whitespace();
+    let kind = SdioOpKind::from_name(it.next()?)?;
+    let addr = parse_u32(it.next()?)?;
+    let arg = parse_u32(it.next()?)?;
+    let status = match it.next()? {
+        "ok" => Ok(()),
+        e => Err(e.parse::<i32>().ok()?),
+    };
+    let (len, hex) = it.next()?.split_once(':')?;
+    if it.next().is_some() || hex.len() % 2 != 0 {
+        return None;
+    }
+    let len: usize = len.parse().ok()?;
+    let mut data = Vec::with_capacity(len);
+    for i in (0..hex.len()).step_by(2) {
+        data.push(u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()?);
+    }
+    if data.len() > len {
+        return None;
+    }
+    data.resize(len, 0);
+    Some(SdioTraceOp { kind, addr, arg, data, status })
+}
+
+/// 录制包装：所有访问透传给 inner 并按顺序记录
+pub struct SdioRecorder<S: SdioOps> {
+    inner: S,
+    ops: Mutex<Vec<SdioTraceOp>>,
+}
+
+impl<S: SdioOps> SdioRecorder<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner, ops: Mutex::new(Vec::new()) }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// 取走目前为止的 trace（录制继续）
+    pub fn take_trace(&self) -> SdioTrace {
+        SdioTrace { ops: core::mem::take(&mut *self.ops.lock()) }
+    }
+
+    pub fn into_parts(self) -> (S, SdioTrace) {
+        (self.inner, SdioTrace { ops: self.ops.into_inner() })
+    }
+
+    fn push(&self, kind: SdioOpKind, addr: u32, arg: u32, data: &[u8], status: Result<(), i32>) {
+        self.ops.lock().push(SdioTraceOp { kind, addr, arg, data: data.to_vec(), status });
+    }
+
+    fn rec_read_u8(&self, kind: SdioOpKind, addr: u32, arg: u32, r: Result<u8, i32>) -> Result<u8, i32> {
+        match r {
+            Ok(v) => self.push(kind, addr, arg, &[v], Ok(())),
+            Err(e) => self.push(kind, addr, arg, &[], Err(e)),
+        }
+        r
+    }
+
+    fn rec_write(&self, kind: SdioOpKind, addr: u32, arg: u32, data: &[u8], r: Result<(), i32>) -> Result<(), i32> {
+        self.push(kind, addr, arg, data, r);
+        r
+    }
+}
+
+impl<S: SdioOps> SdioOps for SdioRecorder<S> {
+    fn writeb(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        self.rec_write(SdioOpKind::Writeb, regaddr, 0, &[val], self.inner.writeb(regaddr, val))
+    }
+    fn readb(&self, regaddr: u32) -> Result<u8, i32> {
+        self.rec_read_u8(SdioOpKind::Readb, regaddr, 0, self.inner.readb(regaddr))
+    }
+    fn readb_func2(&self, regaddr: u32) -> Result<u8, i32> {
+        self.rec_read_u8(SdioOpKind::ReadbFunc2, regaddr, 0, self.inner.readb_func2(regaddr))
+    }
+    fn writeb_func2(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        self.rec_write(SdioOpKind::WritebFunc2, regaddr, 0, &[val], self.inner.writeb_func2(regaddr, val))
+    }
+    fn read_byte(&self, addr: u32) -> Result<u8, i32> {
+        self.rec_read_u8(SdioOpKind::ReadByte, addr, 0, self.inner.read_byte(addr))
+    }
+    fn read_byte_f0(&self, addr: u32) -> Result<u8, i32> {
+        self.rec_read_u8(SdioOpKind::ReadByteF0, addr, 0, self.inner.read_byte_f0(addr))
+    }
+    fn read_byte_at_func(&self, func_num: u8, reg_addr: u32) -> Result<u8, i32> {
+        let r = self.inner.read_byte_at_func(func_num, reg_addr);
+        self.rec_read_u8(SdioOpKind::ReadByteAtFunc, reg_addr, func_num as u32, r)
+    }
+    fn write_byte(&self, addr: u32, val: u8) -> Result<(), i32> {
+        self.rec_write(SdioOpKind::WriteByte, addr, 0, &[val], self.inner.write_byte(addr, val))
+    }
+    fn recv_pkt(&self, buf: &mut [u8], size: u32, msg: u8) -> Result<usize, i32> {
+        let r = self.inner.recv_pkt(buf, size, msg);
+        match r {
+            Ok(n) => self.push(SdioOpKind::RecvPkt, msg as u32, size, &buf[..n.min(buf.len())], Ok(())),
+            Err(e) => self.push(SdioOpKind::RecvPkt, msg as u32, size, &[], Err(e)),
+        }
+        r
+    }
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        let r = self.inner.rx_pending_len();
+        match r {
+            Ok(len) => self.push(SdioOpKind::RxPendingLen, 0, 0, &(len as u32).to_le_bytes(), Ok(())),
+            Err(e) => self.push(SdioOpKind::RxPendingLen, 0, 0, &[], Err(e)),
+        }
+        r
+    }
+    fn read_rx_fifo(&self, buf: &mut [u8]) -> Result<usize, i32> {
+        let len = buf.len() as u32;
+        let r = self.inner.read_rx_fifo(buf);
+        match r {
+            Ok(n) => self.push(SdioOpKind::ReadRxFifo, 0, len, &buf[..n.min(buf.len())], Ok(())),
+            Err(e) => self.push(SdioOpKind::ReadRxFifo, 0, len, &[], Err(e)),
+        }
+        r
+    }
+    fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        let r = self.inner.send_pkt(buf, count);
+        self.push(SdioOpKind::SendPkt, 0, count as u32, &buf[..count.min(buf.len())], r.map(|_| ()));
+        r
+    }
+    fn send_msg(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        let r = self.inner.send_msg(buf, count);
+        self.push(SdioOpKind::SendMsg, 0, count as u32, &buf[..count.min(buf.len())], r.map(|_| ()));
+        r
+    }
+    fn read_block(&self, addr: u32, buf: &mut [u8]) -> Result<usize, i32> {
+        let r = self.inner.read_block(addr, buf);
+        match r {
+            Ok(n) => self.push(SdioOpKind::ReadBlock, addr, 0, &buf[..n.min(buf.len())], Ok(())),
+            Err(e) => self.push(SdioOpKind::ReadBlock, addr, 0, &[], Err(e)),
+        }
+        r
+    }
+    fn write_block(&self, addr: u32, buf: &[u8]) -> Result<usize, i32> {
+        let r = self.inner.write_block(addr, buf);
+        self.push(SdioOpKind::WriteBlock, addr, 0, buf, r.map(|_| ()));
+        r
+    }
+}
+
+/// 回放与录制不一致
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SdioReplayError {
+    /// 第 index 条访问不一致：expected 为录制内容（trace 已用完时为 None）
+    Mismatch { index: usize, expected: Option<SdioTraceOp>, got: SdioTraceOp },
+    /// 驱动结束时仍有未回放的访问（不计 RX 轮询落空）
+    Unconsumed { index: usize, remaining: usize },
+}
+
+impl fmt::Display for SdioReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdioReplayError::Mismatch { index, expected: Some(exp), got } => {
+                write!(f, "sdio replay: op {} mismatch\n  expected: {}\n  got:      {}", index, exp, got)
+            }
+            SdioReplayError::Mismatch { index, expected: None, got } => {
+                write!(f, "sdio replay: op {} past end of trace: {}", index, got)
+            }
+            SdioReplayError::Unconsumed { index, remaining } => {
+                write!(f, "sdio replay: {} ops left unconsumed from op {}", remaining, index)
+            }
+        }
+    }
+}
+
+struct ReplayState {
+    ops: VecDeque<SdioTraceOp>,
+    /// 下一条录制访问在原 trace 中的序号
+    index: usize,
+    error: Option<SdioReplayError>,
+}
+
+/// 回放后端：按录制顺序返回读数据、返回码，并比对写数据。
+///
+/// 板上录制时 RX 轮询落空（recv_pkt 返回 0）的次数取决于时序，回放时这类访问可多可少：
+/// 驱动多轮询一次即返回 0，驱动少轮询则跳过录制中的空轮询。第一次不一致后所有访问返回 -EIO，
+/// 结果由 `finish` 报告。
+pub struct SdioReplay {
+    state: Mutex<ReplayState>,
+}
+
+impl SdioReplay {
+    pub fn new(trace: SdioTrace) -> Self {
+        Self {
+            state: Mutex::new(ReplayState { ops: trace.ops.into(), index: 0, error: None }),
+        }
+    }
+
+    /// 剩余未回放的访问数
+    pub fn remaining(&self) -> usize {
+        self.state.lock().ops.len()
+    }
+
+    /// 第一次不一致（若有）
+    pub fn error(&self) -> Option<SdioReplayError> {
+        self.state.lock().error.clone()
+    }
+
+    /// 驱动跑完后调用：无不一致且 trace 已回放完（末尾空轮询除外）时返回 Ok
+    pub fn finish(&self) -> Result<(), SdioReplayError> {
+        let st = self.state.lock();
+        if let Some(e) = &st.error {
+            return Err(e.clone());
+        }
+        let remaining = st.ops.iter().filter(|op| !op.is_empty_poll()).count();
+        if remaining != 0 {
+            return Err(SdioReplayError::Unconsumed { index: st.index, remaining });
+        }
+        Ok(())
+    }
+
+    /// 取下一条与 (kind, addr, arg, wdata) 对应的录制访问；返回 None 表示不一致（已记录）或驱动多出的空轮询
+    fn next(&self, kind: SdioOpKind, addr: u32, arg: u32, wdata: &[u8]) -> Result<Option<SdioTraceOp>, i32> {
+        let mut st = self.state.lock();
+        if st.error.is_some() {
+            return Err(REPLAY_MISMATCH_ERR);
+        }
+        while st.ops.front().is_some_and(|op| op.is_empty_poll() && op.kind != kind) {
+            st.ops.pop_front();
+            st.index += 1;
+        }
+        match st.ops.front() {
+            Some(op) if op.matches(kind, addr, arg, wdata) => {
+                st.index += 1;
+                Ok(st.ops.pop_front())
+            }
+            // 录制中此处没有轮询：驱动多轮询一次，按无数据处理
+            front if kind.is_rx_poll() && front.is_none_or(|op| op.kind != kind) => Ok(None),
+            front => {
+                let got = SdioTraceOp { kind, addr, arg, data: wdata.to_vec(), status: Ok(()) };
+                let e = SdioReplayError::Mismatch { index: st.index, expected: front.cloned(), got };
+                log::error!(target: "wireless::bsp::sdio", "{}", e);
+                st.error = Some(e);
+                Err(REPLAY_MISMATCH_ERR)
+            }
+        }
+    }
+
+    fn replay_read_u8(&self, kind: SdioOpKind, addr: u32, arg: u32) -> Result<u8, i32> {
+        let op = self.next(kind, addr, arg, &[])?.ok_or(REPLAY_MISMATCH_ERR)?;
+        op.status?;
+        op.data.first().copied().ok_or(REPLAY_MISMATCH_ERR)
+    }
+
+    fn replay_write(&self, kind: SdioOpKind, addr: u32, arg: u32, data: &[u8]) -> Result<(), i32> {
+        self.next(kind, addr, arg, data)?.ok_or(REPLAY_MISMATCH_ERR)?.status
+    }
+
+    fn replay_read_buf(&self, kind: SdioOpKind, addr: u32, arg: u32, buf: &mut [u8]) -> Result<usize, i32> {
+        let Some(op) = self.next(kind, addr, arg, &[])? else {
+            return Ok(0);
+        };
+        op.status?;
+        let n = op.data.len().min(buf.len());
+        buf[..n].copy_from_slice(&op.data[..n]);
+        Ok(n)
+    }
+}
+
+impl SdioOps for SdioReplay {
+    fn writeb(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        self.replay_write(SdioOpKind::Writeb, regaddr, 0, &[val])
+    }
+    fn readb(&self, regaddr: u32) -> Result<u8, i32> {
+        self.replay_read_u8(SdioOpKind::Readb, regaddr, 0)
+    }
+    fn readb_func2(&self, regaddr: u32) -> Result<u8, i32> {
+        self.replay_read_u8(SdioOpKind::ReadbFunc2, regaddr, 0)
+    }
+    fn writeb_func2(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+        self.replay_write(SdioOpKind::WritebFunc2, regaddr, 0, &[val])
+    }
+    fn read_byte(&self, addr: u32) -> Result<u8, i32> {
+        self.replay_read_u8(SdioOpKind::ReadByte, addr, 0)
+    }
+    fn read_byte_f0(&self, addr: u32) -> Result<u8, i32> {
+        self.replay_read_u8(SdioOpKind::ReadByteF0, addr, 0)
+    }
+    fn read_byte_at_func(&self, func_num: u8, reg_addr: u32) -> Result<u8, i32> {
+        self.replay_read_u8(SdioOpKind::ReadByteAtFunc, reg_addr, func_num as u32)
+    }
+    fn write_byte(&self, addr: u32, val: u8) -> Result<(), i32> {
+        self.replay_write(SdioOpKind::WriteByte, addr, 0, &[val])
+    }
+    fn recv_pkt(&self, buf: &mut [u8], size: u32, msg: u8) -> Result<usize, i32> {
+        self.replay_read_buf(SdioOpKind::RecvPkt, msg as u32, size, buf)
+    }
+    fn rx_pending_len(&self) -> Result<usize, i32> {
+        let Some(op) = self.next(SdioOpKind::RxPendingLen, 0, 0, &[])? else {
+            return Ok(0);
+        };
+        op.status?;
+        let len: [u8; 4] = op.data.as_slice().try_into().map_err(|_| REPLAY_MISMATCH_ERR)?;
+        Ok(u32::from_le_bytes(len) as usize)
+    }
+    fn read_rx_fifo(&self, buf: &mut [u8]) -> Result<usize, i32> {
+        let len = buf.len() as u32;
+        self.replay_read_buf(SdioOpKind::ReadRxFifo, 0, len, buf)
+    }
+    fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        self.replay_write(SdioOpKind::SendPkt, 0, count as u32, &buf[..count.min(buf.len())]).map(|_| count)
+    }
+    fn send_msg(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+        self.replay_write(SdioOpKind::SendMsg, 0, count as u32, &buf[..count.min(buf.len())]).map(|_| count)
+    }
+    fn read_block(&self, addr: u32, buf: &mut [u8]) -> Result<usize, i32> {
+        self.replay_read_buf(SdioOpKind::ReadBlock, addr, 0, buf)
+    }
+    fn write_block(&self, addr: u32, buf: &[u8]) -> Result<usize, i32> {
+        self.replay_write(SdioOpKind::WriteBlock, addr, 0, buf).map(|_| buf.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::AicBspInfo;
+    use crate::fw_load::DBG_MEM_READ_REQ;
+    use crate::sdio::emu::EmuBootrom8801;
+    use crate::sdio::types::{reg, ProductId};
+    use alloc::sync::Arc;
+    use axerrno::{AxError, AxResult};
+
+    /// 把 dev 挂为 8801 软件设备跑 aicbsp_driver_fw_init；调用方须持 GLOBAL_TEST_LOCK
+    fn boot<S: SdioOps + Send + Sync + 'static>(dev: &Arc<S>, info: &mut AicBspInfo) -> AxResult<()> {
+        crate::sdio::driver_fw_init_on_sim_for_test(dev.clone(), ProductId::Aic8801, info)
+    }
+
+    fn record_boot(chip_rev: u8) -> (SdioTrace, AxResult<()>) {
+        let rec = Arc::new(SdioRecorder::new(EmuBootrom8801::new(chip_rev)));
+        let r = boot(&rec, &mut AicBspInfo::default());
+        (rec.take_trace(), r)
+    }
+
+    #[test]
+    fn text_round_trip_trims_trailing_zeros() {
+        let mut padded = alloc::vec![0x14, 0x00, 0x11, 0x00, 0x2a];
+        padded.resize(512, 0);
+        let trace = SdioTrace {
+            ops: alloc::vec![
+                SdioTraceOp { kind: SdioOpKind::Readb, addr: reg::BLOCK_CNT as u32, arg: 0, data: alloc::vec![1], status: Ok(()) },
+                SdioTraceOp { kind: SdioOpKind::SendMsg, addr: 0, arg: 512, data: padded, status: Ok(()) },
+                SdioTraceOp { kind: SdioOpKind::RecvPkt, addr: 1, arg: 1536, data: Vec::new(), status: Err(-110) },
+                SdioTraceOp { kind: SdioOpKind::ReadByteAtFunc, addr: 0x1000, arg: 1, data: alloc::vec![0], status: Ok(()) },
+            ],
+        };
+        let text = trace.to_text();
+        assert_eq!(text.lines().nth(1), Some("send_msg 0x0 512 ok 512:140011002a"));
+        assert_eq!(text.lines().nth(2), Some("recv_pkt 0x1 1536 -110 0:"));
+        assert_eq!(SdioTrace::parse(&alloc::format!("# header\n\n{}", text)), Ok(trace));
+        assert_eq!(SdioTrace::parse("readb 0x12 0 ok 1:01\nwriteb 0x12 zz ok 1:01"), Err(SdioTraceParseError { line: 2 }));
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_boot_failure() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        // chip_rev 1 不受支持：一次 DBG_MEM_READ 后返回 -EINVAL
+        let (trace, r) = record_boot(1);
+        assert_eq!(r, Err(AxError::BadState));
+        assert!(trace.ops.iter().any(|op| op.kind == SdioOpKind::SendMsg));
+
+        let replay = Arc::new(SdioReplay::new(SdioTrace::parse(&trace.to_text()).unwrap()));
+        let mut info = AicBspInfo::default();
+        assert_eq!(boot(&replay, &mut info), Err(AxError::BadState));
+        assert_eq!(info.chip_rev, 1);
+        assert_eq!(replay.finish(), Ok(()));
+    }
+
+    #[test]
+    fn replay_detects_changed_write() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let (mut trace, _) = record_boot(1);
+        let i = trace.ops.iter().position(|op| op.kind == SdioOpKind::SendMsg).unwrap();
+        // 相当于 serialize_8801 改了 lmac_msg 的 id
+        trace.ops[i].data[8] ^= 1;
+        assert_eq!(u16::from_le_bytes([trace.ops[i].data[8] ^ 1, trace.ops[i].data[9]]), DBG_MEM_READ_REQ);
+
+        let replay = Arc::new(SdioReplay::new(trace));
+        let mut info = AicBspInfo::default();
+        assert!(boot(&replay, &mut info).is_err());
+        match replay.finish() {
+            Err(SdioReplayError::Mismatch { index, expected: Some(exp), got }) => {
+                assert_eq!(index, i);
+                assert_eq!(exp.kind, SdioOpKind::SendMsg);
+                assert_eq!(got.data[8], exp.data[8] ^ 1);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn replay_tolerates_extra_and_missing_empty_polls() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let (trace, _) = record_boot(1);
+        let i = trace.ops.iter().position(|op| op.kind == SdioOpKind::RxPendingLen).unwrap();
+        let empty = SdioTraceOp { data: alloc::vec![0; 4], ..trace.ops[i].clone() };
+
+        // 录制里多了几次空轮询（板上 CFM 来得慢）
+        let mut slow = trace.clone();
+        for _ in 0..3 {
+            slow.ops.insert(i, empty.clone());
+        }
+        slow.ops.push(empty);
+        let replay = Arc::new(SdioReplay::new(slow));
+        assert_eq!(boot(&replay, &mut AicBspInfo::default()), Err(AxError::BadState));
+        assert_eq!(replay.finish(), Ok(()));
+
+        // 驱动在 trace 已用完后仍轮询：返回无数据
+        let replay = Arc::new(SdioReplay::new(trace));
+        assert_eq!(boot(&replay, &mut AicBspInfo::default()), Err(AxError::BadState));
+        let mut buf = [0u8; 64];
+        assert_eq!(replay.rx_pending_len(), Ok(0));
+        assert_eq!(replay.recv_pkt(&mut buf, 64, 1), Ok(0));
+        assert_eq!(replay.finish(), Ok(()));
+        // 但多出的写仍是不一致
+        assert_eq!(replay.writeb(reg::WAKEUP as u32, 1), Err(REPLAY_MISMATCH_ERR));
+        assert!(matches!(replay.finish(), Err(SdioReplayError::Mismatch { expected: None, .. })));
+    }
+
+    #[test]
+    fn replay_reports_unconsumed_ops() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let (mut trace, _) = record_boot(1);
+        trace.ops.push(SdioTraceOp { kind: SdioOpKind::Readb, addr: reg::BLOCK_CNT as u32, arg: 0, data: alloc::vec![0], status: Ok(()) });
+        let n = trace.len();
+        let replay = Arc::new(SdioReplay::new(trace));
+        let _ = boot(&replay, &mut AicBspInfo::default());
+        assert_eq!(replay.finish(), Err(SdioReplayError::Unconsumed { index: n - 1, remaining: 1 }));
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn full_8801_boot_replays_from_text() {
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let (trace, r) = record_boot(3);
+        r.unwrap();
+        let text = trace.to_text();
+        let replay = Arc::new(SdioReplay::new(SdioTrace::parse(&text).unwrap()));
+        let mut info = AicBspInfo::default();
+        boot(&replay, &mut info).unwrap();
+        assert_eq!(info.chip_rev, 3);
+        assert_eq!(replay.finish(), Ok(()));
+    }
+
+    #[cfg(feature = "embed_firmware_8801")]
+    #[test]
+    fn checked_in_trace_replays() {
+        use crate::fw_load::{DBG_MEM_BLOCK_WRITE_REQ, DBG_START_APP_REQ};
+        let _g = crate::sdio::GLOBAL_TEST_LOCK.lock();
+        let trace = SdioTrace::parse(include_str!("../../fixtures/sdio_8801_u02_emu_full_boot.trace")).unwrap();
+        let block_writes = trace
+            .ops
+            .iter()
+            .filter(|op| op.kind == SdioOpKind::SendMsg && op.data.get(8..10) == Some(&DBG_MEM_BLOCK_WRITE_REQ.to_le_bytes()[..]))
+            .count();
+        assert_eq!(block_writes, 316);
+        let start_app = trace.ops.iter().rev().find(|op| op.kind == SdioOpKind::SendMsg).unwrap();
+        assert_eq!(start_app.data[8..10], DBG_START_APP_REQ.to_le_bytes());
+
+        let replay = Arc::new(SdioReplay::new(trace));
+        let mut info = AicBspInfo::default();
+        boot(&replay, &mut info).unwrap();
+        assert_eq!(info.chip_rev, 3);
+        assert_eq!(replay.finish(), Ok(()));
+    }
+}
@@ -0,0 +1,421 @@
+//! 数据帧 TX 聚合：多帧拼成一个对齐缓冲，一次 CMD53 多块写出
+//! 对照 LicheeRV aicwf_sdio.c aicwf_sdio_aggr / aicwf_sdio_send / aicwf_sdio_tx_process
+//!
+//! 每帧：4B SDIO 头（[len 12 位 LE, 0x01 data, 8801/DC/DW 为 0、D80/D80X2 为 crc8]）+ txdesc_api + 802.3 payload，
+//! 按 TX_ALIGNMENT 补 0；8801/DC/DW 头中 len 改写为对齐后长度。整包发送前尾部补 0 到 TXPKT_BLOCKSIZE 整数倍。
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use super::ops::SdioOps;
+use super::types::{reg, reg_v3, ProductId, BUFFER_SIZE};
+
+/// 数据 TX 块大小（TXPKT_BLOCKSIZE）
+pub const TXPKT_BLOCKSIZE: usize = 512;
+/// 聚合缓冲上限（MAX_AGGR_TXPKT_LEN）
+pub const MAX_AGGR_TXPKT_LEN: usize = 1536 * 64;
+/// 数据发送队列长度（aicwf_txrxif.h TXQLEN）
+pub const TXQLEN: usize = 2048;
+/// 单帧（txdesc_api + payload）上限：加 SDIO 头对齐后须放进固件的一个 BUFFER_SIZE 缓冲
+pub const TX_FRAME_MAX_LEN: usize = BUFFER_SIZE - SDIO_HDR_LEN;
+
+/// 帧内 4 字节对齐（TX_ALIGNMENT）
+const TX_ALIGNMENT: usize = 4;
+const SDIO_HDR_LEN: usize = 4;
+/// aicwf_sdio_aggr：sdio_header[2] = 0x01 //data
+const SDIO_TYPE_DATA_TX: u8 = 0x01;
+/// 固件空闲缓冲不多于该值时先重读流控（DATA_FLOW_CTRL_THRESH）
+const DATA_FLOW_CTRL_THRESH: u8 = 2;
+/// 与 aicwf_sdio_flow_ctrl 一致的重试次数
+const FLOW_CTRL_RETRY_COUNT: u32 = 50;
+
+#[inline]
+fn is_v3(product_id: ProductId) -> bool {
+    matches!(product_id, ProductId::Aic8800D80 | ProductId::Aic8800D80X2)
+}
+
+/// crc8_ponl_107（多项式 0x07），D80/D80X2 SDIO 头第 4 字节
+fn crc8_ponl_107(buf: &[u8]) -> u8 {
+    let mut crc: u8 = 0;
+    for &b in buf {
+        let mut i: u8 = 0x80;
+        while i > 0 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+            if b & i != 0 {
+                crc ^= 0x07;
+            }
+            i >>= 1;
+        }
+    }
+    crc
+}
+
+/// 一帧在聚合缓冲中占的长度（SDIO 头 + 帧，按 TX_ALIGNMENT 对齐）
+#[inline]
+fn aggr_frame_len(frame_len: usize) -> usize {
+    (SDIO_HDR_LEN + frame_len).next_multiple_of(TX_ALIGNMENT)
+}
+
+/// 聚合缓冲（tx_priv->aggr_buf、head/tail、aggr_count）
+#[derive(Debug)]
+pub struct TxAggrBuf {
+    product_id: ProductId,
+    buf: Vec<u8>,
+    count: usize,
+}
+
+impl TxAggrBuf {
+    pub fn new(product_id: ProductId) -> Self {
+        Self { product_id, buf: Vec::new(), count: 0 }
+    }
+
+    /// 已聚合帧数（aggr_count）
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 当前长度（tail - head，未补块尾）
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// 再放一帧后补块尾仍不超过 MAX_AGGR_TXPKT_LEN
+    pub fn fits(&self, frame_len: usize) -> bool {
+        (self.buf.len() + aggr_frame_len(frame_len)).next_multiple_of(TXPKT_BLOCKSIZE) <= MAX_AGGR_TXPKT_LEN
+    }
+
+    /// 追加一帧（aicwf_sdio_aggr）。frame 为 txdesc_api + payload
+    pub fn push(&mut self, frame: &[u8]) -> Result<(), i32> {
+        if frame.is_empty() || frame.len() > TX_FRAME_MAX_LEN {
+            return Err(-22);
+        }
+        if !self.fits(frame.len()) {
+            return Err(-105); // -ENOBUFS
+        }
+        if self.buf.capacity() == 0 {
+            self.buf.reserve_exact(MAX_AGGR_TXPKT_LEN);
+        }
+        let start = self.buf.len();
+        let mut hdr = [(frame.len() & 0xff) as u8, ((frame.len() >> 8) & 0x0f) as u8, SDIO_TYPE_DATA_TX, 0];
+        if is_v3(self.product_id) {
+            hdr[3] = crc8_ponl_107(&hdr[..3]);
+        }
+        self.buf.extend_from_slice(&hdr);
+        self.buf.extend_from_slice(frame);
+        self.buf.resize(start + aggr_frame_len(frame.len()), 0);
+        if !is_v3(self.product_id) {
+            let aligned = self.buf.len() - start - SDIO_HDR_LEN;
+            self.buf[start] = (aligned & 0xff) as u8;
+            self.buf[start + 1] = ((aligned >> 8) & 0x0f) as u8;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    /// 尾部补 0 到 TXPKT_BLOCKSIZE 整数倍，返回待发送缓冲（aicwf_sdio_send）
+    pub fn finish(&mut self) -> &[u8] {
+        let len = self.buf.len().next_multiple_of(TXPKT_BLOCKSIZE);
+        self.buf.resize(len, 0);
+        &self.buf
+    }
+
+    /// 发送后复位（head = tail，aggr_count = 0），保留缓冲容量
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.count = 0;
+    }
+}
+
+/// 数据 TX 队列（tx_priv->txq 与统计）：enqueue 与发送方共享，双方都只短暂持锁
+#[derive(Debug, Default)]
+pub struct TxDataPriv {
+    pub txq: VecDeque<Vec<u8>>,
+    /// 已发送帧数 / CMD53 次数
+    pub tx_frames: u64,
+    pub tx_xfers: u64,
+}
+
+impl TxDataPriv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 入队（aicwf_frame_enq），队列满返回 -ENOBUFS
+    pub fn enqueue(&mut self, frame: &[u8]) -> Result<(), i32> {
+        if frame.is_empty() || frame.len() > TX_FRAME_MAX_LEN {
+            return Err(-22);
+        }
+        if self.txq.len() >= TXQLEN {
+            return Err(-105);
+        }
+        self.txq.push_back(frame.to_vec());
+        Ok(())
+    }
+
+    /// 从队首取帧放进 aggr，直到 aggr 有 budget 帧或放不下（aicwf_sdio_aggr 循环）
+    fn drain_into(&mut self, aggr: &mut TxAggrBuf, budget: usize) -> Result<(), i32> {
+        while aggr.count() < budget {
+            match self.txq.front() {
+                Some(frame) if aggr.fits(frame.len()) => {
+                    let frame = self.txq.pop_front().unwrap_or_default();
+                    aggr.push(&frame)?;
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 发送侧状态（fw_avail_bufcnt、aggr_buf），只由 bustx（或 sdio_tx_process_once）使用
+#[derive(Debug)]
+pub struct TxSender {
+    /// 固件可用缓冲数（fw_avail_bufcnt），每发一帧减一，不足时重读 FLOW_CTRL
+    pub fw_avail_bufcnt: u8,
+    pub aggr: TxAggrBuf,
+}
+
+impl TxSender {
+    pub fn new(product_id: ProductId) -> Self {
+        Self { fw_avail_bufcnt: 0, aggr: TxAggrBuf::new(product_id) }
+    }
+
+    /// 读固件空闲缓冲数（aicwf_sdio_flow_ctrl），重试耗尽返回 0
+    fn flow_ctrl(&self, sdio: &dyn SdioOps) -> u8 {
+        let flow_ctrl_reg = if is_v3(self.aggr.product_id) { reg_v3::FLOW_CTRL_Q1 } else { reg::FLOW_CTRL };
+        for i in 0..FLOW_CTRL_RETRY_COUNT {
+            if let Ok(fc) = sdio.readb(flow_ctrl_reg as u32) {
+                let fc = fc & reg::FLOWCTRL_MASK;
+                if fc != 0 {
+                    return fc;
+                }
+            }
+            if i < 30 {
+                crate::delay_spin_us(200);
+            } else if i < 40 {
+                axtask::sleep(core::time::Duration::from_millis(1));
+            } else {
+                axtask::sleep(core::time::Duration::from_millis(10));
+            }
+        }
+        0
+    }
+
+    /// 发送聚合缓冲：一次 send_pkt（CMD53 多块写 WR_FIFO），返回发出的帧数
+    fn send(&mut self, sdio: &dyn SdioOps) -> Result<usize, i32> {
+        let count = self.aggr.count();
+        let buf = self.aggr.finish();
+        let r = sdio.send_pkt(buf, buf.len());
+        self.aggr.reset();
+        r?;
+        self.fw_avail_bufcnt = self.fw_avail_bufcnt.saturating_sub(count as u8);
+        Ok(count)
+    }
+
+    /// 发完队列或固件无缓冲为止（aicwf_sdio_tx_process 数据部分），返回本次发出的帧数。
+    /// 每次聚合帧数受固件空闲缓冲（减去 DATA_FLOW_CTRL_THRESH）与 MAX_AGGR_TXPKT_LEN 限制。
+    /// queue 只在取一批帧与记统计时短暂持有，流控等待与 send_pkt 期间不持锁，enqueue 不会被阻塞。
+    pub fn process(&mut self, queue: &Mutex<Option<TxDataPriv>>, sdio: &dyn SdioOps) -> Result<usize, i32> {
+        let mut sent = 0;
+        while queue.lock().as_ref().is_some_and(|q| !q.txq.is_empty()) {
+            if self.fw_avail_bufcnt <= DATA_FLOW_CTRL_THRESH {
+                self.fw_avail_bufcnt = self.flow_ctrl(sdio);
+                if self.fw_avail_bufcnt <= DATA_FLOW_CTRL_THRESH {
+                    log::debug!(
+                        target: "wireless::bsp::sdio",
+                        "tx_process: fw buffer full, {} frames queued",
+                        queue.lock().as_ref().map_or(0, |q| q.txq.len())
+                    );
+                    break;
+                }
+            }
+            let budget = (self.fw_avail_bufcnt - DATA_FLOW_CTRL_THRESH) as usize;
+            match queue.lock().as_mut() {
+                Some(q) => q.drain_into(&mut self.aggr, budget)?,
+                None => break,
+            }
+            if self.aggr.is_empty() {
+                break;
+            }
+            let count = self.send(sdio)?;
+            if let Some(q) = queue.lock().as_mut() {
+                q.tx_frames += count as u64;
+                q.tx_xfers += 1;
+            }
+            sent += count;
+        }
+        Ok(sent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sdio::emu::EmuBootrom8801;
+
+    fn frame(len: usize, seed: u8) -> Vec<u8> {
+        (0..len).map(|i| seed.wrapping_add(i as u8)).collect()
+    }
+
+    #[test]
+    fn aggr_layout_8801() {
+        let mut aggr = TxAggrBuf::new(ProductId::Aic8801);
+        aggr.push(&frame(61, 1)).unwrap();
+        aggr.push(&frame(100, 2)).unwrap();
+        assert_eq!(aggr.len(), 68 + 104);
+        assert_eq!(aggr.count(), 2);
+        let buf = aggr.finish().to_vec();
+        assert_eq!(buf.len(), TXPKT_BLOCKSIZE);
+        // 8801：len 为对齐后长度
+        assert_eq!(&buf[..4], &[64, 0, SDIO_TYPE_DATA_TX, 0]);
+        assert_eq!(&buf[4..65], &frame(61, 1)[..]);
+        assert_eq!(&buf[65..68], &[0, 0, 0]);
+        assert_eq!(&buf[68..72], &[100, 0, SDIO_TYPE_DATA_TX, 0]);
+        assert!(buf[172..].iter().all(|&b| b == 0));
+        aggr.reset();
+        assert!(aggr.is_empty());
+        assert_eq!(aggr.len(), 0);
+    }
+
+    #[test]
+    fn aggr_header_v3_keeps_len_and_adds_crc() {
+        let mut aggr = TxAggrBuf::new(ProductId::Aic8800D80);
+        aggr.push(&frame(0x123, 0)).unwrap();
+        let buf = aggr.finish();
+        assert_eq!(&buf[..3], &[0x23, 0x01, SDIO_TYPE_DATA_TX]);
+        assert_eq!(buf[3], crc8_ponl_107(&[0x23, 0x01, SDIO_TYPE_DATA_TX]));
+        assert_eq!(crc8_ponl_107(b"123456789"), 0xf4);
+    }
+
+    #[test]
+    fn aggr_rejects_oversized_and_stops_at_max_len() {
+        let mut aggr = TxAggrBuf::new(ProductId::Aic8801);
+        assert_eq!(aggr.push(&[]), Err(-22));
+        assert_eq!(aggr.push(&frame(TX_FRAME_MAX_LEN + 1, 0)), Err(-22));
+        let mut n = 0;
+        while aggr.fits(TX_FRAME_MAX_LEN) {
+            aggr.push(&frame(TX_FRAME_MAX_LEN, 0)).unwrap();
+            n += 1;
+        }
+        assert_eq!(n, MAX_AGGR_TXPKT_LEN / BUFFER_SIZE);
+        assert_eq!(aggr.push(&frame(TX_FRAME_MAX_LEN, 0)), Err(-105));
+        assert!(aggr.finish().len() <= MAX_AGGR_TXPKT_LEN);
+    }
+
+    fn queue_with(frames: impl IntoIterator<Item = Vec<u8>>) -> Mutex<Option<TxDataPriv>> {
+        let mut tx = TxDataPriv::new();
+        for f in frames {
+            tx.enqueue(&f).unwrap();
+        }
+        Mutex::new(Some(tx))
+    }
+
+    #[test]
+    fn process_coalesces_up_to_fw_buffers() {
+        let emu = EmuBootrom8801::new(3);
+        emu.set_flow_ctrl(8);
+        let queue = queue_with((0..10u8).map(|i| frame(60 + i as usize, i)));
+        let mut sender = TxSender::new(ProductId::Aic8801);
+        assert_eq!(sender.process(&queue, &emu), Ok(10));
+        // 8 个空闲缓冲，留 DATA_FLOW_CTRL_THRESH：6 + 4 帧，两次 CMD53
+        assert_eq!(emu.data_xfers(), alloc::vec![6, 4]);
+        let tx = queue.lock().take().unwrap();
+        assert_eq!(tx.tx_xfers, 2);
+        assert_eq!(tx.tx_frames, 10);
+        let frames = emu.data_frames();
+        assert_eq!(frames.len(), 10);
+        for (i, f) in frames.iter().enumerate() {
+            assert_eq!(&f[..60 + i], &frame(60 + i, i as u8)[..]);
+        }
+        assert!(tx.txq.is_empty());
+        assert_eq!(emu.dropped(), 0);
+    }
+
+    #[test]
+    fn process_keeps_frames_queued_without_fw_buffers() {
+        let emu = EmuBootrom8801::new(3);
+        emu.set_flow_ctrl(DATA_FLOW_CTRL_THRESH);
+        let queue = queue_with([frame(64, 0)]);
+        let mut sender = TxSender::new(ProductId::Aic8801);
+        assert_eq!(sender.process(&queue, &emu), Ok(0));
+        assert_eq!(queue.lock().as_ref().unwrap().txq.len(), 1);
+        assert!(emu.data_xfers().is_empty());
+
+        emu.set_flow_ctrl(4);
+        assert_eq!(sender.process(&queue, &emu), Ok(1));
+        assert_eq!(emu.data_xfers(), alloc::vec![1]);
+    }
+
+    /// 每次访问设备时检查队列锁是否空闲，前 extra 次借机再入队一帧（模拟 FDRV 在流控等待期间继续 enqueue）
+    struct EnqueueDuringIo<'a> {
+        emu: &'a EmuBootrom8801,
+        queue: &'a Mutex<Option<TxDataPriv>>,
+        extra: core::cell::Cell<u32>,
+        locked_during_io: core::cell::Cell<u32>,
+    }
+
+    impl EnqueueDuringIo<'_> {
+        fn check(&self) {
+            match self.queue.try_lock() {
+                Some(mut q) if self.extra.get() > 0 => {
+                    self.extra.set(self.extra.get() - 1);
+                    q.as_mut().unwrap().enqueue(&frame(32, 0xaa)).unwrap();
+                }
+                Some(_) => {}
+                None => self.locked_during_io.set(self.locked_during_io.get() + 1),
+            }
+        }
+    }
+
+    impl SdioOps for EnqueueDuringIo<'_> {
+        fn writeb(&self, regaddr: u32, val: u8) -> Result<(), i32> {
+            self.emu.writeb(regaddr, val)
+        }
+        fn readb(&self, regaddr: u32) -> Result<u8, i32> {
+            self.check();
+            self.emu.readb(regaddr)
+        }
+        fn recv_pkt(&self, buf: &mut [u8], size: u32, msg: u8) -> Result<usize, i32> {
+            self.emu.recv_pkt(buf, size, msg)
+        }
+        fn send_pkt(&self, buf: &[u8], count: usize) -> Result<usize, i32> {
+            self.check();
+            self.emu.send_pkt(buf, count)
+        }
+    }
+
+    #[test]
+    fn process_releases_queue_during_flow_ctrl_and_send() {
+        let emu = EmuBootrom8801::new(3);
+        emu.set_flow_ctrl(4);
+        let queue = queue_with([frame(64, 0)]);
+        let dev = EnqueueDuringIo {
+            emu: &emu,
+            queue: &queue,
+            extra: core::cell::Cell::new(2),
+            locked_during_io: core::cell::Cell::new(0),
+        };
+        let mut sender = TxSender::new(ProductId::Aic8801);
+        // FLOW_CTRL 读时入队的帧与原帧一起发出，send_pkt 时入队的帧在下一轮发出
+        assert_eq!(sender.process(&queue, &dev), Ok(3));
+        assert_eq!(dev.locked_during_io.get(), 0);
+        assert_eq!(emu.data_xfers(), alloc::vec![2, 1]);
+        assert_eq!(queue.lock().as_ref().unwrap().tx_frames, 3);
+    }
+
+    #[test]
+    fn enqueue_bounds() {
+        let mut tx = TxDataPriv::new();
+        assert_eq!(tx.enqueue(&frame(TX_FRAME_MAX_LEN + 1, 0)), Err(-22));
+        for _ in 0..TXQLEN {
+            tx.enqueue(&[1]).unwrap();
+        }
+        assert_eq!(tx.enqueue(&[1]), Err(-105));
+    }
+}
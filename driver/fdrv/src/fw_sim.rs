@@ -0,0 +1,815 @@
+//! LMAC 运行固件软件模型（主机侧控制面集成测试用，无需板卡）
+//!
+//! LmacFwSim 作为 bsp::EmuFirmware 挂在 EmuBootrom8801 上，按 lmac_msg.h 布局应答 WiphyOpsImpl 下发的
+//! MM_VERSION、MM_ADD_IF/REMOVE_IF、SCANU_START（先逐个回 SCANU_RESULT_IND 信标，再回 CFM）、
+//! SM_CONNECT（CFM 后回 SM_CONNECT_IND）、SM_DISCONNECT（回 SM_DISCONNECT_IND）、MM_KEY_ADD/DEL、
+//! APM_START/STOP、MM_STA_ADD/DEL、MM_SET_POWER、MM_GET_STA_INFO。
+//! 行为由 FwSimScenario 描述：可见 AP、按 REQ 覆盖的 CFM status、不回 CFM 的 REQ 以及按主机轮询次数计的延迟。
+//! fw_sim_attach 经 bsp::aicbsp_attach_sim_device 把它接到 with_cmd_mgr / submit_cmd_tx_and_wait_tx_done 之下，
+//! 并注册 e2a_indication_handler，scan/connect/AP 流程与 e2a_dispatch 回调即可在主机上跑通。
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use bsp::{
+    aicbsp_attach_sim_device, aicbsp_detach_sim_device, aicbsp_set_sim_power_cycle, set_e2a_indication_cb,
+    EmuBootrom8801, EmuFirmware,
+    EmuReply, FwVersion, ProductId, APM_START_CFM, APM_START_REQ, APM_STOP_CFM, APM_STOP_REQ, MM_ADD_IF_CFM,
+    MM_ADD_IF_REQ, MM_GET_STA_INFO_CFM, MM_GET_STA_INFO_REQ, MM_KEY_ADD_CFM, MM_KEY_ADD_REQ, MM_KEY_DEL_CFM,
+    MM_KEY_DEL_REQ, MM_REMOVE_IF_CFM, MM_REMOVE_IF_REQ, MM_SET_POWER_CFM, MM_SET_POWER_REQ, MM_STA_ADD_CFM,
+    MM_STA_ADD_REQ, MM_STA_DEL_CFM, MM_STA_DEL_REQ, MM_VERSION_CFM, MM_VERSION_REQ, SCANU_RESULT_IND,
+    SCANU_START_CFM, SCANU_START_REQ, SM_CONNECT_CFM, SM_CONNECT_IND, SM_CONNECT_REQ, SM_DISCONNECT_CFM,
+    SM_DISCONNECT_IND, SM_DISCONNECT_REQ,
+};
+
+use crate::e2a_dispatch::e2a_indication_handler;
+use crate::lmac_cmd::{ieee80211_channel_to_freq, MAC_BCST};
+use crate::lmac_struct::*;
+
+/// 模拟固件支持的 VIF 数（NX_VIRT_DEV_MAX）
+const SIM_VIF_MAX: usize = 4;
+/// 每 VIF 的默认密钥槽（MAC_DEFAULT_KEY_COUNT），成对密钥的 hw_key_idx 排在全部默认密钥之后
+const SIM_DEFAULT_KEY_COUNT: u8 = 6;
+/// 模拟固件的 STA 表项数（NX_REMOTE_STA_MAX + 每 VIF 一个 bcmc）
+const SIM_STA_MAX: u8 = 16;
+/// fw_sim_attach 使用的 chip_rev（U03）
+const SIM_CHIP_REV: u8 = 7;
+/// SM_CONNECT_IND.status_code：未找到匹配的 AP（WLAN_STATUS_UNSPECIFIED_FAILURE）
+const WLAN_STATUS_UNSPECIFIED_FAILURE: u16 = 1;
+/// MM_GET_STA_INFO_CFM.rate_info 固定值：HT MCS7
+const SIM_RATE_INFO: u32 = 0x0000_0207;
+
+/// 场景中可被扫描/连接到的 AP
+#[derive(Debug, Clone)]
+pub struct SimAp {
+    pub bssid: [u8; 6],
+    pub ssid: Vec<u8>,
+    pub channel: u8,
+    pub rssi: i8,
+}
+
+/// 模拟固件的行为脚本
+#[derive(Debug, Clone, Default)]
+pub struct FwSimScenario {
+    /// SCANU_START 时依次上报、SM_CONNECT 时按 SSID/BSSID 匹配的 AP
+    pub aps: Vec<SimAp>,
+    /// 按 REQ id 覆盖 CFM 的 status 字节；非 0 时 SCANU_START 不再上报结果、SM_CONNECT 不再回 IND
+    pub cfm_status: BTreeMap<u16, u8>,
+    /// 收到后不作任何应答的 REQ id（模拟固件卡死，驱动侧等待 CFM 超时）；崩溃恢复下电后清空，重启的固件正常应答
+    pub no_cfm: BTreeSet<u16>,
+    /// 按 REQ id 推迟整组应答（含其后的 IND）的主机轮询次数；wait_done_until 约每 1ms 轮询一次。
+    /// 不同 REQ 的应答按各自到期先后送出，可与下发顺序不同（如 scan 进行中先回 get_station）
+    pub delay_polls: BTreeMap<u16, u32>,
+    /// SM_CONNECT_IND / SM_DISCONNECT_IND 在 CFM 之后再推迟的轮询次数
+    pub ind_delay_polls: u32,
+    /// 强制 SM_CONNECT_IND.status_code；None 时按 aps 是否匹配取 0 或 WLAN_STATUS_UNSPECIFIED_FAILURE
+    pub connect_status_code: Option<u16>,
+    /// MM_VERSION_CFM 内容
+    pub version: FwVersion,
+}
+
+/// LMAC 运行固件模型，见模块说明
+pub struct LmacFwSim {
+    scenario: FwSimScenario,
+    /// 已经过的主机轮询次数
+    polls: u32,
+    /// 尚未到期的应答 (到期轮询次数, msg id, param)；同时到期者按产生顺序释放
+    pending: VecDeque<(u32, u16, Vec<u8>)>,
+    /// add_if 成功的 VIF 类型
+    vifs: [Option<u8>; SIM_VIF_MAX],
+    /// STA 模式 VIF 当前连接的 (aps 下标, sta_idx)
+    connected: [Option<(usize, u8)>; SIM_VIF_MAX],
+    /// 已分配的 sta_idx -> 对端 MAC
+    stas: BTreeMap<u8, [u8; 6]>,
+}
+
+impl LmacFwSim {
+    pub fn new(scenario: FwSimScenario) -> Self {
+        Self {
+            scenario,
+            polls: 0,
+            pending: VecDeque::new(),
+            vifs: [None; SIM_VIF_MAX],
+            connected: [None; SIM_VIF_MAX],
+            stas: BTreeMap::new(),
+        }
+    }
+
+    fn status(&self, req_id: u16) -> u8 {
+        self.scenario.cfm_status.get(&req_id).copied().unwrap_or(0)
+    }
+
+    /// 无延迟的应答立即送出，否则进入 pending。同一 REQ 的 CFM/IND 延迟单调不减，组内顺序不乱；
+    /// 不同 REQ 之间不互相阻塞，与固件各任务独立应答一致
+    fn emit(&mut self, reply: &mut EmuReply<'_>, delay: u32, id: u16, param: &[u8]) {
+        if delay == 0 {
+            reply.push(id, param);
+        } else {
+            self.pending.push_back((self.polls + delay, id, param.to_vec()));
+        }
+    }
+
+    fn alloc_sta(&mut self, mac: [u8; 6]) -> Option<u8> {
+        let idx = (0..SIM_STA_MAX).find(|i| !self.stas.contains_key(i))?;
+        self.stas.insert(idx, mac);
+        Some(idx)
+    }
+
+    fn vif_mut(&mut self, vif_idx: u8) -> Option<&mut Option<u8>> {
+        self.vifs.get_mut(vif_idx as usize)
+    }
+
+    /// SCANU_RESULT_IND：scanu_result_ind 头 + 信标（24B MAC 头 + 时间戳/间隔/能力 + SSID、DS 参数 IE）
+    fn scan_result_ind(ap: &SimAp, vif_idx: u8) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(36 + ap.ssid.len() + 3);
+        frame.extend_from_slice(&[0x80, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&MAC_BCST);
+        frame.extend_from_slice(&ap.bssid);
+        frame.extend_from_slice(&ap.bssid);
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.extend_from_slice(&[0u8; 8]);
+        frame.extend_from_slice(&100u16.to_le_bytes());
+        frame.extend_from_slice(&0x0001u16.to_le_bytes());
+        frame.extend_from_slice(&[0, ap.ssid.len() as u8]);
+        frame.extend_from_slice(&ap.ssid);
+        frame.extend_from_slice(&[3, 1, ap.channel]);
+
+        let mut hdr = ScanuResultIndHdr::zeroed();
+        hdr.length = frame.len() as u16;
+        hdr.framectrl = 0x0080;
+        hdr.center_freq = ieee80211_channel_to_freq(ap.channel);
+        hdr.band = (ap.channel > 14) as u8;
+        hdr.sta_idx = 0xff;
+        hdr.inst_nbr = vif_idx;
+        hdr.rssi = ap.rssi;
+        let mut param = hdr.as_bytes().to_vec();
+        param.extend_from_slice(&frame);
+        param
+    }
+
+    fn on_scanu_start(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = ScanuStartReqParam::from_bytes(param)?;
+        let status = self.status(SCANU_START_REQ);
+        let mut result_cnt = 0u8;
+        if status == 0 {
+            for ap in self.scenario.aps.clone() {
+                self.emit(reply, delay, SCANU_RESULT_IND, &Self::scan_result_ind(&ap, req.vif_idx));
+                result_cnt = result_cnt.saturating_add(1);
+            }
+        }
+        let cfm = ScanuStartCfmParam { vif_idx: req.vif_idx, status, result_cnt };
+        self.emit(reply, delay, SCANU_START_CFM, cfm.as_bytes());
+        Some(())
+    }
+
+    fn on_sm_connect(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = SmConnectReqParam::from_bytes(param)?;
+        let status = self.status(SM_CONNECT_REQ);
+        self.emit(reply, delay, SM_CONNECT_CFM, SmConnectCfmParam { status }.as_bytes());
+        if status != 0 {
+            return Some(());
+        }
+        let ssid = &req.ssid.array[..(req.ssid.length as usize).min(MAC_SSID_LEN)];
+        let bssid = req.bssid.array;
+        let found = self
+            .scenario
+            .aps
+            .iter()
+            .position(|ap| ap.ssid == ssid && (bssid == MAC_BCST || bssid == ap.bssid));
+        let status_code = self.scenario.connect_status_code.unwrap_or(match found {
+            Some(_) => 0,
+            None => WLAN_STATUS_UNSPECIFIED_FAILURE,
+        });
+        let mut ind = SmConnectIndHdr::zeroed();
+        ind.status_code = status_code;
+        ind.vif_idx = req.vif_idx;
+        ind.bssid = MacAddr::new(&found.map_or(bssid, |i| self.scenario.aps[i].bssid));
+        if let (0, Some(i)) = (status_code, found) {
+            let ap_bssid = self.scenario.aps[i].bssid;
+            let sta_idx = self.alloc_sta(ap_bssid)?;
+            if let Some(slot) = self.connected.get_mut(req.vif_idx as usize) {
+                *slot = Some((i, sta_idx));
+            }
+            ind.ap_idx = sta_idx;
+            ind.qos = 1;
+        }
+        let ind_delay = delay + self.scenario.ind_delay_polls;
+        self.emit(reply, ind_delay, SM_CONNECT_IND, ind.as_bytes());
+        Some(())
+    }
+
+    fn on_sm_disconnect(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = SmDisconnectReqParam::from_bytes(param)?;
+        self.emit(reply, delay, SM_DISCONNECT_CFM, &[]);
+        let conn = self.connected.get_mut(req.vif_idx as usize).and_then(Option::take);
+        if let Some((_, sta_idx)) = conn {
+            self.stas.remove(&sta_idx);
+            let ind = SmDisconnectIndParam {
+                reason_code: req.reason_code,
+                vif_idx: req.vif_idx,
+                ft_over_ds: 0,
+            };
+            let ind_delay = delay + self.scenario.ind_delay_polls;
+            self.emit(reply, ind_delay, SM_DISCONNECT_IND, ind.as_bytes());
+        }
+        Some(())
+    }
+
+    fn on_mm_add_if(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = MmAddIfReqParam::from_bytes(param)?;
+        let mut status = self.status(MM_ADD_IF_REQ);
+        let free = self.vifs.iter().position(Option::is_none);
+        let inst_nbr = match (status, free) {
+            (0, Some(i)) => {
+                self.vifs[i] = Some(req.vif_type);
+                i as u8
+            }
+            (0, None) => {
+                status = 1;
+                0
+            }
+            _ => 0,
+        };
+        self.emit(reply, delay, MM_ADD_IF_CFM, MmAddIfCfmParam { status, inst_nbr }.as_bytes());
+        Some(())
+    }
+
+    fn on_mm_remove_if(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = MmRemoveIfReqParam::from_bytes(param)?;
+        if let Some(vif) = self.vif_mut(req.inst_nbr) {
+            *vif = None;
+        }
+        if let Some((_, sta_idx)) = self.connected.get_mut(req.inst_nbr as usize).and_then(Option::take) {
+            self.stas.remove(&sta_idx);
+        }
+        self.emit(reply, delay, MM_REMOVE_IF_CFM, &[]);
+        Some(())
+    }
+
+    fn on_mm_key_add(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = MmKeyAddReqParam::from_bytes(param)?;
+        let hw_key_idx = if req.sta_idx == 0xff {
+            req.inst_nbr * SIM_DEFAULT_KEY_COUNT + req.key_idx
+        } else {
+            SIM_VIF_MAX as u8 * SIM_DEFAULT_KEY_COUNT + req.sta_idx
+        };
+        let cfm = MmKeyAddCfmParam { status: self.status(MM_KEY_ADD_REQ), hw_key_idx };
+        self.emit(reply, delay, MM_KEY_ADD_CFM, cfm.as_bytes());
+        Some(())
+    }
+
+    fn on_apm_start(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = ApmStartReqParam::from_bytes(param)?;
+        let status = self.status(APM_START_REQ);
+        let bcmc_idx = if status == 0 { self.alloc_sta(MAC_BCST)? } else { 0xff };
+        let cfm = ApmStartCfmParam { status, vif_idx: req.vif_idx, ch_idx: 0, bcmc_idx };
+        self.emit(reply, delay, APM_START_CFM, cfm.as_bytes());
+        Some(())
+    }
+
+    fn on_mm_sta_add(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = MmStaAddReqParam::from_bytes(param)?;
+        let mut status = self.status(MM_STA_ADD_REQ);
+        // inst_nbr 须是 add_if 成功的 VIF
+        if status == 0 && self.vifs.get(req.inst_nbr as usize).is_none_or(|v| v.is_none()) {
+            status = 1;
+        }
+        let mut sta_idx = 0xff;
+        if status == 0 {
+            match self.alloc_sta(req.mac_addr.array) {
+                Some(i) => sta_idx = i,
+                None => status = 1,
+            }
+        }
+        let cfm = MmStaAddCfmParam { status, sta_idx, hw_sta_idx: sta_idx };
+        self.emit(reply, delay, MM_STA_ADD_CFM, cfm.as_bytes());
+        Some(())
+    }
+
+    fn on_mm_get_sta_info(&mut self, param: &[u8], reply: &mut EmuReply<'_>, delay: u32) -> Option<()> {
+        let req = MmGetStaInfoReqParam::from_bytes(param)?;
+        let mut cfm = MmGetStaInfoCfmParam::zeroed();
+        cfm.rate_info = SIM_RATE_INFO;
+        let ap = self
+            .connected
+            .iter()
+            .flatten()
+            .find(|(_, sta_idx)| *sta_idx == req.sta_idx)
+            .map(|&(i, _)| &self.scenario.aps[i]);
+        if let Some(ap) = ap {
+            cfm.rssi = ap.rssi as u8;
+        }
+        self.emit(reply, delay, MM_GET_STA_INFO_CFM, cfm.as_bytes());
+        Some(())
+    }
+
+    fn on_mm_version(&mut self, reply: &mut EmuReply<'_>, delay: u32) {
+        let v = &self.scenario.version;
+        let mut cfm = MmVersionCfmParam::zeroed();
+        cfm.version_lmac = v.version_lmac;
+        cfm.version_machw_1 = v.version_machw_1;
+        cfm.version_machw_2 = v.version_machw_2;
+        cfm.version_phy_1 = v.version_phy_1;
+        cfm.version_phy_2 = v.version_phy_2;
+        cfm.features = v.features;
+        cfm.max_sta_nb = v.max_sta_nb;
+        cfm.max_vif_nb = v.max_vif_nb;
+        self.emit(reply, delay, MM_VERSION_CFM, cfm.as_bytes());
+    }
+}
+
+impl EmuFirmware for LmacFwSim {
+    fn on_msg(&mut self, id: u16, param: &[u8], reply: &mut EmuReply<'_>) -> bool {
+        if self.scenario.no_cfm.contains(&id) {
+            log::info!(target: "wireless::fdrv", "fw_sim: swallow 0x{:04x} (no_cfm)", id);
+            return true;
+        }
+        let delay = self.scenario.delay_polls.get(&id).copied().unwrap_or(0);
+        let handled = match id {
+            MM_VERSION_REQ => {
+                self.on_mm_version(reply, delay);
+                Some(())
+            }
+            MM_ADD_IF_REQ => self.on_mm_add_if(param, reply, delay),
+            MM_REMOVE_IF_REQ => self.on_mm_remove_if(param, reply, delay),
+            SCANU_START_REQ => self.on_scanu_start(param, reply, delay),
+            SM_CONNECT_REQ => self.on_sm_connect(param, reply, delay),
+            SM_DISCONNECT_REQ => self.on_sm_disconnect(param, reply, delay),
+            MM_KEY_ADD_REQ => self.on_mm_key_add(param, reply, delay),
+            MM_KEY_DEL_REQ => {
+                self.emit(reply, delay, MM_KEY_DEL_CFM, &[]);
+                Some(())
+            }
+            APM_START_REQ => self.on_apm_start(param, reply, delay),
+            APM_STOP_REQ => {
+                self.emit(reply, delay, APM_STOP_CFM, &[]);
+                Some(())
+            }
+            MM_STA_ADD_REQ => self.on_mm_sta_add(param, reply, delay),
+            MM_STA_DEL_REQ => MmStaDelReqParam::from_bytes(param).map(|req| {
+                self.stas.remove(&req.sta_idx);
+                let status = self.status(MM_STA_DEL_REQ);
+                self.emit(reply, delay, MM_STA_DEL_CFM, &[status]);
+            }),
+            MM_SET_POWER_REQ => MmSetPowerReqParam::from_bytes(param).map(|req| {
+                let cfm = MmSetPowerCfmParam { radio_idx: 0, power: req.power };
+                self.emit(reply, delay, MM_SET_POWER_CFM, cfm.as_bytes());
+            }),
+            MM_GET_STA_INFO_REQ => self.on_mm_get_sta_info(param, reply, delay),
+            _ => return false,
+        };
+        if handled.is_none() {
+            log::warn!(target: "wireless::fdrv", "fw_sim: 0x{:04x} param too short or table full (len={})", id, param.len());
+        }
+        handled.is_some()
+    }
+
+    fn on_power_cycle(&mut self) {
+        let scenario = core::mem::take(&mut self.scenario);
+        *self = Self::new(FwSimScenario { no_cfm: BTreeSet::new(), ..scenario });
+    }
+
+    fn on_poll(&mut self, reply: &mut EmuReply<'_>) {
+        self.polls += 1;
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].0 > self.polls {
+                i += 1;
+                continue;
+            }
+            if let Some((_, id, param)) = self.pending.remove(i) {
+                reply.push(id, &param);
+            }
+        }
+    }
+}
+
+/// fw_sim_attach 挂上的设备，崩溃恢复的下电/上电钩子据此重新挂载
+static SIM_DEVICE: Mutex<Option<Arc<EmuBootrom8801>>> = Mutex::new(None);
+
+/// 以 scenario 创建模拟固件并接到 BSP 之下（按 8801 收发），同时注册 e2a_indication_handler 与
+/// 崩溃恢复用的下电/上电钩子；返回的设备可查看 msg_log / dropped 等
+pub fn fw_sim_attach(scenario: FwSimScenario) -> Arc<EmuBootrom8801> {
+    let emu = Arc::new(EmuBootrom8801::with_firmware(SIM_CHIP_REV, Box::new(LmacFwSim::new(scenario))));
+    aicbsp_attach_sim_device(emu.clone(), ProductId::Aic8801);
+    *SIM_DEVICE.lock() = Some(emu.clone());
+    aicbsp_set_sim_power_cycle(Some(fw_sim_power_cycle));
+    set_e2a_indication_cb(Some(e2a_indication_handler));
+    emu
+}
+
+/// 卸下模拟固件并注销 E2A 指示回调与下电/上电钩子
+pub fn fw_sim_detach() {
+    set_e2a_indication_cb(None);
+    aicbsp_set_sim_power_cycle(None);
+    SIM_DEVICE.lock().take();
+    aicbsp_detach_sim_device();
+}
+
+/// aicbsp_wifi_recover 的下电/上电：卸下设备（flush 旧 cmd_mgr）、复位模拟固件，再挂回同一设备（新建 cmd_mgr），
+/// 对应真实设备上的 sdio_exit → power_on → sdio_init → driver_fw_init
+fn fw_sim_power_cycle() {
+    let Some(emu) = SIM_DEVICE.lock().clone() else {
+        return;
+    };
+    aicbsp_detach_sim_device();
+    emu.power_cycle();
+    aicbsp_attach_sim_device(emu, ProductId::Aic8801);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::e2a_dispatch::{set_connect_result_cb, set_disconnect_cb, set_scan_done_cb, set_scan_result_cb};
+    use crate::lmac_cmd::{parse_scan_result_to_bss_info, ScanuResultInd, SmConnectInd, SmDisconnectInd};
+    use crate::wiphy::WiphyOps;
+    use crate::WiphyOpsImpl;
+    use crate::lmac_cmd::ScanuStartReq;
+    use crate::recovery::{set_recovered_cb, RecoveryEvent};
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+    use ieee80211::{BssInfo, Nl80211Iftype};
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// BSP 的 cmd_mgr / 软件设备与 e2a_dispatch 回调都是全局的，测试须串行
+    static SERIAL: Mutex<()> = Mutex::new(());
+    static SCAN_RESULTS: Mutex<Vec<BssInfo>> = Mutex::new(Vec::new());
+    static SCAN_DONE: Mutex<usize> = Mutex::new(0);
+    static CONNECT_INDS: Mutex<Vec<(u16, [u8; 6], u8)>> = Mutex::new(Vec::new());
+    static DISCONNECT_INDS: Mutex<Vec<(u16, u8)>> = Mutex::new(Vec::new());
+    static RECOVERED: Mutex<Vec<RecoveryEvent>> = Mutex::new(Vec::new());
+
+    fn lock<T>(m: &'static Mutex<T>) -> MutexGuard<'static, T> {
+        m.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 代替 busrx 线程：把 CFM 之后留在 RD_FIFO 的 IND 收完
+    fn drain(emu: &EmuBootrom8801) {
+        while emu.pending_rx() > 0 {
+            bsp::sdio_poll_rx_once();
+        }
+    }
+
+    /// 代替执行器：以空 waker 轮询一次，busrx 的唤醒由测试循环里的 sdio_poll_rx_once 代替
+    fn poll_once<F: Future + Unpin>(f: &mut F) -> Poll<F::Output> {
+        Pin::new(f).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    unsafe fn on_scan_result(ind: &ScanuResultInd, param: &[u8]) {
+        if let Some(bss) = parse_scan_result_to_bss_info(ind, param) {
+            lock(&SCAN_RESULTS).push(bss);
+        }
+    }
+
+    unsafe fn on_scan_done() {
+        *lock(&SCAN_DONE) += 1;
+    }
+
+    unsafe fn on_connect(ind: &SmConnectInd) {
+        lock(&CONNECT_INDS).push((ind.status_code, ind.bssid, ind.ap_idx));
+    }
+
+    unsafe fn on_disconnect(ind: &SmDisconnectInd) {
+        lock(&DISCONNECT_INDS).push((ind.reason_code, ind.vif_idx));
+    }
+
+    unsafe fn on_recovered(ev: &RecoveryEvent) {
+        lock(&RECOVERED).push(*ev);
+    }
+
+    struct Sim {
+        emu: Arc<EmuBootrom8801>,
+        _serial: MutexGuard<'static, ()>,
+    }
+
+    impl Drop for Sim {
+        fn drop(&mut self) {
+            fw_sim_detach();
+        }
+    }
+
+    fn start(scenario: FwSimScenario) -> Sim {
+        let serial = lock(&SERIAL);
+        lock(&SCAN_RESULTS).clear();
+        *lock(&SCAN_DONE) = 0;
+        lock(&CONNECT_INDS).clear();
+        lock(&DISCONNECT_INDS).clear();
+        lock(&RECOVERED).clear();
+        set_recovered_cb(Some(on_recovered));
+        set_scan_result_cb(Some(on_scan_result));
+        set_scan_done_cb(Some(on_scan_done));
+        set_connect_result_cb(Some(on_connect));
+        set_disconnect_cb(Some(on_disconnect));
+        Sim { emu: fw_sim_attach(scenario), _serial: serial }
+    }
+
+    const AP1: [u8; 6] = [0x02, 0x11, 0x22, 0x33, 0x44, 0x55];
+    const AP2: [u8; 6] = [0x02, 0x66, 0x77, 0x88, 0x99, 0xaa];
+
+    fn two_aps() -> FwSimScenario {
+        FwSimScenario {
+            aps: alloc::vec![
+                SimAp { bssid: AP1, ssid: b"home".to_vec(), channel: 6, rssi: -42 },
+                SimAp { bssid: AP2, ssid: b"lab-5g".to_vec(), channel: 36, rssi: -67 },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scan_reports_scripted_aps_then_scan_done() {
+        let sim = start(two_aps());
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        assert_eq!(vif, 0);
+        wiphy.scan(vif).unwrap();
+
+        let results = lock(&SCAN_RESULTS).clone();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].bssid, AP1);
+        assert_eq!(&results[0].ssid[..results[0].ssid_len as usize], b"home");
+        assert_eq!((results[0].freq, results[0].rssi), (2437, -42));
+        assert_eq!(results[1].bssid, AP2);
+        assert_eq!((results[1].freq, results[1].rssi), (5180, -67));
+        assert_eq!(*lock(&SCAN_DONE), 1);
+        assert_eq!(sim.emu.msg_log(), alloc::vec![MM_ADD_IF_REQ, SCANU_START_REQ]);
+        assert_eq!(sim.emu.pending_rx(), 0);
+    }
+
+    #[test]
+    fn connect_get_station_and_disconnect() {
+        let sim = start(two_aps());
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        wiphy.connect(vif, b"home", None).unwrap();
+        drain(&sim.emu);
+
+        let inds = lock(&CONNECT_INDS).clone();
+        assert_eq!(inds.len(), 1);
+        let (status_code, bssid, ap_idx) = inds[0];
+        assert_eq!((status_code, bssid), (0, AP1));
+        wiphy.register_sta_from_connect_ind(vif as u8, &bssid, ap_idx);
+        let info = wiphy.get_station(vif, &AP1).unwrap();
+        assert_eq!(info.rssi, -42);
+        assert_eq!(info.tx_rate, SIM_RATE_INFO);
+
+        wiphy.disconnect(vif).unwrap();
+        drain(&sim.emu);
+        assert_eq!(lock(&DISCONNECT_INDS).clone(), alloc::vec![(3, vif as u8)]);
+        assert_eq!(sim.emu.dropped(), 0);
+    }
+
+    #[test]
+    fn connect_failures_surface_as_ind_status_and_errno() {
+        let sim = start(two_aps());
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+
+        // SSID 不在场景中：CFM 成功，IND 带失败码
+        wiphy.connect(vif, b"nowhere", None).unwrap();
+        drain(&sim.emu);
+        assert_eq!(lock(&CONNECT_INDS)[0].0, WLAN_STATUS_UNSPECIFIED_FAILURE);
+        // 未连接时 disconnect 不回 IND
+        wiphy.disconnect(vif).unwrap();
+        drain(&sim.emu);
+        assert!(lock(&DISCONNECT_INDS).is_empty());
+        drop(sim);
+
+        let mut scenario = two_aps();
+        scenario.cfm_status.insert(SM_CONNECT_REQ, 8);
+        let sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        assert_eq!(wiphy.connect(vif, b"home", None), Err(-115));
+        drain(&sim.emu);
+        assert!(lock(&CONNECT_INDS).is_empty());
+    }
+
+    #[test]
+    fn ap_lifecycle_with_station_and_keys() {
+        let sim = start(FwSimScenario::default());
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Ap).unwrap();
+        wiphy.start_ap(vif, b"aic-ap", 11).unwrap();
+        assert_eq!(wiphy.get_channel(vif), Ok(11));
+        let sta = [0x02, 0xde, 0xad, 0xbe, 0xef, 0x01];
+        wiphy.add_station(vif, &sta).unwrap();
+        // mm_sta_add_req.inst_nbr 指向未 add_if 的 VIF：固件拒绝
+        assert_eq!(wiphy.add_station(vif + 1, &sta), Err(-5));
+        wiphy.add_key(vif, 1, &[0x5a; 16]).unwrap();
+        assert!(wiphy.get_key(vif, 1).unwrap().present);
+        wiphy.del_key(vif, 1).unwrap();
+        assert!(!wiphy.get_key(vif, 1).unwrap().present);
+        wiphy.del_station(vif, 1).unwrap();
+        wiphy.stop_ap(vif).unwrap();
+        wiphy.del_interface(vif).unwrap();
+        assert_eq!(
+            sim.emu.msg_log(),
+            alloc::vec![
+                MM_ADD_IF_REQ,
+                APM_START_REQ,
+                MM_STA_ADD_REQ,
+                MM_STA_ADD_REQ,
+                MM_KEY_ADD_REQ,
+                MM_KEY_DEL_REQ,
+                MM_STA_DEL_REQ,
+                APM_STOP_REQ,
+                MM_REMOVE_IF_REQ
+            ]
+        );
+        assert_eq!(sim.emu.dropped(), 0);
+    }
+
+    #[test]
+    fn scripted_status_and_vif_exhaustion() {
+        let mut scenario = FwSimScenario::default();
+        scenario.cfm_status.insert(APM_START_REQ, 1);
+        let _sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        for expected in 0..SIM_VIF_MAX as u32 {
+            assert_eq!(wiphy.add_interface(Nl80211Iftype::Ap), Ok(expected));
+        }
+        assert_eq!(wiphy.add_interface(Nl80211Iftype::Station), Err(-5));
+        assert_eq!(wiphy.start_ap(0, b"aic-ap", 1), Err(-5));
+        assert_eq!(wiphy.get_channel(0), Err(-61));
+    }
+
+    #[test]
+    fn delayed_replies_and_late_connect_ind() {
+        let mut scenario = two_aps();
+        scenario.delay_polls.insert(SCANU_START_REQ, 30);
+        scenario.ind_delay_polls = 5;
+        let _sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        wiphy.scan(vif).unwrap();
+        assert_eq!(lock(&SCAN_RESULTS).len(), 2);
+
+        // CFM 到达即返回，IND 还要再等 5 次轮询
+        wiphy.connect(vif, b"lab-5g", Some(&AP2)).unwrap();
+        assert!(lock(&CONNECT_INDS).is_empty());
+        for _ in 0..5 {
+            bsp::sdio_poll_rx_once();
+        }
+        assert_eq!(lock(&CONNECT_INDS)[0].1, AP2);
+    }
+
+    #[test]
+    fn swallowed_request_times_out_and_marks_crashed() {
+        let mut scenario = two_aps();
+        scenario.no_cfm.insert(SCANU_START_REQ);
+        let _sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        assert_eq!(wiphy.scan(vif), Err(-62));
+        assert!(bsp::aicbsp_wifi_crashed());
+        assert_eq!(*lock(&SCAN_DONE), 0);
+    }
+
+    #[test]
+    fn concurrent_async_requests_complete_out_of_order() {
+        let mut scenario = two_aps();
+        scenario.delay_polls.insert(SCANU_START_REQ, 20);
+        let sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        wiphy.connect(vif, b"home", None).unwrap();
+        drain(&sim.emu);
+        let (_, bssid, ap_idx) = lock(&CONNECT_INDS)[0];
+        wiphy.register_sta_from_connect_ind(vif as u8, &bssid, ap_idx);
+
+        // 两条命令同时挂起：先发的 scan 晚完成，后发的 get_station 先完成
+        let mut scan = wiphy.scan_async(vif);
+        let mut station = wiphy.get_station_async(vif, &AP1);
+        let (mut scan_done, mut station_done) = (None, None);
+        let mut order = Vec::new();
+        for _ in 0..40 {
+            bsp::sdio_poll_rx_once();
+            if scan_done.is_none() {
+                if let Poll::Ready(r) = poll_once(&mut scan) {
+                    scan_done = Some(r);
+                    order.push(SCANU_START_REQ);
+                }
+            }
+            if station_done.is_none() {
+                if let Poll::Ready(r) = poll_once(&mut station) {
+                    station_done = Some(r);
+                    order.push(MM_GET_STA_INFO_REQ);
+                }
+            }
+        }
+        assert_eq!(order, alloc::vec![MM_GET_STA_INFO_REQ, SCANU_START_REQ]);
+        assert_eq!(station_done.unwrap().unwrap().rssi, -42);
+        assert_eq!(scan_done.unwrap(), Ok(()));
+        assert_eq!(lock(&SCAN_RESULTS).len(), 2);
+        assert!(!bsp::aicbsp_wifi_crashed());
+        assert_eq!(sim.emu.dropped(), 0);
+    }
+
+    #[test]
+    fn async_request_times_out_and_marks_crashed() {
+        let mut scenario = two_aps();
+        scenario.no_cfm.insert(SCANU_START_REQ);
+        let _sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let vif = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        let mut scan = crate::send_lmac_req_async_timeout(&ScanuStartReq { vif_idx: vif as u8, duration_us: 0 }, 5);
+
+        for _ in 0..4 {
+            bsp::sdio_poll_rx_once();
+            assert!(poll_once(&mut scan).is_pending());
+        }
+        bsp::sdio_poll_rx_once();
+        assert!(matches!(poll_once(&mut scan), Poll::Ready(Err(-62))));
+        assert!(bsp::aicbsp_wifi_crashed());
+    }
+
+    #[test]
+    fn crash_power_cycle_and_replay() {
+        let mut scenario = two_aps();
+        scenario.no_cfm.insert(MM_GET_STA_INFO_REQ);
+        let sim = start(scenario);
+        let mut wiphy = WiphyOpsImpl::new();
+        let ap = wiphy.add_interface(Nl80211Iftype::Ap).unwrap();
+        wiphy.start_ap(ap, b"aic-ap", 11).unwrap();
+        wiphy.add_key(ap, 1, &[0x5a; 16]).unwrap();
+        let sta = wiphy.add_interface(Nl80211Iftype::Station).unwrap();
+        wiphy.connect(sta, b"home", None).unwrap();
+        drain(&sim.emu);
+        let (_, bssid, ap_idx) = lock(&CONNECT_INDS)[0];
+        wiphy.register_sta_from_connect_ind(sta as u8, &bssid, ap_idx);
+        wiphy.add_key(sta, 0, &[0x11; 16]).unwrap();
+        wiphy.add_key(sta, 1, &[0x22; 16]).unwrap();
+
+        // 固件卡死：MM_GET_STA_INFO 不回 CFM，cmd_mgr 进入 Crashed
+        assert!(matches!(wiphy.get_station(sta, &AP1), Err(-62)));
+        assert!(bsp::aicbsp_wifi_crashed());
+        let before = sim.emu.msg_log().len();
+
+        // 下一次操作先恢复：钩子代替下电/上电，随后按崩溃前状态重放；旧 sta_idx 已清除
+        assert!(matches!(wiphy.get_station(sta, &AP1), Err(-2)));
+        assert!(!bsp::aicbsp_wifi_crashed());
+        assert_eq!(
+            sim.emu.msg_log()[before..],
+            [MM_ADD_IF_REQ, APM_START_REQ, MM_KEY_ADD_REQ, MM_ADD_IF_REQ, SM_CONNECT_REQ, MM_KEY_ADD_REQ]
+        );
+        assert_eq!(wiphy.get_channel(ap), Ok(11));
+        assert!(wiphy.get_key(ap, 1).unwrap().present);
+        assert!(!wiphy.get_key(sta, 0).unwrap().present);
+        assert!(wiphy.get_key(sta, 1).unwrap().present);
+
+        let events = lock(&RECOVERED).clone();
+        assert_eq!(events.len(), 1);
+        let ev = &events[0];
+        assert_eq!(ev.replay_failures, 0);
+        assert!(ev.recovery_count >= 1);
+        assert_eq!(ev.crash.cmd.map(|c| c.timeout_reqid), Some(MM_GET_STA_INFO_CFM));
+        assert_eq!(ev.dropped_stas[0], Some(AP1));
+        assert_eq!(ev.dropped_stas[1], None);
+
+        // 重新关联后再登记，get_station 恢复可用（MM_GET_STA_INFO 仅在下电前卡死）
+        drain(&sim.emu);
+        let (_, bssid, ap_idx) = lock(&CONNECT_INDS)[1];
+        wiphy.register_sta_from_connect_ind(sta as u8, &bssid, ap_idx);
+        assert_eq!(wiphy.get_station(sta, &AP1).unwrap().rssi, -42);
+        assert_eq!(sim.emu.dropped(), 0);
+    }
+
+    #[test]
+    fn version_query_returns_scripted_version() {
+        let mut scenario = FwSimScenario::default();
+        scenario.version = FwVersion {
+            version_lmac: 0x0604_0003,
+            max_sta_nb: 10,
+            max_vif_nb: 4,
+            ..Default::default()
+        };
+        let _sim = start(scenario);
+        let v = crate::send_lmac_req(&crate::MmVersionReq).unwrap();
+        assert_eq!(v.fw_version_string(), "6.4.0.3");
+        assert_eq!((v.max_sta_nb, v.max_vif_nb), (10, 4));
+    }
+
+    #[test]
+    fn tx_data_frames_are_aggregated_into_one_cmd53() {
+        let sim = start(FwSimScenario::default());
+        let frames: Vec<Vec<u8>> = (0..5u8).map(|i| alloc::vec![i; 100 + i as usize]).collect();
+        for f in &frames {
+            crate::tx_data(f).unwrap();
+        }
+        assert!(sim.emu.data_xfers().is_empty());
+        bsp::sdio_tx_process_once();
+        assert_eq!(sim.emu.data_xfers(), alloc::vec![5]);
+        let got = sim.emu.data_frames();
+        for (f, g) in frames.iter().zip(&got) {
+            assert_eq!(&g[..f.len()], &f[..]);
+        }
+        assert_eq!(bsp::aicbsp_txdata_stats(), (5, 1, 0));
+    }
+}
@@ -5,7 +5,7 @@
 
 use core::result::Result;
 
-use bsp::{LmacMsg, RwnxCmdMgr, IpcE2AMsg};
+use bsp::{cmd_flags, LmacMsg, RwnxCmdMgr, IpcE2AMsg};
 
 /// 命令发送缓冲区大小（A2E 头 8 字节 + param）
 pub const CMD_TX_BUF_SIZE: usize = 8 + bsp::LMAC_MSG_MAX_LEN;
@@ -60,7 +60,7 @@ where
     F: FnMut(&LmacMsg) -> Result<(), E>,
 {
     log::debug!(target: "wireless::fdrv", "ipc_send_cmd_sync msg_id=0x{:04x} reqid=0x{:04x}", msg.header.id, reqid);
-    let token = cmd_mgr.push(reqid).ok_or(-12)?; // -ENOMEM
+    let token = cmd_mgr.push(reqid, cmd_flags::REQ_CFM).ok_or(-12)?; // -ENOMEM
     tx_fn(msg).map_err(|_| -5)?; // -EIO
     cmd_mgr.wait_done(token, timeout_ms, poll_fn, None)
 }
@@ -8,6 +8,8 @@
 //! - WiFi 管理器 (aicwf_manager)
 //! - SDIO Host (sdio_host) - 数据收发
 //! - Vendor 命令 (aic_vendor) - nl80211 扩展
+//! - RF 测试 (rftest) - 测试模式下的 DBG_RFTEST_CMD_REQ 命令
+//! - 固件模拟 (fw_sim，sim feature) - 主机侧控制面测试用的 LMAC 固件软件模型
 
 #![no_std]
 
@@ -15,11 +17,16 @@ extern crate alloc;
 
 mod cfgfile;
 mod e2a_dispatch;
+#[cfg(any(test, feature = "sim"))]
+mod fw_sim;
 mod ipc;
 mod lmac_cmd;
+pub mod lmac_struct;
 mod manager;
 mod net_device;
 mod priv_cmd;
+mod recovery;
+mod rftest;
 mod sdio_bus;
 mod sdio_host;
 mod tcp_ack;
@@ -28,20 +35,34 @@ mod wiphy;
 mod wiphy_impl;
 mod txrxif;
 
+#[cfg(any(test, feature = "sim"))]
+pub use fw_sim::{fw_sim_attach, fw_sim_detach, FwSimScenario, LmacFwSim, SimAp};
 pub use ipc::{ipc_handle_e2a_msg, ipc_send_cmd_sync, CMD_TX_BUF_SIZE, IpcHostCb};
 pub use manager::{WifiManager, WifiState};
 pub use priv_cmd::{AndroidWifiPrivCmd, PRIV_CMD_BUF_MAX};
+pub use recovery::{set_recovered_cb, RecoveredCb, RecoveryEvent};
+pub use rftest::{
+    rftest_boot, rftest_cmd, rftest_get_freq_cal, rftest_read_power_index, rftest_read_power_offset,
+    rftest_rx_result, rftest_rx_start, rftest_rx_stop, rftest_set_freq_cal, rftest_set_freq_cal_fine,
+    rftest_set_power, rftest_set_xtal_cap, rftest_set_xtal_cap_fine, rftest_tx_start, rftest_tx_stop,
+    rftest_tx_tone, rftest_write_power_index, rftest_write_power_offset, RfBand, RfBandwidth, RfRxResult,
+    RfTestCmd, RfTxMode, RfTxParams,
+};
 pub use sdio_bus::{
     aicwf_sdio_exit_equiv, aicwf_sdio_probe_equiv, aicwf_sdio_register_equiv, BusOps, BusState,
     NX_TXQ_CNT, NX_TXDESC_CNT_MAX, SdioDev, SdioHostEnv, SdioReg, SDIO_ACTIVE_ST, SDIO_BUFFER_SIZE,
     SDIO_SLEEP_ST, SDIO_TAIL_LEN, SDIOWIFI_FUNC_BLOCKSIZE,
 };
 pub use sdio_host::SdioHost;
-pub use vendor::{AIC_OUI, VendorSubcmd};
+pub use vendor::{aic_vendor_get_version, AIC_OUI, VendorSubcmd};
 pub use wiphy::{
     IfaceType, InterfaceId, ScanResult, StationInfo, WiphyOps, WiphyOpsStub,
 };
-pub use wiphy_impl::WiphyOpsImpl;
+pub use wiphy_impl::{
+    send as send_lmac_req, send_async as send_lmac_req_async, send_async_timeout as send_lmac_req_async_timeout,
+    send_lmac_cmd_async, send_lmac_cmd_flags, send_lmac_msg_nowait, wait_lmac_cfm, wait_lmac_cfm_vec,
+    LmacCfmFuture, WiphyOpsImpl,
+};
 pub use e2a_dispatch::{
     set_scan_result_cb, set_scan_done_cb, set_connect_result_cb, set_disconnect_cb,
     e2a_indication_handler,
@@ -63,7 +84,13 @@ pub use lmac_cmd::{
     parse_mm_add_if_cfm, parse_mm_key_add_cfm, parse_mm_get_sta_info_cfm, parse_apm_start_cfm,
     MacVifType, MmAddIfCfm, MmKeyAddCfm, MmGetStaInfoCfm, ApmStartCfm, ScanuStartCfm,
     ScanuResultInd, SmConnectInd, SmDisconnectInd, MmPsChangeInd, MmRssiStatusInd,
+    LmacRequest, StatusCfm, ScanuStartReq, SmConnectReq, SmDisconnectReq, MmAddIfReq, MmRemoveIfReq,
+    MmKeyAddReq, MmKeyDelReq, MmSetPowerReq, MmStaAddReq, MmStaDelReq, MmGetStaInfoReq,
+    ApmStartReq, ApmStopReq, MmSetPowerCfm,
+    build_dbg_rftest_cmd_req, parse_dbg_rftest_cmd_cfm, DbgRftestCmdReq, DbgRftestCmdCfm,
+    build_mm_version_req, parse_mm_version_cfm, MmVersionReq,
 };
+pub use lmac_struct::LmacStruct;
 pub use txrxif::{
     SDIO_TYPE_DATA, SDIO_TYPE_CFG, SDIO_TYPE_CFG_CMD_RSP, SDIO_TYPE_CFG_DATA_CFM, SDIO_TYPE_CFG_PRINT,
     CMD_BUF_MAX, MAX_RXQLEN, RX_HWHRD_LEN, IPC_RXBUF_CNT, IPC_RXDESC_CNT,
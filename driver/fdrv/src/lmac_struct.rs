@@ -0,0 +1,492 @@
+//! lmac_mac.h / lmac_msg.h 固件结构体的 Rust 定义
+//!
+//! 每个结构体按 C 侧（固件以自然对齐编译）布局逐字段展开为 `#[repr(C, packed)]`，对齐产生的空洞以 `_padN`
+//! 字段显式写出，并以编译期断言校验 size_of 与 C 侧 sizeof 一致。REQ 序列化与 CFM 解析统一经
+//! `LmacStruct::as_bytes` / `LmacStruct::from_bytes`，不再手写 `off += N`。
+//! 多字节字段按主机字节序存放，固件与所有目标平台均为小端（见文件末断言）。
+
+use core::mem::size_of;
+
+/// 固件结构体：纯数据、任意位模式合法，可直接按字节序列化/解析
+///
+/// # Safety
+/// 实现者必须为 `#[repr(C, packed)]` 且只含整数、整数数组或其它 `LmacStruct` 字段（bool 用 u8 表示）。
+pub unsafe trait LmacStruct: Copy + Sized {
+    /// 全零值（C 侧 memset(0)）
+    fn zeroed() -> Self {
+        // SAFETY: 由 trait 约定，全零位模式合法
+        unsafe { core::mem::zeroed() }
+    }
+
+    /// 按 C 布局取字节，长度为 sizeof
+    fn as_bytes(&self) -> &[u8] {
+        // SAFETY: packed 结构体无未初始化的 padding，按字节读取合法
+        unsafe { core::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>()) }
+    }
+
+    /// 从 buf 前 sizeof 字节解析；buf 不足 sizeof 返回 None
+    fn from_bytes(buf: &[u8]) -> Option<Self> {
+        if buf.len() < size_of::<Self>() {
+            return None;
+        }
+        // SAFETY: 长度已检查，任意位模式合法，read_unaligned 不要求对齐
+        Some(unsafe { core::ptr::read_unaligned(buf.as_ptr() as *const Self) })
+    }
+}
+
+/// 定义固件结构体：`#[repr(C, packed)]` + `LmacStruct` + 编译期 sizeof 断言
+macro_rules! lmac_struct {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident [size = $size:expr] {
+            $( $(#[$fmeta:meta])* $vis:vis $field:ident : $ty:ty ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy)]
+        #[repr(C, packed)]
+        pub struct $name {
+            $( $(#[$fmeta])* $vis $field: $ty ),*
+        }
+
+        // SAFETY: repr(C, packed)，字段均为整数/整数数组/LmacStruct
+        unsafe impl LmacStruct for $name {}
+
+        const _: () = assert!(size_of::<$name>() == $size);
+    };
+}
+
+/// SCAN_CHANNEL_MAX = MAC_DOMAINCHANNEL_24G_MAX + MAC_DOMAINCHANNEL_5G_MAX
+pub const SCAN_CHANNEL_MAX: usize = 42;
+/// SCAN_SSID_MAX
+pub const SCAN_SSID_MAX: usize = 3;
+/// MAC_SSID_LEN
+pub const MAC_SSID_LEN: usize = 32;
+/// MAC_RATESET_LEN
+pub const MAC_RATESET_LEN: usize = 12;
+/// MAC_SEC_KEY_LEN
+pub const MAC_SEC_KEY_LEN: usize = 32;
+/// sm_connect_req.ie_buf 长度（u32_l ie_buf[64]）
+pub const SM_CONNECT_IE_BUF_LEN: usize = 64 * 4;
+
+// ========== lmac_mac.h 基本类型 ==========
+
+lmac_struct! {
+    /// struct mac_chan_def：freq(u16), band, flags, tx_power(s8)；C 侧按 u16 对齐，尾部 1 字节填充
+    pub struct MacChanDef [size = 6] {
+        pub freq: u16,
+        pub band: u8,
+        pub flags: u8,
+        pub tx_power: i8,
+        _pad0: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mac_addr：u16_l array[3]，小端下与 6 字节 MAC 相同
+    pub struct MacAddr [size = 6] {
+        pub array: [u8; 6],
+    }
+}
+
+lmac_struct! {
+    /// struct mac_ssid：length + array[MAC_SSID_LEN]
+    pub struct MacSsid [size = 33] {
+        pub length: u8,
+        pub array: [u8; MAC_SSID_LEN],
+    }
+}
+
+lmac_struct! {
+    /// struct mac_rateset：length + array[MAC_RATESET_LEN]
+    pub struct MacRateset [size = 13] {
+        pub length: u8,
+        pub array: [u8; MAC_RATESET_LEN],
+    }
+}
+
+lmac_struct! {
+    /// struct mac_sec_key：length + u32_l array[MAC_SEC_KEY_LEN/4]；array 按 u32 对齐
+    pub struct MacSecKey [size = 36] {
+        pub length: u8,
+        _pad0: [u8; 3],
+        pub array: [u8; MAC_SEC_KEY_LEN],
+    }
+}
+
+impl MacAddr {
+    pub fn new(mac: &[u8; 6]) -> Self {
+        Self { array: *mac }
+    }
+}
+
+impl MacSsid {
+    /// 超过 MAC_SSID_LEN 的部分截断
+    pub fn new(ssid: &[u8]) -> Self {
+        let mut s = Self::zeroed();
+        let len = ssid.len().min(MAC_SSID_LEN);
+        s.length = len as u8;
+        s.array[..len].copy_from_slice(&ssid[..len]);
+        s
+    }
+}
+
+impl MacRateset {
+    /// 超过 MAC_RATESET_LEN 的部分截断
+    pub fn new(rates: &[u8]) -> Self {
+        let mut r = Self::zeroed();
+        let len = rates.len().min(MAC_RATESET_LEN);
+        r.length = len as u8;
+        r.array[..len].copy_from_slice(&rates[..len]);
+        r
+    }
+}
+
+impl MacSecKey {
+    /// 超过 MAC_SEC_KEY_LEN 的部分截断
+    pub fn new(key: &[u8]) -> Self {
+        let mut k = Self::zeroed();
+        let len = key.len().min(MAC_SEC_KEY_LEN);
+        k.length = len as u8;
+        k.array[..len].copy_from_slice(&key[..len]);
+        k
+    }
+}
+
+// ========== SCANU ==========
+
+lmac_struct! {
+    /// struct scanu_start_req
+    pub struct ScanuStartReqParam [size = 376] {
+        pub chan: [MacChanDef; SCAN_CHANNEL_MAX],
+        pub ssid: [MacSsid; SCAN_SSID_MAX],
+        _pad0: u8,
+        pub bssid: MacAddr,
+        _pad1: [u8; 2],
+        pub add_ies: u32,
+        pub add_ie_len: u16,
+        pub vif_idx: u8,
+        pub chan_cnt: u8,
+        pub ssid_cnt: u8,
+        pub no_cck: u8,
+        _pad2: [u8; 2],
+        pub duration: u32,
+    }
+}
+
+lmac_struct! {
+    /// struct scanu_result_ind 固定头部；payload（u32_l payload[]）按 u32 对齐，从 sizeof 处开始
+    pub struct ScanuResultIndHdr [size = 12] {
+        pub length: u16,
+        pub framectrl: u16,
+        pub center_freq: u16,
+        pub band: u8,
+        pub sta_idx: u8,
+        pub inst_nbr: u8,
+        pub rssi: i8,
+        _pad0: [u8; 2],
+    }
+}
+
+lmac_struct! {
+    /// struct scanu_start_cfm
+    pub struct ScanuStartCfmParam [size = 3] {
+        pub vif_idx: u8,
+        pub status: u8,
+        pub result_cnt: u8,
+    }
+}
+
+// ========== SM ==========
+
+lmac_struct! {
+    /// struct sm_connect_req
+    pub struct SmConnectReqParam [size = 320] {
+        pub ssid: MacSsid,
+        _pad0: u8,
+        pub bssid: MacAddr,
+        pub chan: MacChanDef,
+        _pad1: [u8; 2],
+        pub flags: u32,
+        pub ctrl_port_ethertype: u16,
+        pub ie_len: u16,
+        pub listen_interval: u16,
+        pub dont_wait_bcmc: u8,
+        pub auth_type: u8,
+        pub uapsd_queues: u8,
+        pub vif_idx: u8,
+        _pad2: [u8; 2],
+        pub ie_buf: [u8; SM_CONNECT_IE_BUF_LEN],
+    }
+}
+
+lmac_struct! {
+    /// struct sm_connect_cfm
+    pub struct SmConnectCfmParam [size = 1] {
+        pub status: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct sm_connect_ind 固定头部（assoc_ie_buf 之前的字段）
+    pub struct SmConnectIndHdr [size = 18] {
+        pub status_code: u16,
+        pub bssid: MacAddr,
+        pub roamed: u8,
+        pub vif_idx: u8,
+        pub ap_idx: u8,
+        pub ch_idx: u8,
+        pub qos: u8,
+        pub acm: u8,
+        pub assoc_req_ie_len: u16,
+        pub assoc_rsp_ie_len: u16,
+    }
+}
+
+lmac_struct! {
+    /// struct sm_disconnect_req：reason_code(u16), vif_idx；尾部按 u16 对齐填充
+    pub struct SmDisconnectReqParam [size = 4] {
+        pub reason_code: u16,
+        pub vif_idx: u8,
+        _pad0: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct sm_disconnect_ind
+    pub struct SmDisconnectIndParam [size = 4] {
+        pub reason_code: u16,
+        pub vif_idx: u8,
+        pub ft_over_ds: u8,
+    }
+}
+
+// ========== MM ==========
+
+lmac_struct! {
+    /// struct mm_add_if_req：type, addr(mac_addr，按 u16 对齐), p2p
+    pub struct MmAddIfReqParam [size = 10] {
+        pub vif_type: u8,
+        _pad0: u8,
+        pub addr: MacAddr,
+        pub p2p: u8,
+        _pad1: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_add_if_cfm
+    pub struct MmAddIfCfmParam [size = 2] {
+        pub status: u8,
+        pub inst_nbr: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_remove_if_req
+    pub struct MmRemoveIfReqParam [size = 1] {
+        pub inst_nbr: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_key_add_req：key(mac_sec_key) 按 u32 对齐
+    pub struct MmKeyAddReqParam [size = 44] {
+        pub key_idx: u8,
+        pub sta_idx: u8,
+        _pad0: [u8; 2],
+        pub key: MacSecKey,
+        pub cipher_suite: u8,
+        pub inst_nbr: u8,
+        pub spp: u8,
+        pub pairwise: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_key_add_cfm
+    pub struct MmKeyAddCfmParam [size = 2] {
+        pub status: u8,
+        pub hw_key_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_key_del_req
+    pub struct MmKeyDelReqParam [size = 1] {
+        pub hw_key_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_set_power_req
+    pub struct MmSetPowerReqParam [size = 2] {
+        pub inst_nbr: u8,
+        pub power: i8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_set_power_cfm
+    pub struct MmSetPowerCfmParam [size = 2] {
+        pub radio_idx: u8,
+        pub power: i8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_ps_change_ind
+    pub struct MmPsChangeIndParam [size = 2] {
+        pub sta_idx: u8,
+        pub ps_state: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_rssi_status_ind
+    pub struct MmRssiStatusIndParam [size = 3] {
+        pub vif_index: u8,
+        pub rssi_status: u8,
+        pub rssi: i8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_sta_add_req：三个 u32 A-MPDU/PAID 字段在前，mac_addr 按 u16 对齐，末尾补齐到 u32
+    pub struct MmStaAddReqParam [size = 28] {
+        pub ampdu_size_max_he: u32,
+        pub ampdu_size_max_vht: u32,
+        pub paid_gid: u32,
+        pub ampdu_size_max_ht: u16,
+        pub mac_addr: MacAddr,
+        pub ampdu_spacing_min: u8,
+        pub inst_nbr: u8,
+        pub tdls_sta: u8,
+        pub tdls_sta_initiator: u8,
+        pub tdls_chsw_allowed: u8,
+        pub bssid_index: u8,
+        pub max_bssid_ind: u8,
+        _pad0: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_sta_add_cfm
+    pub struct MmStaAddCfmParam [size = 3] {
+        pub status: u8,
+        pub sta_idx: u8,
+        pub hw_sta_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_sta_del_req
+    pub struct MmStaDelReqParam [size = 1] {
+        pub sta_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_get_sta_info_req
+    pub struct MmGetStaInfoReqParam [size = 1] {
+        pub sta_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_get_sta_info_cfm
+    pub struct MmGetStaInfoCfmParam [size = 32] {
+        pub rate_info: u32,
+        pub txfailed: u32,
+        pub rssi: u8,
+        pub reserved: [u8; 3],
+        pub chan_time: u32,
+        pub chan_busy_time: u32,
+        pub ack_fail_stat: u32,
+        pub ack_succ_stat: u32,
+        pub chan_tx_busy_time: u32,
+    }
+}
+
+lmac_struct! {
+    /// struct mm_version_cfm
+    pub struct MmVersionCfmParam [size = 28] {
+        pub version_lmac: u32,
+        pub version_machw_1: u32,
+        pub version_machw_2: u32,
+        pub version_phy_1: u32,
+        pub version_phy_2: u32,
+        pub features: u32,
+        pub max_sta_nb: u16,
+        pub max_vif_nb: u8,
+        _pad0: u8,
+    }
+}
+
+// ========== APM ==========
+
+lmac_struct! {
+    /// struct apm_start_req
+    pub struct ApmStartReqParam [size = 52] {
+        pub basic_rates: MacRateset,
+        _pad0: u8,
+        pub chan: MacChanDef,
+        pub center_freq1: u32,
+        pub center_freq2: u32,
+        pub ch_width: u8,
+        _pad1: [u8; 3],
+        pub bcn_addr: u32,
+        pub bcn_len: u16,
+        pub tim_oft: u16,
+        pub bcn_int: u16,
+        _pad2: [u8; 2],
+        pub flags: u32,
+        pub ctrl_port_ethertype: u16,
+        pub tim_len: u8,
+        pub vif_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct apm_start_cfm
+    pub struct ApmStartCfmParam [size = 4] {
+        pub status: u8,
+        pub vif_idx: u8,
+        pub ch_idx: u8,
+        pub bcmc_idx: u8,
+    }
+}
+
+lmac_struct! {
+    /// struct apm_stop_req
+    pub struct ApmStopReqParam [size = 1] {
+        pub vif_idx: u8,
+    }
+}
+
+// ========== DBG ==========
+
+/// dbg_rftest_cmd_req.argv 长度
+pub const RFTEST_ARGV_MAX: usize = 30;
+/// dbg_rftest_cmd_cfm.rftest_result 个数
+pub const RFTEST_RESULT_NUM: usize = 18;
+
+lmac_struct! {
+    /// struct dbg_rftest_cmd_req：cmd, argc, argv[30]；C 侧按 u32 对齐，尾部 2 字节填充
+    pub struct DbgRftestCmdReqParam [size = 40] {
+        pub cmd: u32,
+        pub argc: u32,
+        pub argv: [u8; RFTEST_ARGV_MAX],
+        _pad0: [u8; 2],
+    }
+}
+
+lmac_struct! {
+    /// struct dbg_rftest_cmd_cfm
+    pub struct DbgRftestCmdCfmParam [size = 72] {
+        pub rftest_result: [u32; RFTEST_RESULT_NUM],
+    }
+}
+
+// 多字节字段按主机字节序直接读写，要求与固件同为小端
+const _: () = assert!(cfg!(target_endian = "little"));
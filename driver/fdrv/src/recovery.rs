@@ -0,0 +1,43 @@
+//! 固件崩溃恢复事件：WiphyOpsImpl 在 bsp::aicbsp_wifi_recover 重载固件并重放接口/密钥/AP/连接后通知上层
+//! LicheeRV 无自动恢复（cmd_mgr CRASHED 后需重新加载模块），此处回调风格与 e2a_dispatch 一致
+
+use bsp::AicBspCrashInfo;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::wiphy_impl::STA_TABLE_LEN;
+
+/// 恢复完成事件
+#[derive(Debug, Clone, Copy)]
+pub struct RecoveryEvent {
+    /// 下电前采集的现场
+    pub crash: AicBspCrashInfo,
+    /// bsp::aicbsp_recovery_count()，含本次
+    pub recovery_count: u32,
+    /// 重放中失败的步骤数（0 表示状态完全恢复）
+    pub replay_failures: u32,
+    /// 崩溃前登记、恢复后 STA 表中不再有的对端 MAC（STA 模式下为所连 AP），按原表顺序排在前部
+    pub dropped_stas: [Option<[u8; 6]>; STA_TABLE_LEN],
+}
+
+/// 恢复完成回调：在调用 WiphyOps 的线程内、重放结束后调用
+pub type RecoveredCb = Option<unsafe fn(ev: &RecoveryEvent)>;
+
+static RECOVERED_CB: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// 注册恢复完成回调
+pub fn set_recovered_cb(cb: RecoveredCb) {
+    RECOVERED_CB.store(
+        cb.map(|f| f as *mut ()).unwrap_or(core::ptr::null_mut()),
+        Ordering::Relaxed,
+    );
+}
+
+pub(crate) fn notify_recovered(ev: &RecoveryEvent) {
+    let cb = RECOVERED_CB.load(Ordering::Relaxed);
+    if !cb.is_null() {
+        unsafe {
+            let f: unsafe fn(&RecoveryEvent) = core::mem::transmute(cb);
+            f(ev);
+        }
+    }
+}
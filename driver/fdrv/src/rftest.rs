@@ -0,0 +1,222 @@
+//! RF 测试命令（对应 LicheeRV aic_priv_cmd.c handle_private_cmd 中的 SET_TX/SET_RX/... 与 rwnx_send_rftest_req）
+//!
+//! 仅在 AicBspCpMode::Test 下可用：rftest_boot 以测试模式上电，BSP 将 RF 测试固件下载到 RAM_LMAC_FW_ADDR 并启动；
+//! 之后各命令经 DBG_RFTEST_CMD_REQ 下发，需要读回结果的命令等待 DBG_RFTEST_CMD_CFM。
+//! 非测试模式下调用返回 -EPERM。
+
+use bsp::{aicbsp_get_cpmode, aicbsp_init, aicbsp_set_subsys, AicBspCpMode, AicBspInfo, AicBspPwrState, AicBspSubsys};
+
+use crate::lmac_cmd::{build_dbg_rftest_cmd_req, DbgRftestCmdCfm, DbgRftestCmdReq};
+use crate::wiphy_impl::{send, send_lmac_msg_nowait};
+
+/// RF 测试命令号（aic_priv_cmd.c 中 enum 顺序）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum RfTestCmd {
+    SetTx = 0,
+    SetTxStop = 1,
+    SetTxTone = 2,
+    SetRx = 3,
+    GetRxResult = 4,
+    SetRxStop = 5,
+    SetRxMeter = 6,
+    SetPower = 7,
+    SetXtalCap = 8,
+    SetXtalCapFine = 9,
+    GetEfuseBlock = 10,
+    SetFreqCal = 11,
+    SetFreqCalFine = 12,
+    GetFreqCal = 13,
+    SetMacAddr = 14,
+    GetMacAddr = 15,
+    SetBtMacAddr = 16,
+    GetBtMacAddr = 17,
+    SetVendorInfo = 18,
+    GetVendorInfo = 19,
+    RdwrPwrmm = 20,
+    RdwrPwridx = 21,
+    RdwrPwrofst = 22,
+}
+
+/// 信道带宽（SET_TX/SET_RX 的 bw 参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RfBandwidth {
+    Bw20 = 0,
+    Bw40 = 1,
+    Bw80 = 2,
+}
+
+/// 发包调制格式（SET_TX 的 mode 参数，即 formatmod）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RfTxMode {
+    NonHt = 0,
+    HtMf = 2,
+    Vht = 4,
+    HeSu = 5,
+}
+
+/// 功率表频段（RDWR_PWRIDX/RDWR_PWROFST 的 band 参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RfBand {
+    Band2G4 = 1,
+    Band5G = 2,
+}
+
+/// 连续发包参数
+#[derive(Debug, Clone, Copy)]
+pub struct RfTxParams {
+    pub channel: u8,
+    pub bw: RfBandwidth,
+    pub mode: RfTxMode,
+    /// 速率索引（non-HT 为 legacy rate idx，HT/VHT/HE 为 MCS）
+    pub rate: u8,
+    /// 包长（字节）
+    pub length: u16,
+    /// 发包间隔（us）
+    pub interval_us: u16,
+    /// 发包功率（dBm）
+    pub power_dbm: i8,
+}
+
+/// 收包统计（GET_RX_RESULT：result[0]=fcs_ok，result[1]=total）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RfRxResult {
+    pub fcs_ok: u32,
+    pub total: u32,
+}
+
+impl RfRxResult {
+    pub fn fcs_err(&self) -> u32 {
+        self.total.saturating_sub(self.fcs_ok)
+    }
+}
+
+/// 非测试模式拒绝 RF 命令
+fn check_testmode() -> Result<(), i32> {
+    if aicbsp_get_cpmode() != AicBspCpMode::Test {
+        log::warn!(target: "wireless::fdrv", "rftest: not in test mode");
+        return Err(-1);
+    }
+    Ok(())
+}
+
+/// rwnx_send_rftest_req(reqcfm=false)：只发不等
+fn rftest_send_nowait(cmd: RfTestCmd, argv: &[u8]) -> Result<(), i32> {
+    check_testmode()?;
+    let msg = build_dbg_rftest_cmd_req(cmd as u32, argv).ok_or(-22)?;
+    send_lmac_msg_nowait(&msg)
+}
+
+/// rwnx_send_rftest_req(reqcfm=true)：等待 DBG_RFTEST_CMD_CFM
+fn rftest_send(cmd: RfTestCmd, argv: &[u8]) -> Result<DbgRftestCmdCfm, i32> {
+    check_testmode()?;
+    send(&DbgRftestCmdReq { cmd: cmd as u32, argv })
+}
+
+/// 以 RF 测试模式上电并加载测试固件（aicbsp_init(Test) + WiFi 上电）
+pub fn rftest_boot(info: &mut AicBspInfo) -> Result<(), i32> {
+    aicbsp_init(info, AicBspCpMode::Test).map_err(|_| -5)?;
+    aicbsp_set_subsys(AicBspSubsys::Wifi, AicBspPwrState::On).map_err(|_| -5)
+}
+
+/// SET_POWER + SET_TX：开始连续发包
+pub fn rftest_tx_start(p: &RfTxParams) -> Result<(), i32> {
+    rftest_set_power(p.power_dbm)?;
+    let len = p.length.to_le_bytes();
+    let intv = p.interval_us.to_le_bytes();
+    let argv = [p.channel, p.bw as u8, p.mode as u8, p.rate, len[0], len[1], intv[0], intv[1]];
+    rftest_send_nowait(RfTestCmd::SetTx, &argv)
+}
+
+/// SET_TXSTOP
+pub fn rftest_tx_stop() -> Result<(), i32> {
+    rftest_send_nowait(RfTestCmd::SetTxStop, &[])
+}
+
+/// SET_POWER：发包功率（dBm）
+pub fn rftest_set_power(dbm: i8) -> Result<(), i32> {
+    rftest_send_nowait(RfTestCmd::SetPower, &[dbm as u8])
+}
+
+/// SET_TXTONE：Some(freq_offset) 开单音，None 关闭
+pub fn rftest_tx_tone(freq_offset: Option<i8>) -> Result<(), i32> {
+    match freq_offset {
+        Some(ofst) => rftest_send_nowait(RfTestCmd::SetTxTone, &[1, ofst as u8]),
+        None => rftest_send_nowait(RfTestCmd::SetTxTone, &[0]),
+    }
+}
+
+/// SET_RX：在指定信道/带宽收包计数
+pub fn rftest_rx_start(channel: u8, bw: RfBandwidth) -> Result<(), i32> {
+    rftest_send_nowait(RfTestCmd::SetRx, &[channel, bw as u8])
+}
+
+/// SET_RXSTOP
+pub fn rftest_rx_stop() -> Result<(), i32> {
+    rftest_send_nowait(RfTestCmd::SetRxStop, &[])
+}
+
+/// GET_RX_RESULT
+pub fn rftest_rx_result() -> Result<RfRxResult, i32> {
+    let cfm = rftest_send(RfTestCmd::GetRxResult, &[])?;
+    Ok(RfRxResult {
+        fcs_ok: cfm.result[0],
+        total: cfm.result[1],
+    })
+}
+
+/// SET_XTAL_CAP：返回固件写入后的 xtal cap
+pub fn rftest_set_xtal_cap(val: u8) -> Result<u32, i32> {
+    rftest_send(RfTestCmd::SetXtalCap, &[val]).map(|c| c.result[0])
+}
+
+/// SET_XTAL_CAP_FINE：返回固件写入后的 xtal cap fine
+pub fn rftest_set_xtal_cap_fine(val: u8) -> Result<u32, i32> {
+    rftest_send(RfTestCmd::SetXtalCapFine, &[val]).map(|c| c.result[0])
+}
+
+/// SET_FREQ_CAL：返回固件写入后的 freq cal
+pub fn rftest_set_freq_cal(val: u8) -> Result<u32, i32> {
+    rftest_send(RfTestCmd::SetFreqCal, &[val]).map(|c| c.result[0])
+}
+
+/// SET_FREQ_CAL_FINE：返回固件写入后的 freq cal fine
+pub fn rftest_set_freq_cal_fine(val: u8) -> Result<u32, i32> {
+    rftest_send(RfTestCmd::SetFreqCalFine, &[val]).map(|c| c.result[0])
+}
+
+/// GET_FREQ_CAL：result[0] 为 efuse 中的校准值
+pub fn rftest_get_freq_cal() -> Result<u32, i32> {
+    rftest_send(RfTestCmd::GetFreqCal, &[]).map(|c| c.result[0])
+}
+
+/// RDWR_PWRIDX 读：返回整张功率索引表
+pub fn rftest_read_power_index() -> Result<DbgRftestCmdCfm, i32> {
+    rftest_send(RfTestCmd::RdwrPwridx, &[])
+}
+
+/// RDWR_PWRIDX 写：band 下 mod_type（<12）的功率索引
+pub fn rftest_write_power_index(band: RfBand, mod_type: u8, idx: u8) -> Result<DbgRftestCmdCfm, i32> {
+    if mod_type >= 12 {
+        return Err(-22);
+    }
+    rftest_send(RfTestCmd::RdwrPwridx, &[band as u8, mod_type, idx])
+}
+
+/// RDWR_PWROFST 读：返回整张功率偏移表
+pub fn rftest_read_power_offset() -> Result<DbgRftestCmdCfm, i32> {
+    rftest_send(RfTestCmd::RdwrPwrofst, &[])
+}
+
+/// RDWR_PWROFST 写：band 下信道组 ch_grp 的功率偏移（dB）
+pub fn rftest_write_power_offset(band: RfBand, ch_grp: u8, ofst: i8) -> Result<DbgRftestCmdCfm, i32> {
+    rftest_send(RfTestCmd::RdwrPwrofst, &[band as u8, ch_grp, ofst as u8])
+}
+
+/// 任意 RF 测试命令（需要 CFM），供未封装的命令（如 GET_EFUSE_BLOCK、SET_VENDOR_INFO）使用
+pub fn rftest_cmd(cmd: RfTestCmd, argv: &[u8]) -> Result<DbgRftestCmdCfm, i32> {
+    rftest_send(cmd, argv)
+}
@@ -51,13 +51,13 @@ pub const FLOW_CTRL_RETRY_COUNT: u32 = 50;
 #[allow(dead_code)]
 pub const CMD_BUF_MAX: usize = 1536;
 
-/// 数据 TX 块大小（对应 TXPKT_BLOCKSIZE）
+/// 数据 TX 块大小（对应 TXPKT_BLOCKSIZE，聚合发送在 BSP tx_aggr）
 #[allow(dead_code)]
-pub const TXPKT_BLOCKSIZE: u32 = 512;
+pub const TXPKT_BLOCKSIZE: u32 = bsp::TXPKT_BLOCKSIZE as u32;
 
 /// 最大聚合 TX 长度（对应 MAX_AGGR_TXPKT_LEN）
 #[allow(dead_code)]
-pub const MAX_AGGR_TXPKT_LEN: usize = 1536 * 64;
+pub const MAX_AGGR_TXPKT_LEN: usize = bsp::MAX_AGGR_TXPKT_LEN;
 
 /// TX 队列数（对应 NX_TXQ_CNT，Makefile 中 4 或 5）
 pub const NX_TXQ_CNT: usize = 4;
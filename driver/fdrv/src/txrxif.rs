@@ -45,7 +45,8 @@ pub fn fdrv_rx_data_invoke(buf: *const u8, len: usize) {
     }
 }
 
-/// 提交数据包发送（全局入口，对应 aicwf_frame_tx）。需由 BSP 或平台实现：入队到 txdata 队列并触发 bustx
-pub fn tx_data(_buf: &[u8]) -> Result<(), i32> {
-    Err(-38) // -ENOSYS 占位
+/// 提交数据包发送（全局入口，对应 aicwf_frame_tx -> aicwf_bus_txdata）。buf 为 txdesc_api + 802.3 payload，
+/// 入 BSP txdata 队列并触发 bustx；bustx 按固件空闲缓冲把排队的帧聚合成一次 CMD53（aicwf_sdio_aggr）
+pub fn tx_data(buf: &[u8]) -> Result<(), i32> {
+    bsp::aicbsp_txdata_enqueue(buf)
 }
@@ -19,3 +19,12 @@ pub enum VendorSubcmd {
     GetRssi = 7,
     GetLinkSpeed = 8,
 }
+
+/// GetVersion：发送 MM_VERSION_REQ（rwnx_send_version_req），结果缓存到 BSP 的 ChipInfo 后返回
+pub fn aic_vendor_get_version() -> Result<bsp::ChipInfo, i32> {
+    let version = crate::wiphy_impl::send(&crate::lmac_cmd::MmVersionReq)?;
+    bsp::aicbsp_set_fw_version(version);
+    let info = bsp::aicbsp_chip_info().ok_or(-19)?;
+    log::info!(target: "wireless::fdrv", "{}", info);
+    Ok(info)
+}
@@ -2,34 +2,32 @@
 //! 含 scan/connect/disconnect、start_ap/stop_ap、add_key/del_key/set_default_key、get_station
 
 use bsp::{
-    aicbsp_current_product_id, submit_cmd_tx_and_wait_tx_done, with_cmd_mgr, sdio_poll_rx_once,
-    LmacMsg, ProductId, RwnxCmdMgr,
-    SCANU_START_CFM, SM_CONNECT_CFM, SM_DISCONNECT_CFM,
-    MM_ADD_IF_CFM, MM_REMOVE_IF_CFM, MM_STA_ADD_CFM, MM_STA_DEL_CFM,
-    MM_KEY_ADD_CFM, MM_KEY_DEL_CFM, MM_GET_STA_INFO_CFM, MM_SET_POWER_CFM,
-    APM_START_CFM, APM_STOP_CFM,
-    RWNX_80211_CMD_TIMEOUT_MS,
+    aicbsp_current_product_id, aicbsp_recovery_count, aicbsp_wifi_crashed, aicbsp_wifi_recover, submit_cmd_tx_and_wait_tx_done, with_cmd_mgr, sdio_poll_rx_once, ipc_serialize_lmac_msg,
+    cmd_flags, CmdHandle, LmacMsg, RwnxCmdMgr, PENDING_CMD_TX_CAP, RWNX_80211_CMD_TIMEOUT_MS,
 };
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
 use core::result::Result;
+use core::task::{Context, Poll};
 
 use crate::lmac_cmd::{
-    build_mm_sta_add_req, build_mm_sta_del_req, build_scanu_start_req, build_sm_connect_req,
-    build_sm_disconnect_req, build_mm_add_if_req, build_mm_remove_if_req,
-    build_mm_key_add_req, build_mm_key_del_req, build_mm_get_sta_info_req,
-    build_apm_start_req, build_apm_stop_req, build_mm_set_power_req,
-    parse_mm_add_if_cfm, parse_mm_key_add_cfm, parse_mm_get_sta_info_cfm, parse_apm_start_cfm,
-    parse_sm_connect_cfm, parse_mm_set_power_cfm, parse_mm_sta_add_cfm, parse_mm_sta_del_cfm,
-    MacVifType,
+    ApmStartReq, ApmStopReq, LmacRequest, MacVifType, MmAddIfReq, MmGetStaInfoReq, MmKeyAddReq,
+    MmKeyDelReq, MmRemoveIfReq, MmSetPowerReq, MmStaAddReq, MmStaDelReq, ScanuStartReq,
+    SmConnectReq, SmDisconnectReq,
 };
 use ieee80211::{KeyStatus, StationInfo, wlan_cipher_to_mac, nl80211_sta_info};
+use crate::recovery::{notify_recovered, RecoveryEvent};
 use crate::wiphy::{InterfaceId, IfaceType, WiphyOps};
 
 /// 最大 VIF 数
 const MAX_VIF: usize = 4;
 /// 每 VIF 最大密钥槽位
 const MAX_KEYS_PER_VIF: usize = 8;
+/// RSN 中成对密钥（PTK）固定使用 Key ID 0，组密钥用 1..=3、IGTK 用 4..=5、BIGTK 用 6..=7（IEEE 802.11 12.7.2）
+const PAIRWISE_KEY_INDEX: u8 = 0;
 /// STA 表项数（mac -> sta_idx）
-const STA_TABLE_LEN: usize = 16;
+pub(crate) const STA_TABLE_LEN: usize = 16;
 
 /// 单条 STA 表项
 #[derive(Clone, Copy, Default)]
@@ -39,7 +37,47 @@ struct StaEntry {
     used: bool,
 }
 
-/// 内部状态：密钥 hw_key_idx、默认密钥、STA 表、上次设置的 TX 功率、当前信道、默认 mgmt 密钥
+/// SSID 最大长度（IEEE80211_MAX_SSID_LEN）
+const MAX_SSID_LEN: usize = 32;
+/// 密钥最大长度（WLAN_MAX_KEY_LEN）
+const MAX_KEY_LEN: usize = 32;
+
+/// 定长保存的 SSID/密钥字节，供崩溃恢复重放
+#[derive(Clone, Copy)]
+struct ReplayBytes<const N: usize> {
+    len: u8,
+    data: [u8; N],
+}
+
+impl<const N: usize> ReplayBytes<N> {
+    fn new(src: &[u8]) -> Self {
+        let len = src.len().min(N);
+        let mut data = [0u8; N];
+        data[..len].copy_from_slice(&src[..len]);
+        Self { len: len as u8, data }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+/// start_ap 参数，崩溃恢复时重放
+#[derive(Clone, Copy)]
+struct ApConfig {
+    ssid: ReplayBytes<MAX_SSID_LEN>,
+    channel: u8,
+}
+
+/// connect 参数，崩溃恢复时重放
+#[derive(Clone, Copy)]
+struct ConnConfig {
+    ssid: ReplayBytes<MAX_SSID_LEN>,
+    bssid: Option<[u8; 6]>,
+}
+
+/// 内部状态：密钥 hw_key_idx、默认密钥、STA 表、上次设置的 TX 功率、当前信道、默认 mgmt 密钥，
+/// 以及崩溃恢复需重放的接口类型、密钥、AP 配置与连接参数
 struct WiphyState {
     /// key_hw[vif_id][key_index] = hw_key_idx from MM_KEY_ADD_CFM
     key_hw: [[Option<u8>; MAX_KEYS_PER_VIF]; MAX_VIF],
@@ -51,6 +89,12 @@ struct WiphyState {
     last_tx_power_dbm: [Option<i8>; MAX_VIF],
     /// start_ap/connect 后保存，get_channel 返回
     current_channel: [Option<u8>; MAX_VIF],
+    /// add_interface 成功的接口类型
+    vif_type: [Option<IfaceType>; MAX_VIF],
+    /// add_key 的原始密钥（hw_key_idx 在重放时由新的 MM_KEY_ADD_CFM 重新分配）
+    key_data: [[Option<ReplayBytes<MAX_KEY_LEN>>; MAX_KEYS_PER_VIF]; MAX_VIF],
+    ap_config: [Option<ApConfig>; MAX_VIF],
+    connection: [Option<ConnConfig>; MAX_VIF],
 }
 
 impl Default for WiphyState {
@@ -62,79 +106,157 @@ impl Default for WiphyState {
             sta_table: [StaEntry::default(); STA_TABLE_LEN],
             last_tx_power_dbm: [None; MAX_VIF],
             current_channel: [None; MAX_VIF],
+            vif_type: [None; MAX_VIF],
+            key_data: [[None; MAX_KEYS_PER_VIF]; MAX_VIF],
+            ap_config: [None; MAX_VIF],
+            connection: [None; MAX_VIF],
         }
     }
 }
 
-/// 8801 IPC 发送长度：与 BSP flow::ipc_send_len_8801 一致
-fn ipc_send_len_8801(serialized_len: usize) -> usize {
-    const TX_ALIGNMENT: usize = 4;
-    const BLOCK: usize = 512;
-    const TAIL_LEN: usize = 4;
-    let len4 = (serialized_len + TX_ALIGNMENT - 1) / TX_ALIGNMENT * TX_ALIGNMENT;
-    if len4 % BLOCK == 0 {
-        len4
-    } else {
-        ((len4 + TAIL_LEN + BLOCK - 1) / BLOCK) * BLOCK
+/// 发送 REQ 并立即返回 CmdHandle（只等 CMD53 写完，不等 CFM）。
+/// 返回的句柄可 `.await`，由 busrx 收到 CFM 时唤醒，便于同一任务同时挂起多条命令；
+/// 也可交给 wait_lmac_cfm 阻塞等待。
+pub fn send_lmac_cmd_async(msg: &LmacMsg, cfm_id: u16) -> Result<CmdHandle, i32> {
+    send_lmac_cmd_flags(msg, cfm_id, cmd_flags::REQ_CFM | cmd_flags::WAIT_ACK)
+}
+
+/// 以指定 cmd_flags 入队并发送（对应 rwnx_send_msg 按 reqcfm/nonblock 组装 cmd->flags）。
+/// 带 WAIT_ACK 时 CMD53 写完即调用 on_ack；不带 REQ_CFM 的命令以此为完成点。
+pub fn send_lmac_cmd_flags(msg: &LmacMsg, cfm_id: u16, flags: u16) -> Result<CmdHandle, i32> {
+    let mut buf = [0u8; PENDING_CMD_TX_CAP];
+    let send_len = serialize_lmac_msg(msg, &mut buf)?;
+    let handle = with_cmd_mgr(|c| c.push_handle(cfm_id, flags)).flatten().ok_or(-12)?;
+    // 发送失败时 handle 在返回前 drop，由 CmdHandle::drop 取消命令、释放 slot
+    submit_cmd_tx_and_wait_tx_done(&buf[..send_len], send_len)?;
+    if flags & cmd_flags::WAIT_ACK != 0 {
+        with_cmd_mgr(|c| c.on_ack(handle.token()));
     }
+    Ok(handle)
 }
 
-fn send_lmac_cmd_and_wait_cfm(
-    msg: &LmacMsg,
-    cfm_id: u16,
-    timeout_ms: u32,
-) -> Result<(), i32> {
-    send_lmac_cmd_and_wait_cfm_with_buf(msg, cfm_id, timeout_ms, &mut [0u8; 64]).map(|_| ())
+/// 只发不等（fire-and-forget）：NONBLOCK | WAIT_ACK，CMD53 写完即完成并释放 slot，不等 CFM。
+/// 用于 LicheeRV 中 reqcfm=false 的消息（如节能提示、TX credit 更新）；仍经 cmd_mgr 入队以遵守队列深度与 Crashed 状态。
+pub fn send_lmac_msg_nowait(msg: &LmacMsg) -> Result<(), i32> {
+    let mut buf = [0u8; PENDING_CMD_TX_CAP];
+    let send_len = serialize_lmac_msg(msg, &mut buf)?;
+    let token = with_cmd_mgr(|c| c.push(msg.header.id, cmd_flags::NONBLOCK | cmd_flags::WAIT_ACK))
+        .flatten()
+        .ok_or(-12)?;
+    let result = submit_cmd_tx_and_wait_tx_done(&buf[..send_len], send_len);
+    // 失败时同样以 on_ack 释放 slot（NONBLOCK 无人 take_cfm），错误直接返回调用方
+    with_cmd_mgr(|c| c.on_ack(token));
+    result
 }
 
-/// 发送 REQ 并等待 CFM，将 CFM 的 param 写入 cfm_buf，返回写入长度
-fn send_lmac_cmd_and_wait_cfm_with_buf(
-    msg: &LmacMsg,
-    cfm_id: u16,
-    timeout_ms: u32,
-    cfm_buf: &mut [u8],
-) -> Result<usize, i32> {
+/// 按芯片序列化 A2E 消息并补齐到实际发送长度（与 BSP 共用 ipc_serialize_lmac_msg：8801 为 16B 头 + 对齐，其余为 lmac_msg 原样）
+fn serialize_lmac_msg(msg: &LmacMsg, buf: &mut [u8; PENDING_CMD_TX_CAP]) -> Result<usize, i32> {
     let product_id = aicbsp_current_product_id().ok_or(-22)?;
-    let mut buf = [0u8; 512];
-    let len = if product_id == ProductId::Aic8801 {
-        msg.serialize_8801(&mut buf)
-    } else {
-        msg.serialize(&mut buf)
-    };
-    let send_len = if product_id == ProductId::Aic8801 {
-        ipc_send_len_8801(len)
-    } else {
-        len
-    };
-    if send_len > buf.len() {
-        return Err(-22);
-    }
-    if send_len > len {
-        buf[len..send_len].fill(0);
-    }
-    let token = with_cmd_mgr(|c| c.push(cfm_id)).flatten().ok_or(-12)?;
-    submit_cmd_tx_and_wait_tx_done(&buf[..send_len], send_len)?;
+    ipc_serialize_lmac_msg(product_id, msg, buf).map(|(_, send_len)| send_len)
+}
+
+/// 阻塞等待 send_lmac_cmd_async 返回的句柄完成，将 CFM 的 param 写入 cfm_buf，返回写入长度（CFM 长于 cfm_buf 时截断）
+pub fn wait_lmac_cfm(handle: CmdHandle, timeout_ms: u32, cfm_buf: &mut [u8]) -> Result<usize, i32> {
+    let cfm = wait_lmac_cfm_vec(handle, timeout_ms)?;
+    let n = cfm.len().min(cfm_buf.len());
+    cfm_buf[..n].copy_from_slice(&cfm[..n]);
+    Ok(n)
+}
+
+/// 阻塞等待句柄完成并返回完整 CFM param（堆上，长度不受 RWNX_CMD_E2AMSG_LEN_MAX 限制）
+pub fn wait_lmac_cfm_vec(handle: CmdHandle, timeout_ms: u32) -> Result<Vec<u8>, i32> {
+    let token = handle.token();
     let mut poll = || sdio_poll_rx_once();
-    RwnxCmdMgr::wait_done_until(
+    if let Err(e) = RwnxCmdMgr::wait_done_until(
         timeout_ms,
         || with_cmd_mgr(|c| c.is_done(token)).unwrap_or(false),
         None,
         Some(&mut poll),
         None,
-    )?;
-    let n = with_cmd_mgr(|c| c.take_cfm(token, cfm_buf)).flatten().ok_or(-5)?;
-    Ok(n)
+    ) {
+        // 与 LicheeRV cmd_mgr_queue 超时分支一致：cmd_complete + state=CRASHED，后续命令由 WiphyOpsImpl 触发恢复
+        with_cmd_mgr(|c| c.complete_timeout(token));
+        return Err(e);
+    }
+    with_cmd_mgr(|c| c.take_cfm_vec(token)).flatten().ok_or(-5)
+}
+
+/// 发送 LmacRequest 并等待其 CFM，按 R::decode_cfm 解码返回。
+/// 与 LicheeRV rwnx_send_msg(reqcfm=true) 一致；CFM 按实际长度整段交给 decode_cfm，解码失败（CFM 过短）返回 -EIO 并打日志。
+pub fn send<R: LmacRequest>(req: &R) -> Result<R::Cfm, i32> {
+    let msg = req.build().ok_or(-22)?;
+    let handle = send_lmac_cmd_async(&msg, R::CFM_ID)?;
+    let cfm = wait_lmac_cfm_vec(handle, RWNX_80211_CMD_TIMEOUT_MS)?;
+    decode_lmac_cfm(R::CFM_ID, R::decode_cfm, &cfm)
+}
+
+/// send 的非阻塞版本：发出 REQ 后立即返回 Future，CFM 到达时按 R::decode_cfm 解码完成。
+/// 超时 RWNX_80211_CMD_TIMEOUT_MS，见 send_async_timeout。
+pub fn send_async<R: LmacRequest>(req: &R) -> LmacCfmFuture<R::Cfm> {
+    send_async_timeout(req, RWNX_80211_CMD_TIMEOUT_MS)
+}
+
+/// 同 send_async，指定超时：由 cmd_mgr 的 set_timeout/tick_timeouts 计时，到期走 complete_timeout（state=CRASHED），
+/// Future 以 -ETIMEDOUT 完成，与 send 超时后的状态一致。
+pub fn send_async_timeout<R: LmacRequest>(req: &R, timeout_ms: u32) -> LmacCfmFuture<R::Cfm> {
+    LmacCfmFuture::new(req.build().ok_or(-22), R::CFM_ID, timeout_ms, R::decode_cfm)
+}
+
+fn decode_lmac_cfm<C>(cfm_id: u16, decode: fn(&[u8]) -> Option<C>, cfm: &[u8]) -> Result<C, i32> {
+    decode(cfm).ok_or_else(|| {
+        log::warn!(target: "wireless::fdrv", "send: CFM 0x{:04x} too short to decode (len={})", cfm_id, cfm.len());
+        -5
+    })
+}
+
+/// send_async 返回的 Future：持有已发出命令的 CmdHandle，由 busrx 收到 CFM（或超时）时唤醒。
+/// 不借用请求与 WiphyOpsImpl，同一任务可同时挂起多条（如 scan 期间查询 get_station），完成顺序取决于固件应答顺序。
+/// 发送失败时首次 poll 即返回错误；drop 未完成的 Future 即取消命令、释放 slot。
+pub struct LmacCfmFuture<C> {
+    sent: Result<CmdHandle, i32>,
+    cfm_id: u16,
+    decode: fn(&[u8]) -> Option<C>,
+}
+
+impl<C> LmacCfmFuture<C> {
+    fn new(msg: Result<LmacMsg, i32>, cfm_id: u16, timeout_ms: u32, decode: fn(&[u8]) -> Option<C>) -> Self {
+        let sent = msg.and_then(|msg| send_lmac_cmd_async(&msg, cfm_id)).inspect(|handle| {
+            with_cmd_mgr(|c| c.set_timeout(handle, timeout_ms));
+        });
+        Self { sent, cfm_id, decode }
+    }
+
+    fn failed(err: i32, cfm_id: u16, decode: fn(&[u8]) -> Option<C>) -> Self {
+        Self { sent: Err(err), cfm_id, decode }
+    }
+}
+
+impl<C> Future for LmacCfmFuture<C> {
+    type Output = Result<C, i32>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let handle = match this.sent {
+            Ok(ref mut handle) => handle,
+            Err(e) => return Poll::Ready(Err(e)),
+        };
+        let cfm = core::task::ready!(Pin::new(handle).poll(cx))?;
+        Poll::Ready(decode_lmac_cfm(this.cfm_id, this.decode, cfm.as_bytes()))
+    }
 }
 
 /// WiphyOps 真实实现：基于 BSP IPC 与 lmac_cmd 构建/解析，带 key/sta 状态
 pub struct WiphyOpsImpl {
     state: WiphyState,
+    /// recover 重放期间为 true：重放中再次超时不递归恢复，直接返回错误
+    recovering: bool,
 }
 
 impl WiphyOpsImpl {
     pub fn new() -> Self {
         Self {
             state: WiphyState::default(),
+            recovering: false,
         }
     }
 
@@ -173,6 +295,134 @@ impl WiphyOpsImpl {
         None
     }
 
+    /// 若 cmd_mgr 已因命令超时进入 Crashed，则先执行恢复；供每个下发 LMAC 命令的操作入口调用
+    fn ensure_fw_alive(&mut self) -> Result<(), i32> {
+        if aicbsp_wifi_crashed() {
+            if self.recovering {
+                return Err(-5);
+            }
+            self.recover()?;
+        }
+        Ok(())
+    }
+
+    /// 崩溃恢复：bsp::aicbsp_wifi_recover（采集现场 → 下电 → 上电 + driver_fw_init），
+    /// 再按崩溃前的 WiphyState 依次重放 add_interface、set_tx_power、start_ap、connect、组密钥与默认密钥，
+    /// 最后通过 set_recovered_cb 注册的回调上报。STA 表不重放：登记的 sta_idx 随固件重启失效，一律清空
+    /// （start_ap 重放时重新登记 bcmc），其余对端经 RecoveryEvent::dropped_stas 上报，
+    /// 重新关联（SM_CONNECT_IND）后再由 register_sta_from_connect_ind 登记。
+    ///
+    /// 密钥在 start_ap/connect 之后重放，且只重放组/默认密钥：STA 重新关联后 PTK 由四次握手重新协商，
+    /// 崩溃前的 PTK 已作废，重放反而让固件先用旧 PTK 加密 EAPOL。
+    pub fn recover(&mut self) -> Result<(), i32> {
+        let crash = aicbsp_wifi_recover().map_err(|e| {
+            log::error!(target: "wireless::fdrv", "WiphyOpsImpl recover: aicbsp_wifi_recover failed {:?}", e);
+            -5
+        })?;
+        let old = core::mem::take(&mut self.state);
+        self.recovering = true;
+        let mut replay_failures = 0u32;
+        for vif in 0..MAX_VIF {
+            let iface_type = match old.vif_type[vif] {
+                Some(t) => t,
+                None => continue,
+            };
+            let id = match self.add_interface(iface_type) {
+                Ok(id) if (id as usize) < MAX_VIF => id,
+                Ok(id) => {
+                    log::warn!(target: "wireless::fdrv", "WiphyOpsImpl recover: vif {} re-added as {} (out of range)", vif, id);
+                    replay_failures += 1;
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!(target: "wireless::fdrv", "WiphyOpsImpl recover: add_interface vif={} err={}", vif, e);
+                    replay_failures += 1;
+                    continue;
+                }
+            };
+            if id as usize != vif {
+                log::warn!(target: "wireless::fdrv", "WiphyOpsImpl recover: vif {} re-added as {}", vif, id);
+            }
+            if let Some(dbm) = old.last_tx_power_dbm[vif] {
+                let mbm = if dbm == 0x7f { i32::MAX } else { dbm as i32 * 100 };
+                if self.set_tx_power(id, mbm).is_err() {
+                    replay_failures += 1;
+                }
+            }
+            if let Some(ap) = old.ap_config[vif] {
+                if self.start_ap(id, ap.ssid.as_bytes(), ap.channel).is_err() {
+                    replay_failures += 1;
+                }
+            }
+            let connected = match old.connection[vif] {
+                Some(conn) => {
+                    if self.connect(id, conn.ssid.as_bytes(), conn.bssid.as_ref()).is_err() {
+                        replay_failures += 1;
+                    }
+                    true
+                }
+                None => false,
+            };
+            for (key_index, key) in old.key_data[vif].iter().enumerate() {
+                let Some(key) = key else {
+                    continue;
+                };
+                if connected && key_index as u8 == PAIRWISE_KEY_INDEX {
+                    log::info!(target: "wireless::fdrv", "WiphyOpsImpl recover: vif {} PTK left to the 4-way handshake", vif);
+                    continue;
+                }
+                if self.add_key(id, key_index as u8, key.as_bytes()).is_err() {
+                    replay_failures += 1;
+                }
+            }
+            self.state.default_key[id as usize] = old.default_key[vif];
+            self.state.default_mgmt_key[id as usize] = old.default_mgmt_key[vif];
+        }
+        self.recovering = false;
+        // 重放后仍未登记的旧表项（start_ap 会重新登记 bcmc）即为丢弃的对端
+        let mut dropped_stas = [None; STA_TABLE_LEN];
+        let dropped = old.sta_table.iter().filter(|e| e.used && self.lookup_sta_idx(&e.mac).is_none());
+        for (dst, e) in dropped_stas.iter_mut().zip(dropped) {
+            log::info!(target: "wireless::fdrv", "WiphyOpsImpl recover: sta {:02x?} (sta_idx {}) dropped", e.mac, e.sta_idx);
+            *dst = Some(e.mac);
+        }
+        let ev = RecoveryEvent {
+            crash,
+            recovery_count: aicbsp_recovery_count(),
+            replay_failures,
+            dropped_stas,
+        };
+        log::info!(target: "wireless::fdrv", "WiphyOpsImpl recover: done, replay_failures={}", replay_failures);
+        notify_recovered(&ev);
+        Ok(())
+    }
+
+    /// scan 的非阻塞版本：Future 在 SCANU_START_CFM 到达时完成，结果仍经 SCANU_RESULT_IND 上报
+    pub fn scan_async(&mut self, iface_id: InterfaceId) -> LmacCfmFuture<()> {
+        if let Err(e) = self.ensure_fw_alive() {
+            return LmacCfmFuture::failed(e, ScanuStartReq::CFM_ID, |_| Some(()));
+        }
+        log::info!(target: "wireless::fdrv", "WiphyOpsImpl scan_async iface_id={}", iface_id);
+        let req = ScanuStartReq { vif_idx: iface_id as u8, duration_us: 0 };
+        LmacCfmFuture::new(req.build().ok_or(-22), ScanuStartReq::CFM_ID, RWNX_80211_CMD_TIMEOUT_MS, |p| {
+            ScanuStartReq::decode_cfm(p).map(|_| ())
+        })
+    }
+
+    /// get_station 的非阻塞版本
+    pub fn get_station_async(&mut self, _iface_id: InterfaceId, mac: &[u8; 6]) -> LmacCfmFuture<StationInfo> {
+        if let Err(e) = self.ensure_fw_alive() {
+            return LmacCfmFuture::failed(e, MmGetStaInfoReq::CFM_ID, |_| None);
+        }
+        let Some(sta_idx) = self.lookup_sta_idx(mac) else {
+            return LmacCfmFuture::failed(-2, MmGetStaInfoReq::CFM_ID, |_| None);
+        };
+        let req = MmGetStaInfoReq { sta_idx };
+        LmacCfmFuture::new(req.build().ok_or(-22), MmGetStaInfoReq::CFM_ID, RWNX_80211_CMD_TIMEOUT_MS, |p| {
+            MmGetStaInfoReq::decode_cfm(p).map(|c| Self::fill_station_info_from_cfm(&c))
+        })
+    }
+
     fn fill_station_info_from_cfm(cfm: &crate::lmac_cmd::MmGetStaInfoCfm) -> StationInfo {
         use ieee80211::StationInfo;
         let mut info = StationInfo::default();
@@ -194,28 +444,36 @@ impl WiphyOps for WiphyOpsImpl {
             IfaceType::Monitor => MacVifType::Monitor,
             _ => MacVifType::Sta,
         };
-        let mac = [0u8; 6];
-        let msg = build_mm_add_if_req(vif_type, &mac, false);
-        let mut cfm_buf = [0u8; 8];
-        send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_ADD_IF_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let cfm = parse_mm_add_if_cfm(&cfm_buf).ok_or(-5)?;
+        self.ensure_fw_alive()?;
+        let cfm = send(&MmAddIfReq { vif_type, mac_addr: [0u8; 6], p2p: false })?;
         if cfm.status != 0 {
             return Err(-5);
         }
+        if (cfm.inst_nbr as usize) < MAX_VIF {
+            self.state.vif_type[cfm.inst_nbr as usize] = Some(iface_type);
+        }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl add_interface type={:?} => inst_nbr={}", iface_type, cfm.inst_nbr);
         Ok(cfm.inst_nbr as InterfaceId)
     }
 
     fn del_interface(&mut self, iface_id: InterfaceId) -> Result<(), i32> {
-        let msg = build_mm_remove_if_req(iface_id as u8);
-        send_lmac_cmd_and_wait_cfm(&msg, MM_REMOVE_IF_CFM, RWNX_80211_CMD_TIMEOUT_MS)?;
+        self.ensure_fw_alive()?;
+        send(&MmRemoveIfReq { inst_nbr: iface_id as u8 })?;
+        let vif_idx = iface_id as usize;
+        if vif_idx < MAX_VIF {
+            self.state.vif_type[vif_idx] = None;
+            self.state.key_hw[vif_idx] = [None; MAX_KEYS_PER_VIF];
+            self.state.key_data[vif_idx] = [None; MAX_KEYS_PER_VIF];
+            self.state.ap_config[vif_idx] = None;
+            self.state.connection[vif_idx] = None;
+        }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl del_interface id={}", iface_id);
         Ok(())
     }
 
     fn scan(&mut self, iface_id: InterfaceId) -> Result<(), i32> {
-        let msg = build_scanu_start_req(iface_id as u8, 0).ok_or(-22)?;
-        send_lmac_cmd_and_wait_cfm(&msg, SCANU_START_CFM, RWNX_80211_CMD_TIMEOUT_MS)?;
+        self.ensure_fw_alive()?;
+        send(&ScanuStartReq { vif_idx: iface_id as u8, duration_us: 0 })?;
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl scan iface_id={} started, results via SCANU_RESULT_IND", iface_id);
         Ok(())
     }
@@ -226,13 +484,18 @@ impl WiphyOps for WiphyOpsImpl {
         ssid: &[u8],
         bssid: Option<&[u8; 6]>,
     ) -> Result<(), i32> {
-        let msg = build_sm_connect_req(iface_id as u8, ssid, bssid, None).ok_or(-22)?;
-        let mut cfm_buf = [0u8; 8];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, SM_CONNECT_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let status = parse_sm_connect_cfm(&cfm_buf[..n]).unwrap_or(0xff);
+        self.ensure_fw_alive()?;
+        let cfm = send(&SmConnectReq { vif_idx: iface_id as u8, ssid, bssid, chan_freq: None })?;
+        let status = cfm.status;
         // 与 LicheeRV rwnx_cfg80211_connect 一致：CO_OK=0 -> Ok; CO_BUSY=8 -> -EINPROGRESS; CO_OP_IN_PROGRESS=9 -> -EALREADY; 其它 -> -EIO
         match status {
             0 => {
+                if (iface_id as usize) < MAX_VIF {
+                    self.state.connection[iface_id as usize] = Some(ConnConfig {
+                        ssid: ReplayBytes::new(ssid),
+                        bssid: bssid.copied(),
+                    });
+                }
                 log::info!(target: "wireless::fdrv", "WiphyOpsImpl connect iface_id={} ssid_len={}, result via SM_CONNECT_IND", iface_id, ssid.len());
                 Ok(())
             }
@@ -243,19 +506,25 @@ impl WiphyOps for WiphyOpsImpl {
     }
 
     fn disconnect(&mut self, iface_id: InterfaceId) -> Result<(), i32> {
-        let msg = build_sm_disconnect_req(iface_id as u8, 3);
-        send_lmac_cmd_and_wait_cfm(&msg, SM_DISCONNECT_CFM, RWNX_80211_CMD_TIMEOUT_MS)?;
+        self.ensure_fw_alive()?;
+        send(&SmDisconnectReq { vif_idx: iface_id as u8, reason_code: 3 })?;
+        if (iface_id as usize) < MAX_VIF {
+            self.state.connection[iface_id as usize] = None;
+        }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl disconnect iface_id={}", iface_id);
         Ok(())
     }
 
-    fn start_ap(&mut self, iface_id: InterfaceId, _ssid: &[u8], channel: u8) -> Result<(), i32> {
+    fn start_ap(&mut self, iface_id: InterfaceId, ssid: &[u8], channel: u8) -> Result<(), i32> {
+        self.ensure_fw_alive()?;
         let vif_idx = iface_id as usize;
         let basic_rates: [u8; 4] = [0x82, 0x84, 0x8b, 0x96]; // 1,2,5.5,11 Mbps basic
-        let msg = build_apm_start_req(iface_id as u8, channel, 100, &basic_rates).ok_or(-22)?;
-        let mut cfm_buf = [0u8; 32];
-        send_lmac_cmd_and_wait_cfm_with_buf(&msg, APM_START_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let cfm = parse_apm_start_cfm(&cfm_buf).ok_or(-5)?;
+        let cfm = send(&ApmStartReq {
+            vif_idx: iface_id as u8,
+            channel,
+            beacon_interval: 100,
+            basic_rates: &basic_rates,
+        })?;
         if cfm.status != 0 {
             log::warn!(target: "wireless::fdrv", "WiphyOpsImpl start_ap APM_START_CFM status={}", cfm.status);
             return Err(-5);
@@ -268,48 +537,61 @@ impl WiphyOps for WiphyOpsImpl {
         }
         if vif_idx < MAX_VIF {
             self.state.current_channel[vif_idx] = Some(channel);
+            self.state.ap_config[vif_idx] = Some(ApConfig {
+                ssid: ReplayBytes::new(ssid),
+                channel,
+            });
         }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl start_ap iface_id={} ch={} bcmc_idx={}", iface_id, channel, cfm.bcmc_idx);
         Ok(())
     }
 
     fn stop_ap(&mut self, iface_id: InterfaceId) -> Result<(), i32> {
-        let msg = build_apm_stop_req(iface_id as u8);
-        send_lmac_cmd_and_wait_cfm(&msg, APM_STOP_CFM, RWNX_80211_CMD_TIMEOUT_MS)?;
+        self.ensure_fw_alive()?;
+        send(&ApmStopReq { vif_idx: iface_id as u8 })?;
+        if (iface_id as usize) < MAX_VIF {
+            self.state.ap_config[iface_id as usize] = None;
+        }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl stop_ap iface_id={}", iface_id);
         Ok(())
     }
 
     fn add_key(&mut self, iface_id: InterfaceId, key_index: u8, key_data: &[u8]) -> Result<(), i32> {
+        self.ensure_fw_alive()?;
         let vif_idx = iface_id as u8;
         let cipher = ieee80211::wlan_cipher_suite::CCMP;
         let mac_cipher = wlan_cipher_to_mac(cipher).ok_or(-22)?;
         let sta_idx = 0xFF;
-        let msg = build_mm_key_add_req(vif_idx, key_index, sta_idx, key_data, mac_cipher, false)
-            .ok_or(-22)?;
-        let mut cfm_buf = [0u8; 8];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_KEY_ADD_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let cfm = parse_mm_key_add_cfm(&cfm_buf[..n]).ok_or(-5)?;
+        let cfm = send(&MmKeyAddReq {
+            vif_idx,
+            key_idx: key_index,
+            sta_idx,
+            key: key_data,
+            cipher_suite: mac_cipher,
+            pairwise: false,
+        })?;
         if cfm.status != 0 {
             return Err(-5);
         }
         if (vif_idx as usize) < MAX_VIF && (key_index as usize) < MAX_KEYS_PER_VIF {
             self.state.key_hw[vif_idx as usize][key_index as usize] = Some(cfm.hw_key_idx);
+            self.state.key_data[vif_idx as usize][key_index as usize] = Some(ReplayBytes::new(key_data));
         }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl add_key iface_id={} idx={} hw_key_idx={}", iface_id, key_index, cfm.hw_key_idx);
         Ok(())
     }
 
     fn del_key(&mut self, iface_id: InterfaceId, key_index: u8) -> Result<(), i32> {
+        self.ensure_fw_alive()?;
         let vif_idx = iface_id as usize;
         let hw_key_idx = if vif_idx < MAX_VIF && (key_index as usize) < MAX_KEYS_PER_VIF {
             self.state.key_hw[vif_idx][key_index as usize].ok_or(-2)?
         } else {
             return Err(-2);
         };
-        let msg = build_mm_key_del_req(hw_key_idx);
-        send_lmac_cmd_and_wait_cfm(&msg, MM_KEY_DEL_CFM, RWNX_80211_CMD_TIMEOUT_MS)?;
+        send(&MmKeyDelReq { hw_key_idx })?;
         self.state.key_hw[vif_idx][key_index as usize] = None;
+        self.state.key_data[vif_idx][key_index as usize] = None;
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl del_key iface_id={} idx={} hw_key_idx={}", iface_id, key_index, hw_key_idx);
         Ok(())
     }
@@ -343,20 +625,16 @@ impl WiphyOps for WiphyOpsImpl {
     }
 
     fn get_station(&mut self, _iface_id: InterfaceId, mac: &[u8; 6]) -> Result<StationInfo, i32> {
+        self.ensure_fw_alive()?;
         let sta_idx = self.lookup_sta_idx(mac).ok_or(-2)?;
-        let msg = build_mm_get_sta_info_req(sta_idx);
-        let mut cfm_buf = [0u8; 64];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_GET_STA_INFO_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let cfm = parse_mm_get_sta_info_cfm(&cfm_buf[..n]).ok_or(-5)?;
+        let cfm = send(&MmGetStaInfoReq { sta_idx })?;
         Ok(Self::fill_station_info_from_cfm(&cfm))
     }
 
     fn add_station(&mut self, iface_id: InterfaceId, mac: &[u8; 6]) -> Result<(), i32> {
-        let msg = build_mm_sta_add_req(iface_id as u8, mac);
-        let mut cfm_buf = [0u8; 8];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_STA_ADD_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let status = parse_mm_sta_add_cfm(&cfm_buf[..n]).unwrap_or(0xff);
-        if status != 0 {
+        self.ensure_fw_alive()?;
+        let cfm = send(&MmStaAddReq { inst_nbr: iface_id as u8, mac: *mac })?;
+        if cfm.status != 0 {
             return Err(-5);
         }
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl add_station iface_id={} mac={:02x?}", iface_id, mac);
@@ -364,11 +642,9 @@ impl WiphyOps for WiphyOpsImpl {
     }
 
     fn del_station(&mut self, _iface_id: InterfaceId, sta_idx: u8) -> Result<(), i32> {
-        let msg = build_mm_sta_del_req(sta_idx);
-        let mut cfm_buf = [0u8; 8];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_STA_DEL_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let status = parse_mm_sta_del_cfm(&cfm_buf[..n]).unwrap_or(0xff);
-        if status != 0 {
+        self.ensure_fw_alive()?;
+        let cfm = send(&MmStaDelReq { sta_idx })?;
+        if cfm.status != 0 {
             return Err(-5);
         }
         Ok(())
@@ -385,10 +661,8 @@ impl WiphyOps for WiphyOpsImpl {
         } else {
             (power / 100) as i8
         };
-        let msg = build_mm_set_power_req(iface_id as u8, power_dbm);
-        let mut cfm_buf = [0u8; 8];
-        let n = send_lmac_cmd_and_wait_cfm_with_buf(&msg, MM_SET_POWER_CFM, RWNX_80211_CMD_TIMEOUT_MS, &mut cfm_buf)?;
-        let _cfm = parse_mm_set_power_cfm(&cfm_buf[..n]).ok_or(-5)?;
+        self.ensure_fw_alive()?;
+        send(&MmSetPowerReq { inst_nbr: iface_id as u8, power_dbm })?;
         self.state.last_tx_power_dbm[vif_idx] = Some(power_dbm);
         log::info!(target: "wireless::fdrv", "WiphyOpsImpl set_tx_power iface_id={} power_dbm={}", iface_id, power_dbm);
         Ok(())